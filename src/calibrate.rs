@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::{WappAnalyzer, WappCheckOptions, WappPage};
+
+/// A page of a labeled corpus, with the technologies it is known to use.
+#[derive(Debug, Clone)]
+pub struct WappLabeledPage<P> {
+    pub page: P,
+    pub techs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WappCalibrationCounts {
+    pub true_positives: u32,
+    pub false_positives: u32,
+    pub false_negatives: u32,
+}
+
+impl WappCalibrationCounts {
+    fn add_detection(&mut self, correct: bool) {
+        match correct {
+            true => self.true_positives += 1,
+            false => self.false_positives += 1,
+        }
+    }
+
+    /// Share of the detections that were right. `None` without detections.
+    pub fn precision(&self) -> Option<f64> {
+        let detected = self.true_positives + self.false_positives;
+        (detected > 0).then(|| self.true_positives as f64 / detected as f64)
+    }
+
+    /// Share of the technologies in use that were detected. `None` when none were in use.
+    pub fn recall(&self) -> Option<f64> {
+        let expected = self.true_positives + self.false_negatives;
+        (expected > 0).then(|| self.true_positives as f64 / expected as f64)
+    }
+}
+
+/// A confidence for the patterns of a technology's detector that would have matched its precision on the corpus.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WappConfidenceSuggestion {
+    pub tech_name: String,
+    pub detector: &'static str,
+    pub confidence: i32,
+}
+
+/// How well the analyzer did on a labeled corpus, see [`WappAnalyzer::calibrate`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WappCalibrationReport {
+    /// Counts per technology, for the technologies that were detected or in use.
+    pub techs: BTreeMap<String, WappCalibrationCounts>,
+    /// Counts per detector, as named in [`WappTech::pattern_counts`](crate::WappTech::pattern_counts), plus `implies`
+    /// for technologies only implied by others. A detection counts for every detector that had a pattern match; a
+    /// technology in use but not found by a detector counts as a false negative of the detector when the technology
+    /// has patterns for it, whether or not the page had input for it.
+    pub detectors: BTreeMap<&'static str, WappCalibrationCounts>,
+    /// Lower confidences for the detectors that gave false positives, sorted by technology name, then detector.
+    pub suggestions: Vec<WappConfidenceSuggestion>,
+}
+
+impl WappAnalyzer {
+    /// Checks every page of a labeled corpus with `options`, explaining the detections, and measures the precision and
+    /// recall of each technology and detector against the labels. Labels of technologies unknown to the analyzer are
+    /// ignored.
+    pub fn calibrate<P: WappPage>(
+        &self,
+        corpus: &[WappLabeledPage<P>],
+        options: &WappCheckOptions,
+    ) -> WappCalibrationReport {
+        let options = WappCheckOptions {
+            explain: true,
+            ..options.clone()
+        };
+        let mut report = WappCalibrationReport::default();
+        let mut tech_detectors = BTreeMap::<(String, &'static str), WappCalibrationCounts>::new();
+
+        for labeled in corpus {
+            let expected: BTreeSet<&str> = labeled
+                .techs
+                .iter()
+                .map(String::as_str)
+                .filter(|name| self.techs.contains_key(*name))
+                .collect();
+            let results = self.check_with_options(&labeled.page, &options);
+
+            for result in &results {
+                let correct = expected.contains(result.tech_name.as_str());
+                let mut detectors: BTreeSet<&'static str> = result
+                    .evidence
+                    .iter()
+                    .map(|e| match e.detector {
+                        "dom_properties" => "dom",
+                        detector => detector,
+                    })
+                    .collect();
+                if detectors.is_empty() {
+                    detectors.insert("implies");
+                }
+
+                report
+                    .techs
+                    .entry(result.tech_name.clone())
+                    .or_default()
+                    .add_detection(correct);
+                for &detector in &detectors {
+                    report
+                        .detectors
+                        .entry(detector)
+                        .or_default()
+                        .add_detection(correct);
+                    tech_detectors
+                        .entry((result.tech_name.clone(), detector))
+                        .or_default()
+                        .add_detection(correct);
+                }
+
+                if correct {
+                    let tech = &self.techs[&result.tech_name];
+                    for (detector, n) in tech.pattern_counts() {
+                        if n > 0 && !detectors.contains(detector) {
+                            report
+                                .detectors
+                                .entry(detector)
+                                .or_default()
+                                .false_negatives += 1;
+                        }
+                    }
+                }
+            }
+
+            for name in expected {
+                if results.iter().any(|r| r.tech_name == name) {
+                    continue;
+                }
+                report.techs.entry(name.into()).or_default().false_negatives += 1;
+                for (detector, n) in self.techs[name].pattern_counts() {
+                    if n > 0 {
+                        report
+                            .detectors
+                            .entry(detector)
+                            .or_default()
+                            .false_negatives += 1;
+                    }
+                }
+            }
+        }
+
+        report.suggestions = tech_detectors
+            .into_iter()
+            .filter(|(_, counts)| counts.false_positives > 0)
+            .map(|((tech_name, detector), counts)| WappConfidenceSuggestion {
+                tech_name,
+                detector,
+                confidence: (counts.precision().unwrap_or(0.0) * 100.0).round() as i32,
+            })
+            .collect();
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WappCalibrationCounts, WappConfidenceSuggestion, WappLabeledPage};
+    use crate::{test_dataset, WappCheckOptions, WappRawPage};
+
+    #[test]
+    fn test_calibrate() {
+        let techs = br#"{
+            "CMS": {"cats": [], "website": "", "url": "/cms/", "html": "<cms", "implies": "Lang"},
+            "Lang": {"cats": [], "website": ""},
+            "Widget": {"cats": [], "website": "", "html": "widget"}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+
+        let labeled = |url: &str, html: &str, techs: &[&str]| WappLabeledPage {
+            page: WappRawPage::new(Some(url), None, Some(html.as_bytes())),
+            techs: techs.iter().map(|t| t.to_string()).collect(),
+        };
+        let corpus = [
+            labeled("https://a.test/cms/", "<cms>", &["CMS", "Lang"]),
+            labeled("https://b.test/", "<cms> widget", &["CMS", "Widget"]),
+            labeled("https://c.test/", "a widget-like word", &["Unknown"]),
+            labeled("https://d.test/", "", &["Widget"]),
+        ];
+        let report = analyzer.calibrate(&corpus, &WappCheckOptions::default());
+
+        let counts = |tp, fp, fn_| WappCalibrationCounts {
+            true_positives: tp,
+            false_positives: fp,
+            false_negatives: fn_,
+        };
+        assert_eq!(report.techs["CMS"], counts(2, 0, 0));
+        assert_eq!(report.techs["Lang"], counts(1, 1, 0));
+        assert_eq!(report.techs["Widget"], counts(1, 1, 1));
+        assert_eq!(report.techs["Widget"].precision(), Some(0.5));
+        assert_eq!(report.detectors["url"], counts(1, 0, 1));
+        assert_eq!(report.detectors["html"], counts(3, 1, 1));
+        assert_eq!(report.detectors["implies"], counts(1, 1, 0));
+        assert_eq!(
+            report.suggestions,
+            [
+                WappConfidenceSuggestion {
+                    tech_name: "Lang".into(),
+                    detector: "implies",
+                    confidence: 50,
+                },
+                WappConfidenceSuggestion {
+                    tech_name: "Widget".into(),
+                    detector: "html",
+                    confidence: 50,
+                },
+            ]
+        );
+    }
+}
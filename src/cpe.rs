@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Error};
+use serde::Deserialize;
+
+use crate::{WappAnalyzer, WappCheckResult};
+
+/// Number of components in a CPE 2.3 formatted string, including the leading `cpe` and `2.3`.
+const CPE23_COMPONENTS: usize = 13;
+
+/// Splits a CPE 2.3 formatted string into its components, honouring `\:` escapes. Returns `None` if the string is not a
+/// well-formed CPE 2.3 name.
+pub(crate) fn split_cpe23(cpe: &str) -> Option<Vec<&str>> {
+    let mut parts = Vec::with_capacity(CPE23_COMPONENTS);
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in cpe.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ':' => {
+                parts.push(&cpe[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&cpe[start..]);
+
+    let valid = parts.len() == CPE23_COMPONENTS
+        && parts[0] == "cpe"
+        && parts[1] == "2.3"
+        && matches!(parts[2], "a" | "o" | "h")
+        && parts[3..].iter().all(|p| !p.is_empty());
+    valid.then_some(parts)
+}
+
+/// Sets the version component of a CPE 2.3 formatted string, quoting the characters that must be escaped in it.
+/// Whitespace becomes `_`, as in the NVD dictionary. Returns `None` if `cpe` is not a well-formed CPE 2.3 name.
+pub(crate) fn cpe23_with_version(cpe: &str, version: &str) -> Option<String> {
+    let mut parts = split_cpe23(cpe)?;
+    let mut quoted = String::with_capacity(version.len());
+    for c in version.trim().chars() {
+        if c.is_whitespace() {
+            quoted.push('_');
+        } else {
+            if !c.is_ascii_alphanumeric() && !matches!(c, '-' | '.' | '_') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+    }
+    if quoted.is_empty() {
+        return Some(cpe.into());
+    }
+    parts[5] = &quoted;
+    Some(parts.join(":"))
+}
+
+/// A local copy of the [NVD CPE dictionary](https://nvd.nist.gov/products/cpe), reduced to what is needed to validate
+/// the `cpe` field of technologies: which part/vendor/product triples exist and whether they are deprecated.
+#[derive(Debug, Default)]
+pub struct WappCpeDictionary {
+    /// `(part, vendor, product)` => whether every known name for it is deprecated.
+    products: HashMap<(String, String, String), bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NvdCpeResponse {
+    products: Vec<NvdCpeProduct>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NvdCpeProduct {
+    cpe: NvdCpe,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NvdCpe {
+    cpe_name: String,
+    #[serde(default)]
+    deprecated: bool,
+}
+
+impl WappCpeDictionary {
+    /// Loads one page of the NVD CPE API 2.0 (`/rest/json/cpes/2.0`) response. Call it for every downloaded page and
+    /// merge the results with [`extend`](Self::extend).
+    pub fn from_nvd_json(bytes: &[u8]) -> Result<Self, Error> {
+        let data = serde_json::from_slice::<NvdCpeResponse>(bytes)
+            .context("Failed to parse NVD CPE JSON from bytes")?;
+
+        let mut dict = Self::default();
+        dict.extend(
+            data.products
+                .iter()
+                .map(|p| (p.cpe.cpe_name.as_str(), p.cpe.deprecated)),
+        );
+        Ok(dict)
+    }
+
+    /// Adds CPE names with their deprecation status. Malformed names are ignored.
+    pub fn extend<'a, I>(&mut self, names: I)
+    where
+        I: IntoIterator<Item = (&'a str, bool)>,
+    {
+        for (name, deprecated) in names {
+            let Some(parts) = split_cpe23(name) else {
+                continue;
+            };
+            let key = (parts[2].into(), parts[3].into(), parts[4].into());
+            let entry = self.products.entry(key).or_insert(true);
+            *entry &= deprecated;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.products.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WappCpeIssueKind {
+    /// Not a well-formed CPE 2.3 name.
+    Invalid,
+    /// The product is not in the dictionary.
+    Unknown,
+    /// Every dictionary entry for the product is deprecated.
+    Deprecated,
+}
+
+#[derive(Debug)]
+pub struct WappCpeIssue {
+    pub tech_name: String,
+    pub cpe: String,
+    pub kind: WappCpeIssueKind,
+}
+
+impl WappAnalyzer {
+    /// The CPE 2.3 name of a detected technology, with the detected version if any, e.g.
+    /// `cpe:2.3:a:wordpress:wordpress:6.4.2:*:*:*:*:*:*:*` for feeding vulnerability scanners. Without a detected
+    /// version, the technology's `cpe` field is returned as is. `None` for technologies without a well-formed `cpe`.
+    pub fn cpe(&self, result: &WappCheckResult) -> Option<String> {
+        let cpe = self.techs.get(&result.tech_name)?.cpe.as_deref()?;
+        match &result.version {
+            Some(version) => cpe23_with_version(cpe, version),
+            None => split_cpe23(cpe).map(|_| cpe.into()),
+        }
+    }
+
+    /// Cross-checks the `cpe` field of every technology against `dict`. Issues are sorted by technology name.
+    pub fn check_cpes(&self, dict: &WappCpeDictionary) -> Vec<WappCpeIssue> {
+        let mut issues = Vec::new();
+
+        for tech in self.techs.values() {
+            let Some(cpe) = &tech.cpe else {
+                continue;
+            };
+            let kind = match split_cpe23(cpe) {
+                None => Some(WappCpeIssueKind::Invalid),
+                Some(parts) => {
+                    let key = (parts[2].into(), parts[3].into(), parts[4].into());
+                    match dict.products.get(&key) {
+                        None => Some(WappCpeIssueKind::Unknown),
+                        Some(true) => Some(WappCpeIssueKind::Deprecated),
+                        Some(false) => None,
+                    }
+                }
+            };
+            if let Some(kind) = kind {
+                issues.push(WappCpeIssue {
+                    tech_name: tech.name.clone(),
+                    cpe: cpe.clone(),
+                    kind,
+                });
+            }
+        }
+
+        issues.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cpe23_with_version, split_cpe23, WappCpeDictionary, WappCpeIssueKind};
+    use crate::{test_dataset, WappCheckResult};
+
+    #[test]
+    fn test_split_cpe23() {
+        let parts = split_cpe23("cpe:2.3:a:wordpress:wordpress:*:*:*:*:*:*:*:*").unwrap();
+        assert_eq!(parts[3], "wordpress");
+
+        let parts = split_cpe23(r"cpe:2.3:a:foo\:bar:baz:1.0:*:*:*:*:*:*:*").unwrap();
+        assert_eq!(parts[3], r"foo\:bar");
+
+        assert!(split_cpe23("cpe:/a:wordpress:wordpress").is_none());
+        assert!(split_cpe23("cpe:2.3:x:wordpress:wordpress:*:*:*:*:*:*:*:*").is_none());
+        assert!(split_cpe23("cpe:2.3:a:wordpress:wordpress:*").is_none());
+    }
+
+    #[test]
+    fn test_cpe_with_version() {
+        let base = "cpe:2.3:a:wordpress:wordpress:*:*:*:*:*:*:*:*";
+        assert_eq!(
+            cpe23_with_version(base, "6.4.2").unwrap(),
+            "cpe:2.3:a:wordpress:wordpress:6.4.2:*:*:*:*:*:*:*"
+        );
+        assert_eq!(
+            cpe23_with_version(base, "1.0 rc1+build:2").unwrap(),
+            r"cpe:2.3:a:wordpress:wordpress:1.0_rc1\+build\:2:*:*:*:*:*:*:*"
+        );
+        assert_eq!(cpe23_with_version(base, " ").unwrap(), base);
+        assert!(cpe23_with_version("cpe:2.3:a:broken", "1.0").is_none());
+
+        let techs = br#"{
+            "WordPress": {"cats": [], "website": "", "cpe": "cpe:2.3:a:wordpress:wordpress:*:*:*:*:*:*:*:*"},
+            "None": {"cats": [], "website": ""}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let result = |name: &str, version: Option<&str>| WappCheckResult {
+            tech_name: name.into(),
+            confidence: 100,
+            version: version.map(Into::into),
+            version_captures: Vec::new(),
+            version_candidates: Vec::new(),
+            sources: Vec::new(),
+            evidence: Vec::new(),
+        };
+        assert_eq!(
+            analyzer.cpe(&result("WordPress", Some("6.4.2"))).unwrap(),
+            "cpe:2.3:a:wordpress:wordpress:6.4.2:*:*:*:*:*:*:*"
+        );
+        assert_eq!(analyzer.cpe(&result("WordPress", None)).unwrap(), base);
+        assert!(analyzer.cpe(&result("None", Some("1.0"))).is_none());
+    }
+
+    #[test]
+    fn test_check_cpes() {
+        let techs = br#"{
+            "Valid": {"cats": [], "website": "", "cpe": "cpe:2.3:a:valid:valid:*:*:*:*:*:*:*:*"},
+            "Old": {"cats": [], "website": "", "cpe": "cpe:2.3:a:old:old:*:*:*:*:*:*:*:*"},
+            "Missing": {"cats": [], "website": "", "cpe": "cpe:2.3:a:missing:missing:*:*:*:*:*:*:*:*"},
+            "Broken": {"cats": [], "website": "", "cpe": "cpe:2.3:a:broken"},
+            "None": {"cats": [], "website": ""}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+
+        let dict = WappCpeDictionary::from_nvd_json(
+            br#"{"products": [
+                {"cpe": {"cpeName": "cpe:2.3:a:valid:valid:1.0:*:*:*:*:*:*:*", "deprecated": false}},
+                {"cpe": {"cpeName": "cpe:2.3:a:valid:valid:0.9:*:*:*:*:*:*:*", "deprecated": true}},
+                {"cpe": {"cpeName": "cpe:2.3:a:old:old:1.0:*:*:*:*:*:*:*", "deprecated": true}}
+            ]}"#,
+        )
+        .unwrap();
+
+        let issues: Vec<_> = analyzer
+            .check_cpes(&dict)
+            .into_iter()
+            .map(|i| (i.tech_name, i.kind))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                ("Broken".into(), WappCpeIssueKind::Invalid),
+                ("Missing".into(), WappCpeIssueKind::Unknown),
+                ("Old".into(), WappCpeIssueKind::Deprecated),
+            ]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::{Context, Error};
+
+use crate::{SharedAnalyzer, WappAnalyzer, WappLoadOptions, WappTech};
+
+impl WappAnalyzer {
+    /// Adds the technologies of a technologies file (upstream schema) on top of the dataset, replacing any of the same
+    /// name, and returns their names. They are kept as written, so the custom overlay can be exported again with
+    /// [`export_custom_techs`](Self::export_custom_techs) for review, versioning or sharing.
+    pub fn add_custom_techs(&mut self, bytes: &[u8]) -> Result<Vec<String>, Error> {
+        self.add_custom_techs_with_options(bytes, &WappLoadOptions::default())
+    }
+
+    pub fn add_custom_techs_with_options(
+        &mut self,
+        bytes: &[u8],
+        options: &WappLoadOptions,
+    ) -> Result<Vec<String>, Error> {
+        let techs =
+            WappTech::load_from_bytes(bytes, options).context("Loading custom technologies")?;
+        let raw = serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(bytes)
+            .context("Failed to parse JSON from bytes")?;
+
+        let mut names: Vec<String> = techs.keys().cloned().collect();
+        names.sort();
+        for (name, tech) in techs {
+            let replaced = self.techs.insert(name.clone(), tech);
+            if let Some(replaced) = replaced.filter(|_| !self.custom_techs.contains_key(&name)) {
+                self.shadowed_techs.insert(name, replaced);
+            }
+        }
+        self.custom_techs.extend(raw);
+        Ok(names)
+    }
+
+    /// Removes a technology added with [`add_custom_techs`](Self::add_custom_techs). Technologies of the dataset are
+    /// left alone: one the custom technology replaced is restored.
+    pub fn remove_custom_tech(&mut self, name: &str) -> Option<WappTech> {
+        self.custom_techs.remove(name)?;
+        let removed = self.techs.remove(name);
+        if let Some(shadowed) = self.shadowed_techs.remove(name) {
+            self.techs.insert(name.into(), shadowed);
+        }
+        removed
+    }
+
+    /// Names of the technologies added with [`add_custom_techs`](Self::add_custom_techs), in alphabetical order.
+    pub fn custom_tech_names(&self) -> impl Iterator<Item = &str> {
+        self.custom_techs.keys().map(String::as_str)
+    }
+
+    /// The custom technologies as a technologies file in the upstream schema, which
+    /// [`add_custom_techs`](Self::add_custom_techs) can import again.
+    pub fn export_custom_techs(&self) -> Result<Vec<u8>, Error> {
+        serde_json::to_vec_pretty(&self.custom_techs).context("Failed to serialize JSON")
+    }
+}
+
+impl SharedAnalyzer {
+    /// Same as [`WappAnalyzer::add_custom_techs`], without pausing checks. See [`update`](Self::update).
+    pub fn add_custom_techs(&self, bytes: &[u8]) -> Result<Vec<String>, Error> {
+        self.update(|analyzer| analyzer.add_custom_techs(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{WappAnalyzer, WappRawPage};
+
+    #[test]
+    fn test_custom_techs_round_trip() {
+        let custom = br#"{
+            "Internal CMS": {"cats": [1], "website": "https://intranet.test", "html": "<meta name=\"internal-cms\""},
+            "Test URL": {"cats": [1], "website": "", "url": "^https://override\\.test/"}
+        }"#;
+        let mut analyzer = WappAnalyzer::new_test();
+        assert_eq!(
+            analyzer.add_custom_techs(custom).unwrap(),
+            ["Internal CMS", "Test URL"]
+        );
+        assert!(analyzer
+            .add_custom_techs(b"{\"Bad\": {\"cats\": \"x\"}}")
+            .is_err());
+        assert!(analyzer.remove_custom_tech("Test Implied").is_none());
+
+        let exported = analyzer.export_custom_techs().unwrap();
+        let mut imported = WappAnalyzer::new_test();
+        imported.add_custom_techs(&exported).unwrap();
+        assert_eq!(
+            imported.custom_tech_names().collect::<Vec<_>>(),
+            ["Internal CMS", "Test URL"]
+        );
+
+        let page = WappRawPage::new(
+            Some("https://override.test/"),
+            None,
+            Some(br#"<meta name="internal-cms" content="">"#),
+        );
+        let mut names: Vec<_> = imported
+            .check(&page)
+            .into_iter()
+            .map(|r| r.tech_name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["Internal CMS", "Test URL"]);
+
+        let dataset_url = WappAnalyzer::new_test().techs["Test URL"].url[0]
+            .inner
+            .as_str()
+            .to_string();
+        assert!(imported.remove_custom_tech("Test URL").is_some());
+        assert_eq!(
+            imported.techs["Test URL"].url[0].inner.as_str(),
+            dataset_url
+        );
+        assert!(imported.remove_custom_tech("Test URL").is_none());
+        assert!(imported.remove_custom_tech("Internal CMS").is_some());
+        assert!(!imported.techs.contains_key("Internal CMS"));
+        assert_eq!(imported.custom_tech_names().count(), 0);
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    WappAnalyzer, WappLoadOptions, WappTech, WappTechCategory, WappTechGroup, WappTechPricing,
+};
+
+/// A technology defined in code or loaded from elsewhere than the upstream JSON files, for
+/// [`WappAnalyzer::from_definitions`]. Patterns use the dataset syntax, tags included, e.g.
+/// `^nginx(?:/([\d.]+))?\;version:\1`; keyed patterns map e.g. a header name to its patterns.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WappTechDefinition {
+    pub name: String,
+    pub cats: Vec<i32>,
+    pub website: String,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    pub cpe: Option<String>,
+    pub saas: Option<bool>,
+    pub oss: Option<bool>,
+    pub pricing: Vec<WappTechPricing>,
+    pub cert_issuer: Option<String>,
+    pub implies: Vec<String>,
+    pub requires: Vec<String>,
+    pub requires_category: Vec<i32>,
+    pub excludes: Vec<String>,
+    pub url: Vec<String>,
+    pub html: Vec<String>,
+    pub text: Vec<String>,
+    pub css: Vec<String>,
+    pub script_src: Vec<String>,
+    pub scripts: Vec<String>,
+    pub headers: BTreeMap<String, Vec<String>>,
+    pub cookies: BTreeMap<String, Vec<String>>,
+    pub meta: BTreeMap<String, Vec<String>>,
+    pub js: BTreeMap<String, Vec<String>>,
+    pub dns: BTreeMap<String, Vec<String>>,
+    pub probe: BTreeMap<String, Vec<String>>,
+    pub dom: Vec<WappDomDefinition>,
+}
+
+/// A `dom` pattern of a [`WappTechDefinition`]. With neither text, attributes nor properties, the selector matching
+/// any element is enough.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WappDomDefinition {
+    pub selector: String,
+    pub text: Option<String>,
+    pub attributes: BTreeMap<String, Vec<String>>,
+    pub properties: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WappCategoryDefinition {
+    pub id: i32,
+    pub name: String,
+    pub priority: i32,
+    pub groups: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WappGroupDefinition {
+    pub id: i32,
+    pub name: String,
+}
+
+impl WappAnalyzer {
+    /// Builds an analyzer from definitions made in code, e.g. generated or read from a database, rather than from
+    /// upstream JSON files. Later definitions of the same technology, category or group replace earlier ones.
+    pub fn from_definitions(
+        techs: Vec<WappTechDefinition>,
+        cats: Vec<WappCategoryDefinition>,
+        groups: Vec<WappGroupDefinition>,
+    ) -> Result<Self, Error> {
+        Self::from_definitions_with_options(techs, cats, groups, &WappLoadOptions::default())
+    }
+
+    pub fn from_definitions_with_options(
+        techs: Vec<WappTechDefinition>,
+        cats: Vec<WappCategoryDefinition>,
+        groups: Vec<WappGroupDefinition>,
+        options: &WappLoadOptions,
+    ) -> Result<Self, Error> {
+        let tech_map = techs
+            .into_iter()
+            .map(|def| (def.name.clone(), WappTech::from_definition(def, options)))
+            .collect();
+
+        Ok(Self {
+            groups: groups
+                .into_iter()
+                .map(|g| {
+                    (
+                        g.id,
+                        WappTechGroup {
+                            id: g.id,
+                            name: g.name,
+                        },
+                    )
+                })
+                .collect(),
+            cats: cats
+                .into_iter()
+                .map(|c| {
+                    let cat = WappTechCategory {
+                        id: c.id,
+                        groups: c.groups,
+                        name: c.name,
+                        priority: c.priority,
+                    };
+                    (c.id, cat)
+                })
+                .collect(),
+            techs: tech_map,
+            ..Self::new_empty()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{
+        WappCategoryDefinition, WappDomDefinition, WappGroupDefinition, WappTechDefinition,
+    };
+    use crate::WappAnalyzer;
+
+    #[test]
+    fn test_from_definitions() {
+        let server = WappTechDefinition {
+            name: "Server".into(),
+            cats: vec![1],
+            headers: BTreeMap::from([("Server".into(), vec![r"^Srv/([\d.]+)\;version:\1".into()])]),
+            implies: vec!["Lang".into()],
+            ..Default::default()
+        };
+        let widget = WappTechDefinition {
+            name: "Widget".into(),
+            html: vec!["<widget".into()],
+            dom: vec![WappDomDefinition {
+                selector: "#widget".into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let lang = WappTechDefinition {
+            name: "Lang".into(),
+            oss: Some(true),
+            ..Default::default()
+        };
+        let analyzer = WappAnalyzer::from_definitions(
+            vec![server, widget, lang],
+            vec![WappCategoryDefinition {
+                id: 1,
+                name: "Web servers".into(),
+                priority: 8,
+                groups: vec![7],
+            }],
+            vec![WappGroupDefinition {
+                id: 7,
+                name: "Servers".into(),
+            }],
+        )
+        .unwrap();
+        assert_eq!(analyzer.techs.len(), 3);
+        assert_eq!(analyzer.cats[&1].groups, [7]);
+        assert_eq!(analyzer.groups[&7].name, "Servers");
+
+        let headers = [("Server".to_string(), "Srv/2.0".to_string())];
+        let mut results = analyzer.analyze(Some("https://a/"), Some(&headers), Some(b"<widget>"));
+        results.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+        let results: Vec<_> = results
+            .iter()
+            .map(|r| (r.tech_name.as_str(), r.version.as_deref()))
+            .collect();
+        #[cfg(feature = "http")]
+        assert_eq!(
+            results,
+            [("Lang", None), ("Server", Some("2.0")), ("Widget", None)]
+        );
+        #[cfg(not(feature = "http"))]
+        assert_eq!(results, [("Widget", None)]);
+    }
+
+    #[test]
+    fn test_from_definitions_patterns() {
+        let dom = |attribute: &str, pattern: &str| WappDomDefinition {
+            selector: "#app".into(),
+            attributes: BTreeMap::from([(attribute.into(), vec![pattern.into()])]),
+            ..Default::default()
+        };
+        let app = WappTechDefinition {
+            name: "App".into(),
+            html: vec!["(?=lookahead)".into(), "<app-root".into()],
+            dom: vec![
+                dom("data-a", "^a$"),
+                dom("data-b", r"^b-([\d.]+)$\;version:\1"),
+            ],
+            ..Default::default()
+        };
+        let analyzer = WappAnalyzer::from_definitions(vec![app], vec![], vec![]).unwrap();
+        let tech = &analyzer.techs["App"];
+        assert_eq!(tech.html.len(), 1);
+        assert_eq!(tech.dropped_patterns.len(), 1);
+        assert_eq!(tech.dropped_patterns[0].field, "html");
+
+        #[cfg(feature = "scraper")]
+        {
+            use crate::WappRawPage;
+
+            assert_eq!(tech.dom.len(), 2);
+            let page = WappRawPage::new(None, None, Some(br#"<div id="app" data-b="b-2.1">"#));
+            let results = analyzer.check(&page);
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].version.as_deref(), Some("2.1"));
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    fmt,
+    ops::{BitAnd, BitOr, BitOrAssign, Not},
+};
+
+/// A set of detectors, for running only some of them with [`WappCheckOptions::detectors`], e.g. only the cheap ones in
+/// high-throughput pipelines. Combine kinds with `|`.
+///
+/// [`WappCheckOptions::detectors`]: crate::WappCheckOptions::detectors
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WappDetectorKinds(u32);
+
+impl WappDetectorKinds {
+    pub const URL: Self = Self(1 << 0);
+    pub const CERT_ISSUER: Self = Self(1 << 1);
+    pub const DNS: Self = Self(1 << 2);
+    pub const JS: Self = Self(1 << 3);
+    pub const HEADERS: Self = Self(1 << 4);
+    pub const COOKIES: Self = Self(1 << 5);
+    pub const META: Self = Self(1 << 6);
+    pub const SCRIPT_SRC: Self = Self(1 << 7);
+    pub const SCRIPTS: Self = Self(1 << 8);
+    pub const PROBE: Self = Self(1 << 9);
+    pub const CSS: Self = Self(1 << 10);
+    /// `dom` patterns, including their `properties`.
+    pub const DOM: Self = Self(1 << 11);
+    pub const HTML: Self = Self(1 << 12);
+    pub const TEXT: Self = Self(1 << 13);
+
+    pub const NONE: Self = Self(0);
+    pub const ALL: Self = Self((1 << 14) - 1);
+    /// The detectors that only look at the response head and a few tags, as run by
+    /// [`quick_check`](crate::WappAnalyzer::quick_check). Meta tags and script URLs still need the DOM of a
+    /// [`WappRawPage`](crate::WappRawPage), which it parses on first use.
+    pub const CHEAP: Self = Self(
+        Self::URL.0
+            | Self::CERT_ISSUER.0
+            | Self::HEADERS.0
+            | Self::COOKIES.0
+            | Self::META.0
+            | Self::SCRIPT_SRC.0,
+    );
+
+    const NAMES: [(Self, &'static str); 14] = [
+        (Self::URL, "url"),
+        (Self::CERT_ISSUER, "cert_issuer"),
+        (Self::DNS, "dns"),
+        (Self::JS, "js"),
+        (Self::HEADERS, "headers"),
+        (Self::COOKIES, "cookies"),
+        (Self::META, "meta"),
+        (Self::SCRIPT_SRC, "script_src"),
+        (Self::SCRIPTS, "scripts"),
+        (Self::PROBE, "probe"),
+        (Self::CSS, "css"),
+        (Self::DOM, "dom"),
+        (Self::HTML, "html"),
+        (Self::TEXT, "text"),
+    ];
+
+    /// Whether every detector of `other` is in the set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The kind of a detector named as in [`WappScanStats::detector_timings`](crate::WappScanStats::detector_timings),
+    /// e.g. `headers`. `dom_properties` is part of [`DOM`](Self::DOM).
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = if name == "dom_properties" {
+            "dom"
+        } else {
+            name
+        };
+        Self::NAMES
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(kind, _)| *kind)
+    }
+}
+
+impl Default for WappDetectorKinds {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl BitOr for WappDetectorKinds {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for WappDetectorKinds {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for WappDetectorKinds {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Not for WappDetectorKinds {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0 & Self::ALL.0)
+    }
+}
+
+/// Lists the detectors by name, e.g. `WappDetectorKinds(url | headers)`.
+impl fmt::Debug for WappDetectorKinds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = Self::NAMES
+            .iter()
+            .filter(|(kind, _)| self.contains(*kind))
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "WappDetectorKinds({})", names.join(" | "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WappDetectorKinds;
+
+    #[test]
+    fn test_detector_kinds() {
+        let kinds = WappDetectorKinds::URL | WappDetectorKinds::HEADERS;
+        assert!(kinds.contains(WappDetectorKinds::URL));
+        assert!(!kinds.contains(WappDetectorKinds::URL | WappDetectorKinds::HTML));
+        assert!(WappDetectorKinds::ALL.contains(WappDetectorKinds::CHEAP));
+        assert!((!WappDetectorKinds::ALL).is_empty());
+        assert_eq!(format!("{kinds:?}"), "WappDetectorKinds(url | headers)");
+        assert_eq!(
+            WappDetectorKinds::from_name("dom_properties"),
+            Some(WappDetectorKinds::DOM)
+        );
+        assert_eq!(WappDetectorKinds::from_name("xhr"), None);
+    }
+}
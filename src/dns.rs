@@ -0,0 +1,65 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Error};
+
+/// DNS record types that `dns` patterns can look at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WappDnsRecordType {
+    Txt,
+    Mx,
+    Ns,
+    Cname,
+    Soa,
+}
+
+impl WappDnsRecordType {
+    /// The record type as named in the dataset, e.g. `TXT`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Txt => "TXT",
+            Self::Mx => "MX",
+            Self::Ns => "NS",
+            Self::Cname => "CNAME",
+            Self::Soa => "SOA",
+        }
+    }
+}
+
+impl FromStr for WappDnsRecordType {
+    type Err = Error;
+
+    /// Parses a record type as named in the dataset, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "TXT" => Self::Txt,
+            "MX" => Self::Mx,
+            "NS" => Self::Ns,
+            "CNAME" => Self::Cname,
+            "SOA" => Self::Soa,
+            _ => bail!("Unknown DNS record type {s}"),
+        })
+    }
+}
+
+/// DNS records of the page's host, resolved by the host application. Records are given in their presentation format,
+/// e.g. `10 mx.example.com.` for MX records.
+#[derive(Debug, Clone, Default)]
+pub struct WappDnsRecords {
+    pub txt: Vec<String>,
+    pub mx: Vec<String>,
+    pub ns: Vec<String>,
+    pub cname: Vec<String>,
+    pub soa: Vec<String>,
+}
+
+impl WappDnsRecords {
+    pub fn get(&self, record_type: WappDnsRecordType) -> &[String] {
+        match record_type {
+            WappDnsRecordType::Txt => &self.txt,
+            WappDnsRecordType::Mx => &self.mx,
+            WappDnsRecordType::Ns => &self.ns,
+            WappDnsRecordType::Cname => &self.cname,
+            WappDnsRecordType::Soa => &self.soa,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use serde::Serialize;
+
+use crate::{WappAnalyzer, WappCheckResult};
+
+/// A check result together with the categories of its technology, see [`WappAnalyzer::enrich`].
+#[derive(Debug, Serialize)]
+pub struct WappEnrichedResult<'a> {
+    #[serde(flatten)]
+    pub result: &'a WappCheckResult,
+    /// Categories of the technology, in dataset order. Empty when the technology is unknown to the analyzer.
+    pub categories: Vec<WappResultCategory<'a>>,
+    /// CPE 2.3 name with the detected version, see [`WappAnalyzer::cpe`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpe: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WappResultCategory<'a> {
+    pub id: i32,
+    /// `None` when the dataset has no such category.
+    pub name: Option<&'a str>,
+    /// Names of the groups of the category that the dataset has.
+    pub groups: Vec<&'a str>,
+}
+
+impl WappAnalyzer {
+    /// Resolves the categories of a result's technology and their groups, and its CPE name, so consumers do not have
+    /// to look them up in [`techs`](Self::techs), [`cats`](Self::cats) and [`groups`](Self::groups) themselves.
+    pub fn enrich<'a>(&'a self, result: &'a WappCheckResult) -> WappEnrichedResult<'a> {
+        let cat_ids = self
+            .techs
+            .get(&result.tech_name)
+            .map_or(&[][..], |tech| &tech.cats);
+        let categories = cat_ids
+            .iter()
+            .map(|&id| {
+                let cat = self.cats.get(&id);
+                WappResultCategory {
+                    id,
+                    name: cat.map(|c| c.name.as_str()),
+                    groups: cat
+                        .into_iter()
+                        .flat_map(|c| &c.groups)
+                        .filter_map(|g| self.groups.get(g))
+                        .map(|g| g.name.as_str())
+                        .collect(),
+                }
+            })
+            .collect();
+        WappEnrichedResult {
+            result,
+            categories,
+            cpe: self.cpe(result),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{WappAnalyzer, WappCheckResult};
+
+    #[test]
+    fn test_enrich() {
+        let analyzer = WappAnalyzer::new_test();
+        let result = WappCheckResult {
+            tech_name: "Test Header".into(),
+            confidence: 100,
+            version: Some("1.5".into()),
+            version_captures: Vec::new(),
+            version_candidates: Vec::new(),
+            sources: Vec::new(),
+            evidence: Vec::new(),
+        };
+
+        let json = serde_json::to_value(analyzer.enrich(&result)).unwrap();
+        assert_eq!(json["tech_name"], "Test Header");
+        assert_eq!(json["version"], "1.5");
+        let cat = &analyzer.cats[&2];
+        assert_eq!(json["categories"][0]["id"], 2);
+        assert_eq!(json["categories"][0]["name"], cat.name.as_str());
+        assert_eq!(
+            json["categories"][0]["groups"].as_array().unwrap().len(),
+            cat.groups.len()
+        );
+
+        let unknown = WappCheckResult {
+            tech_name: "Unknown".into(),
+            confidence: 100,
+            version: None,
+            version_captures: Vec::new(),
+            version_candidates: Vec::new(),
+            sources: Vec::new(),
+            evidence: Vec::new(),
+        };
+        assert!(analyzer.enrich(&unknown).categories.is_empty());
+    }
+}
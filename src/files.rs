@@ -0,0 +1,181 @@
+use std::{
+    fmt::{self, Debug, Display},
+    fs::{self, File},
+    io::BufReader,
+    iter,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Error};
+
+use crate::{WappAnalyzer, WappLoadOptions, WappTech, WappTechCategory, WappTechGroup};
+
+/// A file that failed to load in [`WappAnalyzer::from_files_partial`].
+#[derive(Debug)]
+pub struct WappLoadError {
+    pub path: PathBuf,
+    pub error: Error,
+}
+
+/// Every error encountered while loading an analyzer leniently.
+#[derive(Debug, Default)]
+pub struct WappLoadReport {
+    pub errors: Vec<WappLoadError>,
+}
+
+impl WappLoadReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Combines all errors into one that lists every failed file, or `None` if nothing failed.
+    pub fn into_error(self) -> Option<Error> {
+        if self.is_ok() {
+            None
+        } else {
+            Some(Error::msg(self.to_string()))
+        }
+    }
+}
+
+impl Display for WappLoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to load {} file(s)", self.errors.len())?;
+        for e in &self.errors {
+            write!(f, "\n  {}: {:#}", e.path.to_string_lossy(), e.error)?;
+        }
+        Ok(())
+    }
+}
+
+impl WappAnalyzer {
+    pub fn from_dir<P: AsRef<Path>>(data_dir: P) -> Result<Self, Error> {
+        Self::from_dir_with_options(data_dir, &WappLoadOptions::default())
+    }
+
+    pub fn from_dir_with_options<P: AsRef<Path>>(
+        data_dir: P,
+        options: &WappLoadOptions,
+    ) -> Result<Self, Error> {
+        let (cat_file, group_file, tech_files) = Self::dir_files(data_dir.as_ref());
+        Self::from_files_with_options(cat_file, group_file, tech_files, options)
+    }
+
+    /// Same as [`from_dir`](Self::from_dir), but keeps going when files fail to load. See
+    /// [`from_files_partial`](Self::from_files_partial).
+    pub fn from_dir_partial<P: AsRef<Path>>(data_dir: P) -> (Self, WappLoadReport) {
+        let (cat_file, group_file, tech_files) = Self::dir_files(data_dir.as_ref());
+        Self::from_files_partial(cat_file, group_file, tech_files)
+    }
+
+    fn dir_files(path: &Path) -> (PathBuf, PathBuf, impl Iterator<Item = PathBuf> + '_) {
+        let cat_file = path.join("categories.json");
+        let group_file = path.join("groups.json");
+        let tech_files = iter::once('_')
+            .chain('a'..='z')
+            .map(|c| path.join(format!("technologies/{c}.json")));
+
+        (cat_file, group_file, tech_files)
+    }
+
+    /// Technology files are parsed while they are read, one entry at a time, so loading stays cheap in memory even for
+    /// very large files.
+    pub fn from_files<P, I>(cat_file: P, group_file: P, tech_files: I) -> Result<Self, Error>
+    where
+        P: AsRef<Path> + Debug,
+        I: Iterator<Item = P>,
+    {
+        Self::from_files_with_options(
+            cat_file,
+            group_file,
+            tech_files,
+            &WappLoadOptions::default(),
+        )
+    }
+
+    pub fn from_files_with_options<P, I>(
+        cat_file: P,
+        group_file: P,
+        tech_files: I,
+        options: &WappLoadOptions,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path> + Debug,
+        I: Iterator<Item = P>,
+    {
+        let cat_bytes = read_file(cat_file.as_ref())?;
+        let group_bytes = read_file(group_file.as_ref())?;
+
+        let mut analyzer = Self::from_bytes_with_options(&cat_bytes, &group_bytes, &[], options)?;
+        for (i, path) in tech_files.enumerate() {
+            let techs = WappTech::load_from_reader(open_file(path.as_ref())?, options)
+                .with_context(|| format!("Loading wapp technology (file #{i})"))?;
+            analyzer.techs.extend(techs);
+        }
+
+        Ok(analyzer)
+    }
+
+    /// Same as [`from_files`](Self::from_files), but does not stop at the first file that fails to load. Returns an
+    /// analyzer with everything that could be loaded, along with a report of every file that could not.
+    pub fn from_files_partial<P, I>(
+        cat_file: P,
+        group_file: P,
+        tech_files: I,
+    ) -> (Self, WappLoadReport)
+    where
+        P: AsRef<Path> + Debug,
+        I: Iterator<Item = P>,
+    {
+        fn load<P, T, F>(path: P, report: &mut WappLoadReport, f: F) -> Option<T>
+        where
+            P: AsRef<Path>,
+            F: FnOnce(&Path) -> Result<T, Error>,
+        {
+            let path = path.as_ref();
+            match f(path) {
+                Ok(x) => Some(x),
+                Err(error) => {
+                    report.errors.push(WappLoadError {
+                        path: path.to_path_buf(),
+                        error,
+                    });
+                    None
+                }
+            }
+        }
+
+        let mut report = WappLoadReport::default();
+        let mut analyzer = Self::new_empty();
+
+        if let Some(cats) = load(cat_file, &mut report, |p| {
+            WappTechCategory::load_from_bytes(&read_file(p)?)
+        }) {
+            analyzer.cats = cats;
+        }
+        if let Some(groups) = load(group_file, &mut report, |p| {
+            WappTechGroup::load_from_bytes(&read_file(p)?)
+        }) {
+            analyzer.groups = groups;
+        }
+        for path in tech_files {
+            if let Some(techs) = load(path, &mut report, |p| {
+                WappTech::load_from_reader(open_file(p)?, &WappLoadOptions::default())
+            }) {
+                analyzer.techs.extend(techs);
+            }
+        }
+
+        (analyzer, report)
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read(path).with_context(|| format!("Failed to open file {}", path.to_string_lossy()))
+}
+
+fn open_file(path: &Path) -> Result<BufReader<File>, Error> {
+    File::open(path)
+        .map(BufReader::new)
+        .with_context(|| format!("Failed to open file {}", path.to_string_lossy()))
+}
@@ -0,0 +1,109 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, Context, Error};
+
+use crate::WappTech;
+
+/// Loads technology icons from a directory, such as the `images/icons` directory of an upstream dataset checkout, for
+/// reports and UIs. Icons are read from disk on every call.
+#[derive(Debug, Clone)]
+pub struct WappIconStore {
+    dir: PathBuf,
+}
+
+impl WappIconStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The bytes of the technology's icon. Fails when the file is missing, or when the icon name is not a plain
+    /// filename, so that a dataset cannot make the store read outside its directory.
+    pub fn load(&self, tech: &WappTech) -> Result<Vec<u8>, Error> {
+        let name = &tech.icon;
+        if name.is_empty() || name.contains(['/', '\\']) || name == ".." {
+            bail!("Invalid icon filename {name:?} for {}", tech.name);
+        }
+        let path = self.dir.join(name);
+        fs::read(&path).with_context(|| format!("Failed to read icon {}", path.to_string_lossy()))
+    }
+
+    /// The technology's icon as a `data:` URI, e.g. for an `<img src>` in a self-contained HTML report. The media type
+    /// is taken from the file extension.
+    pub fn data_uri(&self, tech: &WappTech) -> Result<String, Error> {
+        let bytes = self.load(tech)?;
+        let extension = tech.icon.rsplit_once('.').map(|(_, ext)| ext);
+        let media_type = match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("svg") => "image/svg+xml",
+            Some("png") => "image/png",
+            Some("jpg" | "jpeg") => "image/jpeg",
+            Some("gif") => "image/gif",
+            Some("webp") => "image/webp",
+            Some("ico") => "image/x-icon",
+            _ => "application/octet-stream",
+        };
+        Ok(format!("data:{media_type};base64,{}", base64(&bytes)))
+    }
+}
+
+/// Standard base64 with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::{base64, WappIconStore};
+    use crate::test_dataset;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+    }
+
+    #[test]
+    fn test_icon_store() {
+        let techs = br#"{
+            "A": {"cats": [], "website": "", "icon": "A.svg"},
+            "B": {"cats": [], "website": ""},
+            "C": {"cats": [], "website": "", "icon": "../secret.png"}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        assert_eq!(analyzer.techs["B"].icon, "default.svg");
+
+        let dir = env::temp_dir().join(format!("wapp-icons-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("A.svg"), "<svg/>").unwrap();
+
+        let store = WappIconStore::new(&dir);
+        assert_eq!(
+            store.data_uri(&analyzer.techs["A"]).unwrap(),
+            "data:image/svg+xml;base64,PHN2Zy8+"
+        );
+        assert!(store.load(&analyzer.techs["B"]).is_err());
+        assert!(store.load(&analyzer.techs["C"]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::{WappAnalyzer, WappTech};
+
+/// The technologies every technology implies, directly or through others, see [`WappAnalyzer::implied_closure`].
+#[derive(Debug, Default)]
+pub(crate) struct ImpliesGraph {
+    closure: HashMap<String, Vec<(String, i32)>>,
+    cycles: Vec<String>,
+}
+
+impl ImpliesGraph {
+    fn new(techs: &HashMap<String, WappTech>) -> Self {
+        let mut graph = Self::default();
+        for name in techs.keys() {
+            let (implied, cyclic) = walk(techs, name);
+            if cyclic {
+                graph.cycles.push(name.clone());
+            }
+            if !implied.is_empty() {
+                graph.closure.insert(name.clone(), implied);
+            }
+        }
+        graph.cycles.sort();
+        graph
+    }
+
+    pub(crate) fn closure(&self, tech_name: &str) -> &[(String, i32)] {
+        self.closure.get(tech_name).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Finds the technologies implied by `name` with the highest confidence they can be implied with, and whether `name`
+/// implies itself. `implies` confidences are capped to 0..=100, so confidences only go down along a chain, following a
+/// cycle never raises one and the walk ends.
+fn walk(techs: &HashMap<String, WappTech>, name: &str) -> (Vec<(String, i32)>, bool) {
+    let mut best = HashMap::<&str, i32>::new();
+    let mut cyclic = false;
+    let mut stack = vec![(name, 100)];
+    while let Some((current, confidence)) = stack.pop() {
+        for implies in &techs[current].implies {
+            let implied = implies.inner.as_str();
+            if implied == name {
+                cyclic = true;
+                continue;
+            }
+            if !techs.contains_key(implied) {
+                continue;
+            }
+            let confidence = confidence * implies.confidence.clamp(0, 100) / 100;
+            if best.get(implied).is_some_and(|&c| c >= confidence) {
+                continue;
+            }
+            best.insert(implied, confidence);
+            stack.push((implied, confidence));
+        }
+    }
+
+    let mut implied: Vec<_> = best.into_iter().map(|(n, c)| (n.to_string(), c)).collect();
+    implied.sort();
+    (implied, cyclic)
+}
+
+/// Hash of the technology names and their `implies`, independent of the order of the map, so that a cached graph is
+/// rebuilt whenever [`WappAnalyzer::techs`] changed in a way that matters to it.
+fn fingerprint(techs: &HashMap<String, WappTech>) -> u64 {
+    techs.iter().fold(0, |acc: u64, (name, tech)| {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        for implies in &tech.implies {
+            implies.inner.hash(&mut hasher);
+            implies.confidence.hash(&mut hasher);
+        }
+        acc.wrapping_add(hasher.finish())
+    })
+}
+
+/// The last [`ImpliesGraph`] built, with the fingerprint of the technologies it was built from.
+#[derive(Debug, Default)]
+pub(crate) struct ImpliesCache(Mutex<Option<(u64, Arc<ImpliesGraph>)>>);
+
+impl Clone for ImpliesCache {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(
+            self.0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        ))
+    }
+}
+
+impl WappAnalyzer {
+    /// The implies graph of the current [`techs`](Self::techs), rebuilt when they changed since it was last built.
+    pub(crate) fn implies_graph(&self) -> Arc<ImpliesGraph> {
+        let fingerprint = fingerprint(&self.techs);
+        let mut cached = self
+            .implies
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match &*cached {
+            Some((f, graph)) if *f == fingerprint => graph.clone(),
+            _ => {
+                let graph = Arc::new(ImpliesGraph::new(&self.techs));
+                *cached = Some((fingerprint, graph.clone()));
+                graph
+            }
+        }
+    }
+
+    /// The technologies that `tech_name` implies, directly or through other technologies, with the confidence they are
+    /// implied with when `tech_name` is detected at 100%, sorted by name. Confidences are scaled along chains, and a
+    /// technology implied several ways keeps the highest. Does not include `tech_name` itself, even when it implies
+    /// itself through a cycle, nor technologies unknown to the analyzer.
+    ///
+    /// The closure of every technology is computed on first use and cached until [`techs`](Self::techs) change, so
+    /// checks look it up instead of walking the `implies` graph.
+    pub fn implied_closure(&self, tech_name: &str) -> Vec<(String, i32)> {
+        self.implies_graph().closure(tech_name).to_vec()
+    }
+
+    /// Names of the technologies that imply themselves through a cycle of `implies`, sorted. Cycles are cut when
+    /// resolving [`implied_closure`](Self::implied_closure), so they are only reported here.
+    pub fn implies_cycles(&self) -> Vec<String> {
+        self.implies_graph().cycles.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_dataset, WappAnalyzer, WappRawPage};
+
+    #[test]
+    fn test_implied_closure() {
+        let techs = br#"{
+            "CMS": {"cats": [], "website": "", "html": "cms", "implies": ["Plugin\\;confidence:50", "Lang"]},
+            "Plugin": {"cats": [], "website": "", "implies": ["Lang\\;confidence:50", "Cache\\;confidence:80"]},
+            "Lang": {"cats": [], "website": "", "implies": "Runtime"},
+            "Runtime": {"cats": [], "website": "", "implies": "Lang"},
+            "Cache": {"cats": [], "website": ""}
+        }"#;
+        let mut analyzer = test_dataset::with_techs(techs);
+
+        let closure = |analyzer: &WappAnalyzer, name| analyzer.implied_closure(name);
+        assert_eq!(
+            closure(&analyzer, "CMS"),
+            [
+                ("Cache".into(), 40),
+                ("Lang".into(), 100),
+                ("Plugin".into(), 50),
+                ("Runtime".into(), 100),
+            ]
+        );
+        assert_eq!(closure(&analyzer, "Lang"), [("Runtime".into(), 100)]);
+        assert!(analyzer.implied_closure("Cache").is_empty());
+        assert!(analyzer.implied_closure("Missing").is_empty());
+        assert_eq!(analyzer.implies_cycles(), ["Lang", "Runtime"]);
+
+        let page = WappRawPage::new(None, None, Some(b"cms"));
+        let results: Vec<_> = analyzer
+            .check(&page)
+            .into_iter()
+            .map(|r| (r.tech_name, r.confidence))
+            .collect();
+        assert_eq!(
+            results,
+            [
+                ("CMS".into(), 100),
+                ("Lang".into(), 100),
+                ("Runtime".into(), 100),
+                ("Plugin".into(), 50),
+                ("Cache".into(), 40),
+            ]
+        );
+
+        analyzer
+            .add_custom_techs(br#"{"Cache": {"cats": [], "website": "", "implies": "Lang"}}"#)
+            .unwrap();
+        assert_eq!(
+            closure(&analyzer, "Cache"),
+            [("Lang".into(), 100), ("Runtime".into(), 100)]
+        );
+
+        analyzer.techs.remove("Plugin");
+        assert_eq!(
+            closure(&analyzer, "CMS"),
+            [("Lang".into(), 100), ("Runtime".into(), 100)]
+        );
+    }
+
+    #[test]
+    fn test_implied_closure_caps_confidence() {
+        let techs = br#"{
+            "A": {"cats": [], "website": "", "implies": "B\\;confidence:200"},
+            "B": {"cats": [], "website": "", "implies": ["A\\;confidence:200", "C\\;confidence:2147483647"]},
+            "C": {"cats": [], "website": "", "implies": "A\\;confidence:-300"}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+
+        assert_eq!(
+            analyzer.implied_closure("A"),
+            [("B".into(), 100), ("C".into(), 100)]
+        );
+        assert_eq!(
+            analyzer.implied_closure("C"),
+            [("A".into(), 0), ("B".into(), 0)]
+        );
+        assert_eq!(analyzer.implies_cycles(), ["A", "B", "C"]);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{WappAnalyzer, WappCheckOptions, WappCheckResult, WappRawPage, WappTech};
+
+/// Detects technologies from access-log-style records (host, path and selected response headers) and aggregates the
+/// detections per host, for a passive inventory of a fleet without fetching anything. Only the URL, header and cookie
+/// patterns are run, and only for the technologies that have any.
+///
+/// URL patterns see records as `https://{host}{path}`.
+#[derive(Debug)]
+pub struct WappLogIngester<'a> {
+    analyzer: &'a WappAnalyzer,
+    techs: Vec<&'a WappTech>,
+    hosts: HashMap<String, HashMap<String, WappCheckResult>>,
+    records: u64,
+}
+
+impl<'a> WappLogIngester<'a> {
+    pub fn new(analyzer: &'a WappAnalyzer) -> Self {
+        let techs = analyzer
+            .techs
+            .values()
+            .filter(|tech| {
+                #[allow(unused_mut)]
+                let mut has_patterns = !tech.url.is_empty();
+                #[cfg(feature = "http")]
+                {
+                    has_patterns |= !tech.headers.is_empty();
+                }
+                #[cfg(feature = "cookie")]
+                {
+                    has_patterns |= !tech.cookies.is_empty();
+                }
+                has_patterns
+            })
+            .collect();
+        Self {
+            analyzer,
+            techs,
+            hosts: HashMap::new(),
+            records: 0,
+        }
+    }
+
+    /// Checks one record. `path` includes the query string, if logged; `Set-Cookie` headers are checked as cookies.
+    pub fn ingest(&mut self, host: &str, path: &str, headers: &[(String, String)]) {
+        self.records += 1;
+
+        let host = host.to_ascii_lowercase();
+        let sep = if path.starts_with('/') { "" } else { "/" };
+        let url = format!("https://{host}{sep}{path}");
+        let page = WappRawPage::new(Some(&url), Some(headers), None);
+        let results = self.analyzer.check_techs(
+            &page,
+            &WappCheckOptions::default(),
+            self.techs.iter().copied(),
+            false,
+        );
+        if results.is_empty() {
+            return;
+        }
+
+        let detections = self.hosts.entry(host).or_default();
+        for result in results {
+            match detections.get_mut(&result.tech_name) {
+                // Keep the most confident detection, and among equally confident ones the first with a version.
+                Some(seen)
+                    if seen.confidence > result.confidence
+                        || seen.confidence == result.confidence
+                            && (seen.version.is_some() || result.version.is_none()) => {}
+                Some(seen) => *seen = result,
+                None => {
+                    detections.insert(result.tech_name.clone(), result);
+                }
+            }
+        }
+    }
+
+    /// Number of records ingested so far.
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// The detections of every host with any, post-processed like the results of a check, keyed by lowercased host.
+    pub fn finish(self) -> BTreeMap<String, Vec<WappCheckResult>> {
+        self.hosts
+            .into_iter()
+            .map(|(host, detections)| {
+                let mut results: Vec<_> = detections.into_values().collect();
+                for transform in &self.analyzer.transforms {
+                    transform.apply(self.analyzer, &mut results);
+                }
+                results.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+                (host, results)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(all(feature = "http", feature = "cookie"))]
+    #[test]
+    fn test_ingest() {
+        use super::WappLogIngester;
+        use crate::WappAnalyzer;
+
+        let analyzer = WappAnalyzer::new_test();
+        let mut ingester = WappLogIngester::new(&analyzer);
+
+        let header = |k: &str, v: &str| vec![(k.to_string(), v.to_string())];
+        ingester.ingest("URL.test", "/", &[]);
+        ingester.ingest("url.test", "/a", &header("Server", "TestServer"));
+        ingester.ingest("url.test", "/b", &header("Server", "TestServer/1.5"));
+        ingester.ingest("url.test", "/c", &header("Server", "TestServer"));
+        ingester.ingest("other.test", "x?y", &header("Set-Cookie", "test_session=1"));
+        ingester.ingest("quiet.test", "/", &header("Server", "nginx"));
+        assert_eq!(ingester.records(), 6);
+
+        let hosts = ingester.finish();
+        let detections = |host: &str| -> Vec<_> {
+            hosts[host]
+                .iter()
+                .map(|r| (r.tech_name.as_str(), r.version.as_deref()))
+                .collect()
+        };
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(
+            detections("url.test"),
+            [("Test Header", Some("1.5")), ("Test URL", None)]
+        );
+        assert_eq!(detections("other.test"), [("Test Cookie", None)]);
+    }
+}
@@ -0,0 +1,141 @@
+use serde::Serialize;
+
+use crate::{WappAnalyzer, WappCheckResult, WappCheckResults, WappTech};
+
+/// The layer of a site's stack a technology belongs to, as presented by most technology dashboards. Derived from the
+/// names of the groups of the technology's categories, see [`WappLayer::from_group_name`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WappLayer {
+    /// Servers, CDNs, hosting and security.
+    Infrastructure,
+    /// CMSs, e-commerce platforms, frameworks and languages.
+    Platform,
+    /// Analytics, advertising and marketing automation.
+    Marketing,
+    /// Embedded third-party features such as chats, comments, maps and other utilities.
+    Widgets,
+    /// Technologies whose groups are unknown or not mapped to a layer.
+    Other,
+}
+
+impl WappLayer {
+    /// Maps an upstream group name to its layer, ignoring case. Unknown groups map to [`Other`](Self::Other).
+    pub fn from_group_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "servers" | "hosting" | "security" => Self::Infrastructure,
+            "content" | "web development" | "sales" => Self::Platform,
+            "marketing" | "analytics" | "advertising" => Self::Marketing,
+            "user generated content" | "communication" | "utilities" | "location" | "media" => {
+                Self::Widgets
+            }
+            _ => Self::Other,
+        }
+    }
+
+    /// The layer of a technology: that of the first group of its highest-priority category with a known layer. The
+    /// dataset ranks categories by ascending `priority`, so e.g. an analytics WordPress plugin is marketing rather
+    /// than platform.
+    pub fn of_tech(analyzer: &WappAnalyzer, tech: &WappTech) -> Self {
+        let mut cats: Vec<_> = tech
+            .cats
+            .iter()
+            .filter_map(|id| analyzer.cats.get(id))
+            .collect();
+        cats.sort_by_key(|cat| (cat.priority, cat.id));
+
+        cats.into_iter()
+            .flat_map(|cat| &cat.groups)
+            .filter_map(|id| analyzer.groups.get(id))
+            .map(|group| Self::from_group_name(&group.name))
+            .find(|&layer| layer != Self::Other)
+            .unwrap_or(Self::Other)
+    }
+}
+
+/// Check results bucketed by [`WappLayer`], keeping their order within each layer.
+#[derive(Debug, Default, Serialize)]
+pub struct WappLayerSummary<'a> {
+    pub infrastructure: Vec<&'a WappCheckResult>,
+    pub platform: Vec<&'a WappCheckResult>,
+    pub marketing: Vec<&'a WappCheckResult>,
+    pub widgets: Vec<&'a WappCheckResult>,
+    pub other: Vec<&'a WappCheckResult>,
+}
+
+impl<'a> WappLayerSummary<'a> {
+    /// Buckets results by the layer of their technology. Results whose technology is unknown to the analyzer are
+    /// dropped.
+    pub fn new<R>(analyzer: &WappAnalyzer, results: &'a R) -> Self
+    where
+        R: WappCheckResults + ?Sized,
+    {
+        let mut summary = Self::default();
+        for r in results.filter_by_tech(analyzer, |_| true) {
+            let layer = WappLayer::of_tech(analyzer, &analyzer.techs[&r.tech_name]);
+            summary.layer_mut(layer).push(r);
+        }
+        summary
+    }
+
+    pub fn layer(&self, layer: WappLayer) -> &[&'a WappCheckResult] {
+        match layer {
+            WappLayer::Infrastructure => &self.infrastructure,
+            WappLayer::Platform => &self.platform,
+            WappLayer::Marketing => &self.marketing,
+            WappLayer::Widgets => &self.widgets,
+            WappLayer::Other => &self.other,
+        }
+    }
+
+    fn layer_mut(&mut self, layer: WappLayer) -> &mut Vec<&'a WappCheckResult> {
+        match layer {
+            WappLayer::Infrastructure => &mut self.infrastructure,
+            WappLayer::Platform => &mut self.platform,
+            WappLayer::Marketing => &mut self.marketing,
+            WappLayer::Widgets => &mut self.widgets,
+            WappLayer::Other => &mut self.other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WappLayer;
+    use crate::{WappAnalyzer, WappCheckResults, WappRawPage};
+
+    #[test]
+    fn test_layers() {
+        assert_eq!(
+            WappLayer::from_group_name("Servers"),
+            WappLayer::Infrastructure
+        );
+        assert_eq!(
+            WappLayer::from_group_name("analytics"),
+            WappLayer::Marketing
+        );
+        assert_eq!(WappLayer::from_group_name("Unknown"), WappLayer::Other);
+
+        let analyzer = WappAnalyzer::new_test();
+        let page = WappRawPage::new(
+            Some("https://url.test/"),
+            None,
+            Some(b"<!-- test-html 3.1 -->"),
+        );
+        let results = analyzer.check(&page);
+        let summary = results.by_layer(&analyzer);
+        let names = |layer| -> Vec<_> {
+            let mut names: Vec<_> = summary
+                .layer(layer)
+                .iter()
+                .map(|r| r.tech_name.as_str())
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names(WappLayer::Platform), ["Test HTML", "Test URL"]);
+        assert_eq!(names(WappLayer::Infrastructure), ["Test Implied"]);
+        assert!(summary.other.is_empty());
+    }
+}
@@ -1,10 +1,97 @@
+mod calibrate;
+mod cpe;
+mod custom;
+mod definition;
+mod detector;
+mod dns;
+mod enrich;
+#[cfg(feature = "fs")]
+mod files;
+#[cfg(feature = "fs")]
+mod icons;
+mod implies;
+mod ingest;
+mod layers;
+mod lint;
+mod page;
+pub mod pattern;
+mod probe;
+mod profile;
+#[cfg(feature = "fs")]
+mod replay;
+mod report;
+mod robots;
+mod scanner;
+mod scope;
+mod selftest;
+mod shared;
+mod staged;
+mod stream;
 mod tech;
+mod telemetry;
+mod test_dataset;
+mod transform;
+mod trim;
+#[cfg(feature = "scraper")]
+mod url;
+mod version;
 
-use std::{collections::HashMap, fmt::Debug, fs, iter, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::{self, Display},
+    sync::Arc,
+    time::Instant,
+};
 
 use anyhow::{Context, Error};
-use serde::Deserialize;
-pub use tech::WappTech;
+pub use calibrate::{
+    WappCalibrationCounts, WappCalibrationReport, WappConfidenceSuggestion, WappLabeledPage,
+};
+pub use cpe::{WappCpeDictionary, WappCpeIssue, WappCpeIssueKind};
+pub use definition::{
+    WappCategoryDefinition, WappDomDefinition, WappGroupDefinition, WappTechDefinition,
+};
+pub use detector::WappDetectorKinds;
+pub use dns::{WappDnsRecordType, WappDnsRecords};
+pub use enrich::{WappEnrichedResult, WappResultCategory};
+#[cfg(feature = "fs")]
+pub use files::{WappLoadError, WappLoadReport};
+#[cfg(feature = "fs")]
+pub use icons::WappIconStore;
+use implies::ImpliesCache;
+pub use ingest::WappLogIngester;
+pub use layers::{WappLayer, WappLayerSummary};
+pub use lint::{lint_technologies, WappLintIssue, WappLintIssueKind};
+pub use page::WappRawPage;
+pub use probe::{WappAsyncProbeFetcher, WappProbeFetcher};
+pub use profile::WappScanProfile;
+#[cfg(feature = "fs")]
+pub use replay::WappReplayFetcher;
+pub use report::{WappScanContext, WappScanReport};
+pub use robots::WappRobotsDirectives;
+pub use scanner::{
+    WappChannelSink, WappFetcher, WappResultSink, WappScanEvent, WappScanner, WappScannerOptions,
+};
+pub use scope::WappScanScope;
+pub use selftest::{WappSelfTestDetector, WappSelfTestReport};
+use serde::{Deserialize, Serialize};
+pub use shared::SharedAnalyzer;
+pub use stream::WappStreamOptions;
+use tech::Explain;
+#[cfg(feature = "cookie")]
+pub use tech::WappCookieName;
+pub use tech::{slugify, WappEvidence, WappTech, WappTechInfo, WappTechPricing};
+use telemetry::{DetectionCounter, TelemetryHook};
+pub use telemetry::{WappDetectionCounts, WappScanStats, WappScanTelemetry};
+use transform::default_transforms;
+pub use transform::{
+    WappExcludesTransform, WappImpliesTransform, WappMinConfidenceTransform, WappRankTransform,
+    WappRedactVersionsTransform, WappRequiresTransform, WappResultTransform,
+};
+#[cfg(feature = "fs")]
+pub use trim::trim_dir;
+pub use trim::{trim_technologies, DETECTOR_FIELDS};
+pub use version::{WappDetectedVersion, WappVersion};
 
 #[cfg(feature = "cookie")]
 use cookie::Cookie;
@@ -15,14 +102,26 @@ use scraper::Html;
 #[cfg(feature = "http")]
 use http::HeaderMap;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct WappAnalyzer {
     pub groups: HashMap<i32, WappTechGroup>,
     pub cats: HashMap<i32, WappTechCategory>,
     pub techs: HashMap<String, WappTech>,
+    /// Version of the dataset the analyzer was loaded from, as set by the host. Reported to telemetry hooks.
+    pub dataset_version: Option<String>,
+    transforms: Vec<Arc<dyn WappResultTransform>>,
+    telemetry: Option<TelemetryHook>,
+    detection_counter: Option<DetectionCounter>,
+    /// Raw entries of the technologies added with [`add_custom_techs`](Self::add_custom_techs).
+    custom_techs: serde_json::Map<String, serde_json::Value>,
+    /// Technologies of the dataset replaced by custom ones, restored by
+    /// [`remove_custom_tech`](Self::remove_custom_tech).
+    shadowed_techs: HashMap<String, WappTech>,
+    /// See [`implied_closure`](Self::implied_closure).
+    implies: ImpliesCache,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct WappTechGroup {
     #[serde(skip_deserializing)]
@@ -30,7 +129,7 @@ pub struct WappTechGroup {
     pub name: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct WappTechCategory {
     #[serde(skip_deserializing)]
@@ -51,7 +150,7 @@ pub trait WappPage {
     }
 
     #[cfg(feature = "cookie")]
-    fn cookies(&self) -> Option<&[Cookie]> {
+    fn cookies(&self) -> Option<&[Cookie<'_>]> {
         None
     }
 
@@ -60,6 +159,62 @@ pub trait WappPage {
         None
     }
 
+    /// `(name, content)` of the `<meta>` tags of the page, with names lowercased. When `None`, they are read from
+    /// [`dom`](Self::dom) for every technology with meta patterns, so pages checked repeatedly should provide them.
+    #[cfg(feature = "scraper")]
+    fn meta(&self) -> Option<&[(String, String)]> {
+        None
+    }
+
+    /// Absolute URLs of the `<script src>` of the page, resolved against its base URL. When `None`, they are read from
+    /// [`dom`](Self::dom) for every technology with `scriptSrc` patterns.
+    #[cfg(feature = "scraper")]
+    fn script_src(&self) -> Option<&[String]> {
+        None
+    }
+
+    /// JavaScript source of the page: its inline scripts, plus the external ones if the host fetched them. When `None`,
+    /// the inline scripts are read from [`dom`](Self::dom) for every technology with `scripts` patterns.
+    #[cfg(feature = "scraper")]
+    fn scripts(&self) -> Option<&[String]> {
+        None
+    }
+
+    /// JavaScript properties of the elements matched by the `dom` selectors of the dataset, e.g. collected by a
+    /// headless browser. See [`WappAnalyzer::dom_property_selectors`] for what to collect.
+    #[cfg(feature = "scraper")]
+    fn dom_properties(&self) -> Option<&[WappElementProperties]> {
+        None
+    }
+
+    /// Issuer of the TLS certificate the page was served with, e.g. its organization name, for `certIssuer` patterns.
+    fn cert_issuer(&self) -> Option<&str> {
+        None
+    }
+
+    /// DNS records of the page's host, for `dns` patterns.
+    fn dns(&self) -> Option<&WappDnsRecords> {
+        None
+    }
+
+    /// A JSON snapshot of the page's `window`, e.g. produced by a headless browser, for `js` patterns. Only the
+    /// properties of [`WappAnalyzer::js_properties`] need to be included.
+    fn js(&self) -> Option<&serde_json::Value> {
+        None
+    }
+
+    /// `(path, body)` of the paths of [`WappAnalyzer::probe_paths`] that exist on the page's site. Provided by
+    /// [`WappAnalyzer::check_with_probes`].
+    fn probes(&self) -> Option<&[(String, String)]> {
+        None
+    }
+
+    /// Stylesheet text of the page: its `<style>` elements, plus the linked stylesheets if the host fetched them. When
+    /// `None`, the `<style>` elements are read from [`dom`](Self::dom) for every technology with `css` patterns.
+    fn css(&self) -> Option<&str> {
+        None
+    }
+
     fn html(&self) -> Option<&str> {
         None
     }
@@ -67,68 +222,184 @@ pub trait WappPage {
     fn text(&self) -> Option<&str> {
         None
     }
+
+    /// URL of the `<link rel="canonical">` of the page. Only used with
+    /// [`WappCheckOptions::check_canonical_url`].
+    fn canonical_url(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// A snapshot of the JavaScript properties of one element, for [`WappPage::dom_properties`].
+#[cfg(feature = "scraper")]
+#[derive(Debug, Clone, Default)]
+pub struct WappElementProperties {
+    /// The `dom` selector that selected the element, as written in the dataset.
+    pub selector: String,
+    /// `(name, value)` of the properties, with values stringified like `String(value)` does.
+    pub properties: Vec<(String, String)>,
 }
 
-#[derive(Debug)]
+/// Options that tune how a dataset is loaded.
+#[derive(Debug, Clone, Default)]
+pub struct WappLoadOptions {
+    /// Compile patterns case-sensitively. By default they are case-insensitive, as upstream evaluates them with the
+    /// JavaScript `i` flag; single patterns can still opt out with `(?-i)`.
+    pub case_sensitive: bool,
+    /// Load technologies with fields this crate does not know instead of failing, recording the fields in
+    /// [`WappTech::unknown_fields`]. Lets the crate keep working when the upstream schema gets ahead of it; see
+    /// [`WappAnalyzer::unknown_fields`] for what was skipped.
+    pub allow_unknown_fields: bool,
+}
+
+/// Options that tune how a page is checked.
+#[derive(Debug, Clone, Default)]
+pub struct WappCheckOptions {
+    /// Also report the technologies that were checked for but not detected, with the detectors that were tried, in
+    /// [`WappScanReport::absent`]. Lets compliance workflows show that a technology was looked for and not found.
+    pub report_absent: bool,
+    /// Also match `url` patterns against [`WappPage::canonical_url`]. Some platforms only reveal themselves in the
+    /// structure of their canonical URLs, e.g. when pages are served from a custom domain.
+    pub check_canonical_url: bool,
+    /// Fill in [`WappCheckResult::evidence`] with the patterns that matched, see [`WappTech::explain`]. For debugging
+    /// false positives; the evidence is recorded while checking, at the cost of copying what each pattern matched.
+    pub explain: bool,
+    /// Drop results below this confidence, after the transforms, so that implied technologies are kept only when
+    /// implied confidently enough. Disabled with 0. To filter every check of an analyzer, add a
+    /// [`WappMinConfidenceTransform`] to its [`transforms`](WappAnalyzer::transforms_mut) instead.
+    pub min_confidence: i32,
+    /// The detectors to run, all of them by default. E.g. [`WappDetectorKinds::CHEAP`] skips DNS, JS, probes, inline
+    /// scripts, stylesheets, DOM selectors and scanning the HTML and text, but still parses the DOM for meta tags and
+    /// script URLs.
+    pub detectors: WappDetectorKinds,
+    /// Highest confidence each detector can contribute to a technology, keyed by detector name as in
+    /// [`WappResultSource::detector`], e.g. `html` capped at 50 so that a noisy detector alone cannot confirm a
+    /// technology. Detectors without a cap contribute up to 100.
+    pub detector_caps: HashMap<String, i32>,
+}
+
+#[derive(Debug, Serialize)]
 pub struct WappCheckResult {
     pub tech_name: String,
     pub confidence: i32,
     pub version: Option<String>,
+    /// Raw capture groups of the pattern the version was taken from, indexed like `\N` in version templates. Lets
+    /// callers apply their own version normalization instead of the dataset's version template.
+    pub version_captures: Vec<Option<String>>,
+    /// Every distinct version found, `version` included, e.g. when a page loads two copies of a library. `version` is
+    /// the one from the most confident pattern.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub version_candidates: Vec<String>,
+    /// What each detector contributed to `confidence`, in the order they ran. Detector confidences add up to 100 at
+    /// most, while `implies` only raises the total to the confidence the technology is implied with.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<WappResultSource>,
+    /// The patterns that matched, when checked with [`WappCheckOptions::explain`]. Empty for technologies that were
+    /// only implied.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<WappEvidence>,
 }
 
-impl WappAnalyzer {
-    pub fn new_empty() -> Self {
-        Self {
-            cats: HashMap::new(),
-            groups: HashMap::new(),
-            techs: HashMap::new(),
-        }
+/// The contribution of one detector to the confidence of a [`WappCheckResult`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WappResultSource {
+    /// The detector, as named in [`WappScanStats::detector_timings`], or `implies` for the confidence implied by
+    /// other technologies.
+    pub detector: &'static str,
+    /// The confidence of the patterns of the detector that matched, after [`WappCheckOptions::detector_caps`].
+    pub confidence: i32,
+}
+
+/// A technology that was checked for but not detected.
+#[derive(Debug, Clone, Serialize)]
+pub struct WappAbsentTech {
+    pub tech_name: String,
+    /// The detectors that had both patterns for the technology and input from the page, as named in
+    /// [`WappScanStats::detector_timings`]. Empty when the page gave none of them anything to match.
+    pub detectors: Vec<&'static str>,
+}
+
+/// Filters check results by the metadata of the detected technologies, e.g. for cost-analysis or open-source inventory
+/// reports. Results whose technology is unknown to the analyzer are dropped.
+pub trait WappCheckResults {
+    fn filter_by_tech<F>(&self, analyzer: &WappAnalyzer, f: F) -> Vec<&WappCheckResult>
+    where
+        F: Fn(&WappTech) -> bool;
+
+    /// Results keyed by the slug of their technology name (see [`slugify`]), in a stable order. This is how the
+    /// upstream ecosystem keys technologies, so serializing the map gives integrators the same identifiers.
+    fn by_slug(&self) -> BTreeMap<String, &WappCheckResult>;
+
+    /// Only results for paid products. Always empty with the `slim-metadata` feature.
+    fn only_paid(&self, analyzer: &WappAnalyzer) -> Vec<&WappCheckResult> {
+        self.filter_by_tech(analyzer, WappTech::is_paid)
     }
 
-    pub fn from_dir<P: AsRef<Path>>(data_dir: P) -> Result<Self, Error> {
-        let path: &Path = data_dir.as_ref();
+    /// Only results for open-source technologies.
+    fn only_oss(&self, analyzer: &WappAnalyzer) -> Vec<&WappCheckResult> {
+        self.filter_by_tech(analyzer, WappTech::is_oss)
+    }
 
-        let cat_file = path.join("categories.json");
-        let group_file = path.join("groups.json");
-        let tech_files = iter::once('_')
-            .chain('a'..='z')
-            .map(|c| path.join(format!("technologies/{c}.json")));
+    /// Only results for technologies offered as SaaS.
+    fn only_saas(&self, analyzer: &WappAnalyzer) -> Vec<&WappCheckResult> {
+        self.filter_by_tech(analyzer, WappTech::is_saas)
+    }
 
-        Self::from_files(cat_file, group_file, tech_files)
+    /// Results bucketed into infrastructure, platform, marketing and widget layers by the groups of their categories.
+    fn by_layer(&self, analyzer: &WappAnalyzer) -> WappLayerSummary<'_> {
+        WappLayerSummary::new(analyzer, self)
     }
+}
 
-    pub fn from_files<P, I>(cat_file: P, group_file: P, tech_files: I) -> Result<Self, Error>
+impl WappCheckResults for [WappCheckResult] {
+    fn filter_by_tech<F>(&self, analyzer: &WappAnalyzer, f: F) -> Vec<&WappCheckResult>
     where
-        P: AsRef<Path> + Debug,
-        I: Iterator<Item = P>,
+        F: Fn(&WappTech) -> bool,
     {
-        let cat_bytes = fs::read(&cat_file).with_context(|| {
-            let filename = cat_file.as_ref().to_string_lossy();
-            format!("Failed to open file {filename}",)
-        })?;
-
-        let group_bytes = fs::read(&group_file).with_context(|| {
-            let filename = group_file.as_ref().to_string_lossy();
-            format!("Failed to open file {filename}",)
-        })?;
-
-        let mut tech_bytes_vec = Vec::new();
-        for path in tech_files {
-            let bytes = fs::read(&path).with_context(|| {
-                let filename = path.as_ref().to_string_lossy();
-                format!("Failed to open file {filename}",)
-            })?;
-            tech_bytes_vec.push(bytes);
-        }
-        let tech_bytes: Vec<&[u8]> = tech_bytes_vec.iter().map(|b| b.as_slice()).collect();
+        self.iter()
+            .filter(|r| analyzer.techs.get(&r.tech_name).is_some_and(&f))
+            .collect()
+    }
 
-        Self::from_bytes(&cat_bytes, &group_bytes, &tech_bytes)
+    fn by_slug(&self) -> BTreeMap<String, &WappCheckResult> {
+        self.iter().map(|r| (slugify(&r.tech_name), r)).collect()
+    }
+}
+
+impl WappAnalyzer {
+    pub fn new_empty() -> Self {
+        Self {
+            cats: HashMap::new(),
+            groups: HashMap::new(),
+            techs: HashMap::new(),
+            dataset_version: None,
+            transforms: default_transforms(),
+            telemetry: None,
+            detection_counter: None,
+            custom_techs: serde_json::Map::new(),
+            shadowed_techs: HashMap::new(),
+            implies: ImpliesCache::default(),
+        }
     }
 
     pub fn from_bytes(
         cat_bytes: &[u8],
         group_bytes: &[u8],
         tech_bytes: &[&[u8]],
+    ) -> Result<Self, Error> {
+        Self::from_bytes_with_options(
+            cat_bytes,
+            group_bytes,
+            tech_bytes,
+            &WappLoadOptions::default(),
+        )
+    }
+
+    pub fn from_bytes_with_options(
+        cat_bytes: &[u8],
+        group_bytes: &[u8],
+        tech_bytes: &[&[u8]],
+        options: &WappLoadOptions,
     ) -> Result<Self, Error> {
         Ok(Self {
             groups: WappTechGroup::load_from_bytes(group_bytes)
@@ -139,16 +410,162 @@ impl WappAnalyzer {
                 let mut techs = HashMap::new();
                 for (i, data) in tech_bytes.iter().enumerate() {
                     techs.extend(
-                        WappTech::load_from_bytes(data)
+                        WappTech::load_from_bytes(data, options)
                             .with_context(|| format!("Loading wapp technology (file #{i})"))?,
                     )
                 }
                 techs
             },
+            dataset_version: None,
+            transforms: default_transforms(),
+            telemetry: None,
+            detection_counter: None,
+            custom_techs: serde_json::Map::new(),
+            shadowed_techs: HashMap::new(),
+            implies: ImpliesCache::default(),
         })
     }
 }
 
+impl WappAnalyzer {
+    /// Returns the descriptive information of the named technology.
+    pub fn tech_info(&self, name: &str) -> Option<WappTechInfo> {
+        self.techs.get(name).map(WappTechInfo::from)
+    }
+
+    /// The `dom` selectors with property patterns, and the properties to collect from the elements they select for
+    /// [`WappPage::dom_properties`].
+    #[cfg(feature = "scraper")]
+    pub fn dom_property_selectors(&self) -> BTreeMap<&str, BTreeSet<&str>> {
+        let mut selectors = BTreeMap::<&str, BTreeSet<&str>>::new();
+        for pat in self.techs.values().flat_map(|tech| &tech.dom) {
+            let names: Vec<_> = pat.property_names().collect();
+            if !names.is_empty() {
+                selectors.entry(pat.selector()).or_default().extend(names);
+            }
+        }
+        selectors
+    }
+
+    /// The fields of each technology that were skipped because this crate does not know them, for technologies with
+    /// any. See [`WappLoadOptions::allow_unknown_fields`].
+    pub fn unknown_fields(&self) -> BTreeMap<&str, &[String]> {
+        self.techs
+            .values()
+            .filter(|tech| !tech.unknown_fields.is_empty())
+            .map(|tech| (tech.name.as_str(), tech.unknown_fields.as_slice()))
+            .collect()
+    }
+
+    /// The `window` properties `js` patterns look at, as dotted paths like `jQuery.fn.jquery`.
+    pub fn js_properties(&self) -> BTreeSet<&str> {
+        self.techs
+            .values()
+            .flat_map(|tech| &tech.js)
+            .map(|(path, _)| path.as_str())
+            .collect()
+    }
+
+    /// Returns a new analyzer with only the technologies selected by `f`, and the categories and groups they use.
+    /// Compiled patterns are shared with `self` rather than recompiled.
+    pub fn subset<F>(&self, f: F) -> WappAnalyzer
+    where
+        F: Fn(&WappTech) -> bool,
+    {
+        let techs: HashMap<String, WappTech> = self
+            .techs
+            .iter()
+            .filter(|(_, tech)| f(tech))
+            .map(|(name, tech)| (name.clone(), tech.clone()))
+            .collect();
+        let cats: HashMap<i32, WappTechCategory> = self
+            .cats
+            .iter()
+            .filter(|(id, _)| techs.values().any(|t| t.cats.contains(id)))
+            .map(|(id, cat)| (*id, cat.clone()))
+            .collect();
+        let groups = self
+            .groups
+            .iter()
+            .filter(|(id, _)| cats.values().any(|c| c.groups.contains(id)))
+            .map(|(id, group)| (*id, group.clone()))
+            .collect();
+        let custom_techs = self
+            .custom_techs
+            .iter()
+            .filter(|(name, _)| techs.contains_key(*name))
+            .map(|(name, raw)| (name.clone(), raw.clone()))
+            .collect();
+        let shadowed_techs = self
+            .shadowed_techs
+            .iter()
+            .filter(|(_, tech)| f(tech))
+            .map(|(name, tech)| (name.clone(), tech.clone()))
+            .collect();
+
+        WappAnalyzer {
+            groups,
+            cats,
+            techs,
+            dataset_version: self.dataset_version.clone(),
+            transforms: self.transforms.clone(),
+            telemetry: self.telemetry.clone(),
+            detection_counter: self.detection_counter.clone(),
+            custom_techs,
+            shadowed_techs,
+            implies: ImpliesCache::default(),
+        }
+    }
+
+    /// Returns a new analyzer with only the technologies in any of `cat_ids`. See [`subset`](Self::subset).
+    pub fn subset_by_categories(&self, cat_ids: &[i32]) -> WappAnalyzer {
+        self.subset(|tech| tech.cats.iter().any(|c| cat_ids.contains(c)))
+    }
+
+    /// Returns a new analyzer with only the named technologies. See [`subset`](Self::subset).
+    pub fn subset_by_names(&self, names: &[&str]) -> WappAnalyzer {
+        self.subset(|tech| names.contains(&tech.name.as_str()))
+    }
+}
+
+impl Display for WappAnalyzer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "WappAnalyzer: {} technologies, {} categories, {} groups",
+            self.techs.len(),
+            self.cats.len(),
+            self.groups.len(),
+        )?;
+
+        let features: &[&str] = &[
+            #[cfg(feature = "cookie")]
+            "cookie",
+            #[cfg(feature = "fs")]
+            "fs",
+            #[cfg(feature = "http")]
+            "http",
+            #[cfg(feature = "scraper")]
+            "scraper",
+            #[cfg(feature = "slim-metadata")]
+            "slim-metadata",
+        ];
+        writeln!(f, "  features: {}", features.join(", "))?;
+
+        let mut totals: Vec<(&str, usize)> = Vec::new();
+        for tech in self.techs.values() {
+            for (i, (detector, count)) in tech.pattern_counts().into_iter().enumerate() {
+                match totals.get_mut(i) {
+                    Some((_, total)) => *total += count,
+                    None => totals.push((detector, count)),
+                }
+            }
+        }
+        let totals: Vec<String> = totals.iter().map(|(d, c)| format!("{d} {c}")).collect();
+        write!(f, "  patterns: {}", totals.join(", "))
+    }
+}
+
 impl WappTechGroup {
     pub(crate) fn load_from_bytes(bytes: &[u8]) -> Result<HashMap<i32, Self>, Error> {
         let data = serde_json::from_slice::<HashMap<&str, Self>>(bytes)
@@ -186,19 +603,538 @@ impl WappTechCategory {
 }
 
 impl WappAnalyzer {
+    /// Checks the page for every technology. With the default transforms, results are sorted by descending
+    /// confidence, then by name, so the output of a page is the same from one run to the next.
     pub fn check<P: WappPage>(&self, page: &P) -> Vec<WappCheckResult> {
+        self.check_with_options(page, &WappCheckOptions::default())
+    }
+
+    /// Stages applied in order to the results of every check. Defaults to [`WappRequiresTransform`],
+    /// [`WappImpliesTransform`], [`WappExcludesTransform`] and [`WappRankTransform`]; reorder, remove or add stages to
+    /// change post-processing. The stages get the detections sorted by name.
+    pub fn transforms_mut(&mut self) -> &mut Vec<Arc<dyn WappResultTransform>> {
+        &mut self.transforms
+    }
+
+    /// Installs a hook that receives counters for every page checked from now on. See [`WappScanTelemetry`].
+    pub fn set_telemetry(&mut self, telemetry: Arc<dyn WappScanTelemetry>) {
+        self.telemetry = Some(TelemetryHook(telemetry));
+    }
+
+    /// Starts or stops counting how often each technology is detected, see [`WappDetectionCounts`]. Starting discards
+    /// previous counts. Only the final results of checks are counted, so the first stage of a staged check is not.
+    ///
+    /// Clones and subsets of the analyzer made while counting add to the same counts.
+    pub fn set_count_detections(&mut self, enabled: bool) {
+        self.detection_counter = enabled.then(DetectionCounter::default);
+    }
+
+    /// The counts since counting started or was last reset, or `None` when not counting.
+    pub fn detection_counts(&self) -> Option<WappDetectionCounts> {
+        self.detection_counter.as_ref().map(DetectionCounter::get)
+    }
+
+    /// Resets the counts to zero, returning them as they were, e.g. to export them once per reporting period.
+    pub fn reset_detection_counts(&self) -> Option<WappDetectionCounts> {
+        self.detection_counter.as_ref().map(DetectionCounter::take)
+    }
+
+    pub fn check_with_options<P: WappPage>(
+        &self,
+        page: &P,
+        options: &WappCheckOptions,
+    ) -> Vec<WappCheckResult> {
+        self.check_techs(page, options, self.techs.values(), true)
+    }
+
+    /// Checks the page for the named technology only, without post-processing. Gives the detectors that were tried
+    /// when it is not detected, or `None` when the analyzer does not know the technology.
+    pub fn check_tech<P: WappPage>(
+        &self,
+        page: &P,
+        tech_name: &str,
+        options: &WappCheckOptions,
+    ) -> Option<Result<WappCheckResult, WappAbsentTech>> {
+        let tech = self.techs.get(tech_name)?;
+        let mut evidence = Vec::new();
+        let mut explain = options.explain.then(|| Explain::new(&mut evidence));
+        let (result, detectors) = tech.check_attempted(page, options, None, explain.as_mut());
+        Some(match result {
+            Some(r) => Ok(WappCheckResult {
+                tech_name: tech.name.clone(),
+                confidence: r.confidence,
+                version: r.version,
+                version_captures: r.version_captures,
+                version_candidates: r.version_candidates,
+                sources: r.sources,
+                evidence,
+            }),
+            None => Err(WappAbsentTech {
+                tech_name: tech.name.clone(),
+                detectors,
+            }),
+        })
+    }
+
+    /// Checks the page for the given technologies only, applying the transforms if `transform` is set.
+    pub(crate) fn check_techs<'a, P: WappPage>(
+        &'a self,
+        page: &P,
+        options: &WappCheckOptions,
+        techs: impl IntoIterator<Item = &'a WappTech>,
+        transform: bool,
+    ) -> Vec<WappCheckResult> {
+        self.check_techs_reporting(page, options, techs, transform, None)
+    }
+
+    /// [`check_techs`](Self::check_techs), also collecting the technologies checked for but not detected in `absent`.
+    pub(crate) fn check_techs_reporting<'a, P: WappPage>(
+        &'a self,
+        page: &P,
+        options: &WappCheckOptions,
+        techs: impl IntoIterator<Item = &'a WappTech>,
+        transform: bool,
+        absent: Option<&mut Vec<WappAbsentTech>>,
+    ) -> Vec<WappCheckResult> {
         let mut result = Vec::new();
+        let mut checked = Vec::new();
 
-        for tech in self.techs.values() {
-            if let Some(r) = tech.check(page) {
+        let start = self.telemetry.as_ref().map(|_| Instant::now());
+        let mut stats = self.telemetry.as_ref().map(|_| WappScanStats {
+            dataset_version: self.dataset_version.clone(),
+            ..Default::default()
+        });
+
+        for tech in techs {
+            if let Some(stats) = &mut stats {
+                stats.techs += 1;
+            }
+            let mut evidence = Vec::new();
+            let mut explain = options.explain.then(|| Explain::new(&mut evidence));
+            let r = match absent {
+                Some(_) => {
+                    let (r, detectors) =
+                        tech.check_attempted(page, options, stats.as_mut(), explain.as_mut());
+                    checked.push((tech, detectors));
+                    r
+                }
+                None => tech.check_timed(page, options, stats.as_mut(), explain.as_mut()),
+            };
+            if let Some(r) = r {
                 result.push(WappCheckResult {
                     tech_name: tech.name.clone(),
                     confidence: r.confidence,
                     version: r.version,
+                    version_captures: r.version_captures,
+                    version_candidates: r.version_candidates,
+                    sources: r.sources,
+                    evidence,
                 });
             }
         }
+        // Technologies are kept in a hash map, so the order they are checked in differs from one run to the next.
+        result.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+
+        if transform {
+            for transform in &self.transforms {
+                transform.apply(self, &mut result);
+            }
+        }
+        if options.min_confidence > 0 {
+            result.retain(|r| r.confidence >= options.min_confidence);
+        }
+        if transform {
+            if let Some(counter) = &self.detection_counter {
+                counter.record(&result);
+            }
+        }
+        if let Some(absent) = absent {
+            // Detections dropped by the transforms, e.g. for a missing requirement, count as absent too.
+            absent.extend(
+                checked
+                    .into_iter()
+                    .filter(|(tech, _)| !result.iter().any(|r| r.tech_name == tech.name))
+                    .map(|(tech, detectors)| WappAbsentTech {
+                        tech_name: tech.name.clone(),
+                        detectors,
+                    }),
+            );
+        }
+
+        if let (Some(TelemetryHook(hook)), Some(start), Some(mut stats)) =
+            (&self.telemetry, start, stats)
+        {
+            stats.duration = start.elapsed();
+            stats.detections = result.len();
+            hook.page_checked(&stats);
+        }
 
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{test_dataset, WappAnalyzer, WappCheckResult, WappCheckResults};
+
+    #[test]
+    fn test_filter_results_by_tech() {
+        let techs = br#"{
+            "Paid": {"cats": [], "website": "", "pricing": ["low"], "saas": true},
+            "Free": {"cats": [], "website": "", "oss": true}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+
+        let results: Vec<_> = ["Paid", "Free", "Unknown"]
+            .into_iter()
+            .map(|name| WappCheckResult {
+                tech_name: name.into(),
+                confidence: 100,
+                version: None,
+                version_captures: Vec::new(),
+                version_candidates: Vec::new(),
+                sources: Vec::new(),
+                evidence: Vec::new(),
+            })
+            .collect();
+
+        let names = |r: Vec<&WappCheckResult>| -> Vec<String> {
+            r.into_iter().map(|r| r.tech_name.clone()).collect()
+        };
+        #[cfg(not(feature = "slim-metadata"))]
+        assert_eq!(names(results.only_paid(&analyzer)), ["Paid"]);
+        #[cfg(feature = "slim-metadata")]
+        assert!(results.only_paid(&analyzer).is_empty());
+        assert_eq!(names(results.only_saas(&analyzer)), ["Paid"]);
+        assert_eq!(names(results.only_oss(&analyzer)), ["Free"]);
+    }
+
+    #[test]
+    fn test_by_slug() {
+        use super::slugify;
+
+        assert_eq!(slugify("Apache HTTP Server"), "apache-http-server");
+        assert_eq!(slugify("Node.js"), "node-js");
+        assert_eq!(slugify("  C++ / Qt "), "c-qt");
+        assert_eq!(slugify("Étoile"), "toile");
+
+        let results = [WappCheckResult {
+            tech_name: "Google Analytics".into(),
+            confidence: 100,
+            version: Some("GA4".into()),
+            version_captures: Vec::new(),
+            version_candidates: Vec::new(),
+            sources: Vec::new(),
+            evidence: Vec::new(),
+        }];
+        let json = serde_json::to_value(results.by_slug()).unwrap();
+        assert_eq!(json["google-analytics"]["version"], "GA4");
+    }
+
+    #[cfg(not(feature = "slim-metadata"))]
+    #[test]
+    fn test_tech_info() {
+        let techs = br#"{"Shop": {"cats": [6], "website": "https://shop", "description": "A shop.", "pricing": ["mid", "recurring"], "saas": true}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+
+        assert!(analyzer.tech_info("Unknown").is_none());
+        let json = serde_json::to_value(analyzer.tech_info("Shop").unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "Shop",
+                "slug": "shop",
+                "website": "https://shop",
+                "description": "A shop.",
+                "cats": [6],
+                "icon": "default.svg",
+                "pricing": ["mid", "recurring"],
+                "saas": true,
+                "oss": null,
+                "cpe": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_detection_counts() {
+        use crate::WappRawPage;
+
+        let mut analyzer = WappAnalyzer::new_test();
+        assert!(analyzer.detection_counts().is_none());
+        analyzer.set_count_detections(true);
+
+        for url in [
+            "https://url.test/a",
+            "https://url.test/b",
+            "https://other.test/",
+        ] {
+            analyzer.check(&WappRawPage::new(Some(url), None, None));
+        }
+        let counts = analyzer.detection_counts().unwrap();
+        assert_eq!(counts.pages, 3);
+        assert_eq!(counts.most_frequent(), [("Test URL", 2)]);
+        assert!((counts.rate("Test URL") - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(counts.rate("Test HTML"), 0.0);
+
+        assert_eq!(analyzer.reset_detection_counts().unwrap().pages, 3);
+        assert_eq!(analyzer.detection_counts().unwrap().pages, 0);
+
+        analyzer.set_count_detections(false);
+        assert!(analyzer.detection_counts().is_none());
+    }
+
+    #[test]
+    fn test_telemetry() {
+        use std::sync::{Arc, Mutex};
+
+        use super::{WappRawPage, WappScanStats, WappScanTelemetry};
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<WappScanStats>>);
+
+        impl WappScanTelemetry for Recorder {
+            fn page_checked(&self, stats: &WappScanStats) {
+                self.0.lock().unwrap().push(stats.clone());
+            }
+        }
+
+        let mut analyzer = WappAnalyzer::new_test();
+        let recorder = Arc::new(Recorder::default());
+        analyzer.dataset_version = Some("v1".into());
+        analyzer.set_telemetry(recorder.clone());
+
+        analyzer.check(&WappRawPage::new(
+            None,
+            None,
+            Some(b"<!-- test-html 3.1 -->"),
+        ));
+
+        let stats = recorder.0.lock().unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].dataset_version.as_deref(), Some("v1"));
+        assert_eq!(stats[0].techs, 16);
+        assert_eq!(stats[0].detections, 2);
+        assert!(stats[0].detector_timings.iter().any(|(d, _)| *d == "url"));
+        assert!(stats[0].detector_timings.iter().any(|(d, _)| *d == "html"));
+    }
+
+    #[test]
+    fn test_implies() {
+        let techs = br#"{
+            "CMS": {"cats": [], "website": "", "html": "cms", "implies": ["Lang\\;confidence:50", "Db", "Missing"]},
+            "Lang": {"cats": [], "website": ""},
+            "Db": {"cats": [], "website": "", "html": "db\\;confidence:30"},
+            "Plugin": {"cats": [], "website": "", "html": "plugin\\;confidence:60", "implies": ["Lang\\;confidence:50", "Cache"]},
+            "Cache": {"cats": [], "website": ""}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+
+        let mut results =
+            analyzer.check(&super::WappRawPage::new(None, None, Some(b"cms db plugin")));
+        results.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+        let results: Vec<_> = results
+            .iter()
+            .map(|r| (r.tech_name.as_str(), r.confidence))
+            .collect();
+        assert_eq!(
+            results,
+            [
+                ("CMS", 100),
+                ("Cache", 60),
+                ("Db", 100),
+                ("Lang", 50),
+                ("Plugin", 60)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sources() {
+        use super::WappCheckOptions;
+
+        let techs = br#"{
+            "CMS": {"cats": [], "website": "", "url": "/cms/\\;confidence:30", "html": ["cms\\;confidence:40", "<cms"], "implies": "Lang"},
+            "Lang": {"cats": [], "website": "", "html": "lang\\;confidence:20"}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let page = super::WappRawPage::new(Some("https://a.test/cms/"), None, Some(b"<cms lang"));
+
+        let sources = |options: &WappCheckOptions| -> Vec<_> {
+            analyzer
+                .check_with_options(&page, options)
+                .into_iter()
+                .map(|r| {
+                    let sources: Vec<_> = r
+                        .sources
+                        .iter()
+                        .map(|s| (s.detector, s.confidence))
+                        .collect();
+                    (r.tech_name, r.confidence, sources)
+                })
+                .collect()
+        };
+        assert_eq!(
+            sources(&WappCheckOptions::default()),
+            [
+                ("CMS".into(), 100, vec![("url", 30), ("html", 100)]),
+                ("Lang".into(), 100, vec![("html", 20), ("implies", 100)]),
+            ]
+        );
+
+        let options = WappCheckOptions {
+            detector_caps: [("html".into(), 50)].into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            sources(&options),
+            [
+                ("CMS".into(), 80, vec![("url", 30), ("html", 50)]),
+                ("Lang".into(), 80, vec![("html", 20), ("implies", 80)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_order() {
+        let techs = br#"{
+            "B": {"cats": [], "website": "", "html": "b\\;confidence:50"},
+            "C": {"cats": [], "website": "", "html": "c"},
+            "A": {"cats": [], "website": "", "html": "a"},
+            "D": {"cats": [], "website": "", "html": "d\\;confidence:50"}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let page = super::WappRawPage::new(None, None, Some(b"d c b a"));
+
+        let names: Vec<_> = analyzer
+            .check(&page)
+            .into_iter()
+            .map(|r| r.tech_name)
+            .collect();
+        assert_eq!(names, ["A", "C", "B", "D"]);
+
+        let mut analyzer = analyzer;
+        analyzer.transforms_mut().clear();
+        let names: Vec<_> = analyzer
+            .check(&page)
+            .into_iter()
+            .map(|r| r.tech_name)
+            .collect();
+        assert_eq!(names, ["A", "B", "C", "D"]);
+    }
+
+    #[test]
+    fn test_min_confidence() {
+        let techs = br#"{
+            "CMS": {"cats": [], "website": "", "html": "cms", "implies": "Lang\\;confidence:50"},
+            "Lang": {"cats": [], "website": ""},
+            "Maybe": {"cats": [], "website": "", "html": "maybe\\;confidence:25"}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let page = super::WappRawPage::new(None, None, Some(b"cms maybe"));
+
+        let names = |min_confidence| -> Vec<_> {
+            let options = super::WappCheckOptions {
+                min_confidence,
+                ..Default::default()
+            };
+            analyzer
+                .check_with_options(&page, &options)
+                .into_iter()
+                .map(|r| r.tech_name)
+                .collect()
+        };
+        assert_eq!(names(0), ["CMS", "Lang", "Maybe"]);
+        assert_eq!(names(50), ["CMS", "Lang"]);
+        assert_eq!(names(100), ["CMS"]);
+    }
+
+    #[test]
+    fn test_detector_mask() {
+        use super::{WappCheckOptions, WappDetectorKinds};
+
+        let analyzer = WappAnalyzer::new_test();
+        let page = super::WappRawPage::new(
+            Some("https://url.test/"),
+            None,
+            Some(b"<!-- test-html 3.1 -->"),
+        );
+
+        let names = |detectors| -> Vec<_> {
+            let options = WappCheckOptions {
+                detectors,
+                ..Default::default()
+            };
+            analyzer
+                .check_with_options(&page, &options)
+                .into_iter()
+                .map(|r| r.tech_name)
+                .collect()
+        };
+        assert_eq!(
+            names(WappDetectorKinds::ALL),
+            ["Test HTML", "Test Implied", "Test URL"]
+        );
+        assert_eq!(names(WappDetectorKinds::CHEAP), ["Test URL"]);
+        assert_eq!(
+            names(WappDetectorKinds::HTML),
+            ["Test HTML", "Test Implied"]
+        );
+        assert!(names(WappDetectorKinds::NONE).is_empty());
+    }
+
+    #[test]
+    fn test_excludes() {
+        let techs = br#"{
+            "Apache": {"cats": [], "website": "", "html": "apache\\;confidence:50", "excludes": "Nginx"},
+            "Nginx": {"cats": [], "website": "", "html": "nginx"},
+            "A": {"cats": [], "website": "", "html": "a", "excludes": ["B"]},
+            "B": {"cats": [], "website": "", "html": "b"}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+
+        let mut results = analyzer.check(&super::WappRawPage::new(
+            None,
+            None,
+            Some(b"apache nginx a b"),
+        ));
+        results.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+        let names: Vec<_> = results.iter().map(|r| r.tech_name.as_str()).collect();
+        assert_eq!(names, ["A", "Nginx"]);
+    }
+
+    #[test]
+    fn test_display_summary() {
+        let analyzer = WappAnalyzer::new_test();
+
+        let summary = analyzer.to_string();
+        assert!(summary.starts_with("WappAnalyzer: 16 technologies, 3 categories, 2 groups"));
+        assert!(summary.contains("url 1"));
+        assert!(summary.contains("html 1"));
+
+        assert_eq!(analyzer.techs["Test HTML"].to_string(), "Test HTML: html 1");
+    }
+
+    #[test]
+    fn test_subset() {
+        let cats = br#"{
+            "1": {"groups": [1], "name": "CMS", "priority": 1},
+            "2": {"groups": [2], "name": "Web servers", "priority": 8}
+        }"#;
+        let groups = br#"{"1": {"name": "Content"}, "2": {"name": "Servers"}}"#;
+        let techs = br#"{
+            "CMS": {"cats": [1], "website": "", "html": "<cms"},
+            "Server": {"cats": [2], "website": ""}
+        }"#;
+        let analyzer = WappAnalyzer::from_bytes(cats, groups, &[techs]).unwrap();
+
+        let subset = analyzer.subset_by_categories(&[1]);
+        assert_eq!(subset.techs.keys().collect::<Vec<_>>(), ["CMS"]);
+        assert_eq!(subset.cats.keys().collect::<Vec<_>>(), [&1]);
+        assert_eq!(subset.groups.keys().collect::<Vec<_>>(), [&1]);
+
+        let subset = analyzer.subset_by_names(&["Server"]);
+        assert_eq!(subset.techs.keys().collect::<Vec<_>>(), ["Server"]);
+        assert_eq!(subset.groups.keys().collect::<Vec<_>>(), [&2]);
+    }
+}
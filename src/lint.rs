@@ -0,0 +1,208 @@
+use std::sync::LazyLock;
+
+use anyhow::{Context, Error};
+use regex::Regex;
+
+use crate::{tech::Tagged, WappLoadOptions, DETECTOR_FIELDS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WappLintIssueKind {
+    /// The pattern or its tags do not parse, so the analyzer skips it.
+    InvalidPattern,
+    /// The pattern starts with `.*` without being anchored.
+    UnanchoredWildcard,
+    /// A quantified group ends with a quantifier, e.g. `(a+)+`, which backtracking engines such as upstream's take
+    /// exponential time on when the match fails.
+    NestedQuantifier,
+    /// The version template refers to a capture group the pattern does not have. The analyzer keeps the pattern but
+    /// drops the template, so the technology is detected without a version.
+    VersionGroupOutOfRange,
+}
+
+#[derive(Debug)]
+pub struct WappLintIssue {
+    pub tech_name: String,
+    /// Where the pattern is, e.g. `html` or `headers.Server`.
+    pub field: String,
+    pub pattern: String,
+    pub kind: WappLintIssueKind,
+    /// What is wrong and how to fix it.
+    pub message: String,
+}
+
+/// Flags common authoring issues in the patterns of a technologies file, e.g. before submitting fingerprints upstream.
+/// Issues are sorted by technology name, then field.
+pub fn lint_technologies(bytes: &[u8]) -> Result<Vec<WappLintIssue>, Error> {
+    let mut issues = Vec::new();
+    for_each_pattern(bytes, |name, field, pattern| {
+        lint_pattern(name, field, pattern, &mut issues)
+    })?;
+
+    issues.sort_by(|a, b| (&a.tech_name, &a.field).cmp(&(&b.tech_name, &b.field)));
+    Ok(issues)
+}
+
+/// Calls `f` with the technology name, field (e.g. `headers.Server`) and source of every pattern of a technologies
+/// file, in file order.
+pub(crate) fn for_each_pattern<F>(bytes: &[u8], mut f: F) -> Result<(), Error>
+where
+    F: FnMut(&str, &str, &str),
+{
+    let data = serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(bytes)
+        .context("Failed to parse JSON from bytes")?;
+
+    for (name, tech) in &data {
+        let Some(fields) = tech.as_object() else {
+            continue;
+        };
+        for (field, value) in fields {
+            if !DETECTOR_FIELDS.contains(&field.as_str()) {
+                continue;
+            }
+            let mut patterns = Vec::new();
+            if field == "dom" {
+                dom_patterns(value, &mut patterns);
+            } else {
+                field_patterns(field.clone(), value, &mut patterns);
+            }
+            for (field, pattern) in patterns {
+                f(name, &field, pattern);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Collects the pattern strings of a detector field: a string, an array of strings, or a map of them keyed by e.g.
+/// header name.
+fn field_patterns<'a>(
+    field: String,
+    value: &'a serde_json::Value,
+    out: &mut Vec<(String, &'a str)>,
+) {
+    match value {
+        serde_json::Value::String(s) => out.push((field, s)),
+        serde_json::Value::Array(a) => {
+            out.extend(a.iter().filter_map(|v| Some((field.clone(), v.as_str()?))));
+        }
+        serde_json::Value::Object(o) => {
+            for (k, v) in o {
+                field_patterns(format!("{field}.{k}"), v, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the patterns of the `dom` field. Selectors themselves are not patterns.
+fn dom_patterns<'a>(value: &'a serde_json::Value, out: &mut Vec<(String, &'a str)>) {
+    let Some(selectors) = value.as_object() else {
+        return;
+    };
+    for (selector, description) in selectors {
+        let Some(description) = description.as_object() else {
+            continue;
+        };
+        for (k, v) in description {
+            if matches!(k.as_str(), "text" | "attributes" | "properties" | "src") {
+                field_patterns(format!("dom.{selector}.{k}"), v, out);
+            }
+        }
+    }
+}
+
+fn lint_pattern(tech_name: &str, field: &str, pattern: &str, issues: &mut Vec<WappLintIssue>) {
+    static NESTED_QUANTIFIER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\((?:[^()\\]|\\.)*[+*]\)[+*{]").unwrap());
+
+    let mut issue = |kind, message: String| {
+        issues.push(WappLintIssue {
+            tech_name: tech_name.into(),
+            field: field.into(),
+            pattern: pattern.into(),
+            kind,
+            message,
+        })
+    };
+
+    let parsed = match Tagged::parse_pattern_unchecked(pattern, &WappLoadOptions::default()) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            issue(WappLintIssueKind::InvalidPattern, format!("{e:#}"));
+            return;
+        }
+    };
+    let source = parsed.inner.as_str();
+
+    if source.starts_with(".*") {
+        issue(
+            WappLintIssueKind::UnanchoredWildcard,
+            "Leading `.*` matches nothing more in an unanchored pattern and slows matching down; remove it".into(),
+        );
+    }
+    if let Some(m) = NESTED_QUANTIFIER.find(source) {
+        issue(
+            WappLintIssueKind::NestedQuantifier,
+            format!(
+                "`{}` nests quantifiers, which can backtrack catastrophically; quantify only the group or its content",
+                m.as_str()
+            ),
+        );
+    }
+    if let Some(var) = parsed.version_var_out_of_range() {
+        let groups = parsed.inner.captures_len() - 1;
+        issue(
+            WappLintIssueKind::VersionGroupOutOfRange,
+            format!(
+                "Version refers to \\{var}, but the pattern has {groups} capture group(s); the version is ignored"
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint_technologies, WappLintIssueKind};
+
+    #[test]
+    fn test_lint_technologies() {
+        let techs = br#"{
+            "Clean": {"cats": [], "website": "", "html": "<div id=\"clean-([\\d.]+)\\;version:\\1", "dom": "div.x"},
+            "Wildcard": {"cats": [], "website": "", "headers": {"Server": ".*wildcard"}},
+            "Nested": {"cats": [], "website": "", "scriptSrc": ["ok\\.js", "(\\w+)+\\.js"]},
+            "Version": {"cats": [], "website": "", "meta": {"generator": "version ([\\d.]+)\\;version:\\2?a:b"}},
+            "Broken": {"cats": [], "website": "", "dom": {"div": {"attributes": {"class": "("}}}}
+        }"#;
+
+        let issues: Vec<_> = lint_technologies(techs)
+            .unwrap()
+            .into_iter()
+            .map(|i| (i.tech_name, i.field, i.kind))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (
+                    "Broken".into(),
+                    "dom.div.attributes.class".into(),
+                    WappLintIssueKind::InvalidPattern
+                ),
+                (
+                    "Nested".into(),
+                    "scriptSrc".into(),
+                    WappLintIssueKind::NestedQuantifier
+                ),
+                (
+                    "Version".into(),
+                    "meta.generator".into(),
+                    WappLintIssueKind::VersionGroupOutOfRange
+                ),
+                (
+                    "Wildcard".into(),
+                    "headers.Server".into(),
+                    WappLintIssueKind::UnanchoredWildcard
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,599 @@
+use crate::{WappAnalyzer, WappCheckResult, WappPage};
+
+#[cfg(feature = "cookie")]
+use cookie::Cookie;
+
+#[cfg(feature = "http")]
+use http::{HeaderMap, HeaderName, HeaderValue};
+
+use std::sync::OnceLock;
+
+#[cfg(feature = "scraper")]
+use std::sync::LazyLock;
+
+#[cfg(feature = "scraper")]
+use regex::Regex;
+#[cfg(feature = "scraper")]
+use scraper::{Html, Node, Selector};
+
+#[cfg(feature = "scraper")]
+use crate::url;
+
+/// The canonical URL and the `(hreflang, URL)` language alternates of a document.
+#[cfg(feature = "scraper")]
+type Links = (Option<String>, Vec<(String, String)>);
+
+/// A page built from raw response parts: parsed headers, cookies from `Set-Cookie` and the decoded HTML. Everything
+/// derived from the DOM (the DOM itself, its meta tags, scripts, links, stylesheets and visible text) is only worked
+/// out when a detector or caller first asks for it, so detectors that are not run cost nothing.
+#[derive(Debug)]
+pub struct WappRawPage {
+    url: Option<String>,
+    #[cfg(feature = "http")]
+    headers: HeaderMap,
+    #[cfg(feature = "cookie")]
+    cookies: Vec<Cookie<'static>>,
+    html: Option<String>,
+    #[cfg(feature = "scraper")]
+    dom: OnceLock<Option<Html>>,
+    /// See [`url::document_base`].
+    #[cfg(feature = "scraper")]
+    base: OnceLock<Option<String>>,
+    #[cfg(feature = "scraper")]
+    meta: OnceLock<Vec<(String, String)>>,
+    #[cfg(feature = "scraper")]
+    script_src: OnceLock<Vec<String>>,
+    #[cfg(feature = "scraper")]
+    scripts: OnceLock<Vec<String>>,
+    #[cfg(feature = "scraper")]
+    stylesheet_urls: OnceLock<Vec<String>>,
+    #[cfg(feature = "scraper")]
+    links: OnceLock<Links>,
+    #[cfg(feature = "scraper")]
+    client_redirect: OnceLock<Option<String>>,
+    #[cfg(feature = "scraper")]
+    text: OnceLock<Option<String>>,
+    css: OnceLock<Option<String>>,
+}
+
+impl WappRawPage {
+    /// Builds a page from its URL, response headers and body. Headers that are not valid HTTP headers are skipped and
+    /// the body is decoded as UTF-8, replacing invalid sequences.
+    #[cfg_attr(
+        not(any(feature = "http", feature = "cookie")),
+        allow(unused_variables)
+    )]
+    pub fn new(
+        url: Option<&str>,
+        headers: Option<&[(String, String)]>,
+        body: Option<&[u8]>,
+    ) -> Self {
+        let headers = headers.unwrap_or_default();
+
+        Self {
+            url: url.map(String::from),
+            #[cfg(feature = "http")]
+            headers: headers
+                .iter()
+                .filter_map(|(k, v)| {
+                    let k = HeaderName::from_bytes(k.as_bytes()).ok()?;
+                    let v = HeaderValue::from_str(v).ok()?;
+                    Some((k, v))
+                })
+                .collect(),
+            #[cfg(feature = "cookie")]
+            cookies: headers
+                .iter()
+                .filter(|(k, _)| k.eq_ignore_ascii_case("set-cookie"))
+                .filter_map(|(_, v)| Cookie::parse(v.clone()).ok())
+                .collect(),
+            html: body.map(|b| String::from_utf8_lossy(b).into_owned()),
+            #[cfg(feature = "scraper")]
+            dom: OnceLock::new(),
+            #[cfg(feature = "scraper")]
+            base: OnceLock::new(),
+            #[cfg(feature = "scraper")]
+            meta: OnceLock::new(),
+            #[cfg(feature = "scraper")]
+            script_src: OnceLock::new(),
+            #[cfg(feature = "scraper")]
+            scripts: OnceLock::new(),
+            #[cfg(feature = "scraper")]
+            stylesheet_urls: OnceLock::new(),
+            #[cfg(feature = "scraper")]
+            links: OnceLock::new(),
+            #[cfg(feature = "scraper")]
+            client_redirect: OnceLock::new(),
+            #[cfg(feature = "scraper")]
+            text: OnceLock::new(),
+            css: OnceLock::new(),
+        }
+    }
+
+    #[cfg(feature = "scraper")]
+    fn parsed(&self) -> Option<&Html> {
+        self.dom
+            .get_or_init(|| self.html.as_deref().map(Html::parse_document))
+            .as_ref()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn base(&self) -> Option<&str> {
+        self.base
+            .get_or_init(|| url::document_base(self.parsed()?, self.url.as_deref()))
+            .as_deref()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn links(&self) -> &Links {
+        self.links.get_or_init(|| {
+            self.parsed()
+                .map(|dom| links(dom, self.base()))
+                .unwrap_or_default()
+        })
+    }
+
+    /// Absolute URL of the first `<link rel="canonical">` of the document.
+    pub fn canonical_url(&self) -> Option<&str> {
+        #[cfg(feature = "scraper")]
+        return self.links().0.as_deref();
+        #[cfg(not(feature = "scraper"))]
+        None
+    }
+
+    /// `(hreflang, absolute URL)` of every `<link rel="alternate" hreflang>` of the document, in document order.
+    pub fn alternate_links(&self) -> &[(String, String)] {
+        #[cfg(feature = "scraper")]
+        return &self.links().1;
+        #[cfg(not(feature = "scraper"))]
+        &[]
+    }
+
+    /// Absolute URL the document redirects to on the client side, through a `<meta http-equiv="refresh">` or a
+    /// `location` assignment in an inline script. Scripts are only searched for string literals, not run.
+    pub fn client_redirect(&self) -> Option<&str> {
+        #[cfg(feature = "scraper")]
+        return self
+            .client_redirect
+            .get_or_init(|| client_redirect(self.parsed()?, self.base()))
+            .as_deref();
+        #[cfg(not(feature = "scraper"))]
+        None
+    }
+
+    /// Absolute URLs of the `<link rel="stylesheet">` of the document, in document order.
+    #[cfg(feature = "scraper")]
+    pub fn stylesheet_urls(&self) -> &[String] {
+        self.stylesheet_urls.get_or_init(|| {
+            self.parsed()
+                .map(|dom| stylesheet_urls(dom, self.base()))
+                .unwrap_or_default()
+        })
+    }
+
+    /// Appends the contents of the linked stylesheets to the page's stylesheet text, for `css` patterns. `fetch` is
+    /// called with each of [`stylesheet_urls`](Self::stylesheet_urls) and returns `None` for stylesheets it could not
+    /// fetch, which are skipped.
+    #[cfg(feature = "scraper")]
+    pub fn fetch_stylesheets<F>(&mut self, mut fetch: F)
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let fetched: Vec<String> = self
+            .stylesheet_urls()
+            .iter()
+            .filter_map(|url| fetch(url))
+            .collect();
+        WappPage::css(self);
+        let Some(css) = self.css.get_mut() else {
+            return;
+        };
+        for contents in fetched {
+            let css = css.get_or_insert_with(String::new);
+            if !css.is_empty() {
+                css.push('\n');
+            }
+            css.push_str(&contents);
+        }
+    }
+}
+
+/// `(name, content)` of the `<meta>` tags of the document, named by their `name` or else `property` attribute, like
+/// upstream does. Names are lowercased.
+#[cfg(feature = "scraper")]
+pub(crate) fn meta_tags(dom: &Html) -> Vec<(String, String)> {
+    static META_SELECTOR: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("meta[content]").unwrap());
+
+    dom.select(&META_SELECTOR)
+        .filter_map(|el| {
+            let name = el.attr("name").or_else(|| el.attr("property"))?;
+            Some((name.to_ascii_lowercase(), el.attr("content")?.to_string()))
+        })
+        .collect()
+}
+
+/// URLs of the `<script src>` of the document, in document order, resolved against its `<base href>` or `url` like
+/// browsers do. Left as written when there is no base to resolve against.
+#[cfg(feature = "scraper")]
+pub(crate) fn script_srcs(dom: &Html, url: Option<&str>) -> Vec<String> {
+    script_srcs_from(dom, url::document_base(dom, url).as_deref())
+}
+
+/// [`script_srcs`] resolved against a known `base`.
+#[cfg(feature = "scraper")]
+fn script_srcs_from(dom: &Html, base: Option<&str>) -> Vec<String> {
+    static SCRIPT_SELECTOR: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("script[src]").unwrap());
+
+    dom.select(&SCRIPT_SELECTOR)
+        .filter_map(|el| el.attr("src"))
+        .map(|src| match base {
+            Some(base) => url::resolve(base, src),
+            None => src.to_string(),
+        })
+        .collect()
+}
+
+/// Source of the inline `<script>` elements of the document, in document order. Scripts with a `src` and empty ones
+/// are left out.
+#[cfg(feature = "scraper")]
+pub(crate) fn inline_scripts(dom: &Html) -> Vec<String> {
+    static SCRIPT_SELECTOR: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("script:not([src])").unwrap());
+
+    dom.select(&SCRIPT_SELECTOR)
+        .map(|el| el.text().collect::<String>())
+        .filter(|source| !source.trim().is_empty())
+        .collect()
+}
+
+/// Contents of the `<style>` elements of the document, in document order, one per line.
+#[cfg(feature = "scraper")]
+pub(crate) fn style_blocks(dom: &Html) -> String {
+    static STYLE_SELECTOR: LazyLock<Selector> = LazyLock::new(|| Selector::parse("style").unwrap());
+
+    dom.select(&STYLE_SELECTOR)
+        .map(|el| el.text().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// URLs of the `<link rel="stylesheet" href>` of the document, in document order, resolved against `base`.
+#[cfg(feature = "scraper")]
+fn stylesheet_urls(dom: &Html, base: Option<&str>) -> Vec<String> {
+    static LINK_SELECTOR: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("link[rel][href]").unwrap());
+
+    dom.select(&LINK_SELECTOR)
+        .filter(|el| {
+            el.attr("rel").is_some_and(|rel| {
+                rel.split_ascii_whitespace()
+                    .any(|r| r.eq_ignore_ascii_case("stylesheet"))
+            })
+        })
+        .filter_map(|el| el.attr("href"))
+        .map(|href| match base {
+            Some(base) => url::resolve(base, href),
+            None => href.trim().to_string(),
+        })
+        .collect()
+}
+
+/// Target of the first `<meta http-equiv="refresh">` with a URL, or else of the first `location` assignment, call to
+/// `location.replace` or `location.assign` with a string literal in an inline script, resolved against `base`.
+#[cfg(feature = "scraper")]
+fn client_redirect(dom: &Html, base: Option<&str>) -> Option<String> {
+    static REFRESH_SELECTOR: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("meta[http-equiv][content]").unwrap());
+    static REFRESH_URL: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"(?i)^\s*\d*(?:\.\d*)?\s*[;,]\s*(?:url\s*=\s*)?['"]?([^'"]+)"#).unwrap()
+    });
+    static LOCATION: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r#"\blocation(?:\.href)?\s*(?:=\s*|\.(?:replace|assign)\s*\(\s*)(?:"([^"]+)"|'([^']+)')"#,
+        )
+        .unwrap()
+    });
+
+    let target = dom
+        .select(&REFRESH_SELECTOR)
+        .filter(|el| {
+            el.attr("http-equiv")
+                .is_some_and(|v| v.trim().eq_ignore_ascii_case("refresh"))
+        })
+        .find_map(|el| {
+            Some(
+                REFRESH_URL.captures(el.attr("content")?)?[1]
+                    .trim()
+                    .to_string(),
+            )
+        })
+        .or_else(|| {
+            inline_scripts(dom).iter().find_map(|source| {
+                let caps = LOCATION.captures(source)?;
+                Some(caps.get(1).or(caps.get(2))?.as_str().to_string())
+            })
+        })
+        .filter(|target| !target.is_empty())?;
+
+    Some(match base {
+        Some(base) => url::resolve(base, &target),
+        None => target,
+    })
+}
+
+/// The canonical URL and the language alternates linked from the document, resolved against `base`.
+#[cfg(feature = "scraper")]
+fn links(dom: &Html, base: Option<&str>) -> Links {
+    static LINK_SELECTOR: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("link[rel][href]").unwrap());
+
+    let resolve = |href: &str| match base {
+        Some(base) => url::resolve(base, href),
+        None => href.trim().to_string(),
+    };
+
+    let mut canonical_url = None;
+    let mut alternate_links = Vec::new();
+    for el in dom.select(&LINK_SELECTOR) {
+        let (Some(rel), Some(href)) = (el.attr("rel"), el.attr("href")) else {
+            continue;
+        };
+        let mut rel = rel.split_ascii_whitespace();
+        if canonical_url.is_none() && rel.clone().any(|r| r.eq_ignore_ascii_case("canonical")) {
+            canonical_url = Some(resolve(href));
+        } else if rel.any(|r| r.eq_ignore_ascii_case("alternate")) {
+            if let Some(hreflang) = el.attr("hreflang") {
+                alternate_links.push((hreflang.to_string(), resolve(href)));
+            }
+        }
+    }
+
+    (canonical_url, alternate_links)
+}
+
+/// Text content of the document, leaving out scripts and stylesheets.
+#[cfg(feature = "scraper")]
+fn visible_text(dom: &Html) -> String {
+    let mut text = String::new();
+    for node in dom.root_element().descendants() {
+        let Node::Text(t) = node.value() else {
+            continue;
+        };
+        let hidden = node
+            .parent()
+            .and_then(|p| p.value().as_element())
+            .is_some_and(|e| matches!(e.name(), "script" | "style" | "noscript" | "template"));
+        if !hidden && !t.trim().is_empty() {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(t.trim());
+        }
+    }
+    text
+}
+
+impl WappPage for WappRawPage {
+    fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    #[cfg(feature = "http")]
+    fn headers(&self) -> Option<&HeaderMap> {
+        Some(&self.headers)
+    }
+
+    #[cfg(feature = "cookie")]
+    fn cookies(&self) -> Option<&[Cookie<'_>]> {
+        Some(&self.cookies)
+    }
+
+    #[cfg(feature = "scraper")]
+    fn dom(&self) -> Option<&Html> {
+        self.parsed()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn meta(&self) -> Option<&[(String, String)]> {
+        let dom = self.parsed()?;
+        Some(self.meta.get_or_init(|| meta_tags(dom)))
+    }
+
+    #[cfg(feature = "scraper")]
+    fn script_src(&self) -> Option<&[String]> {
+        let dom = self.parsed()?;
+        Some(
+            self.script_src
+                .get_or_init(|| script_srcs_from(dom, self.base())),
+        )
+    }
+
+    #[cfg(feature = "scraper")]
+    fn scripts(&self) -> Option<&[String]> {
+        let dom = self.parsed()?;
+        Some(self.scripts.get_or_init(|| inline_scripts(dom)))
+    }
+
+    fn css(&self) -> Option<&str> {
+        self.css
+            .get_or_init(|| {
+                #[cfg(feature = "scraper")]
+                return self.parsed().map(style_blocks);
+                #[cfg(not(feature = "scraper"))]
+                None
+            })
+            .as_deref()
+    }
+
+    fn html(&self) -> Option<&str> {
+        self.html.as_deref()
+    }
+
+    fn text(&self) -> Option<&str> {
+        #[cfg(feature = "scraper")]
+        return self
+            .text
+            .get_or_init(|| self.parsed().map(visible_text))
+            .as_deref();
+        #[cfg(not(feature = "scraper"))]
+        None
+    }
+
+    fn canonical_url(&self) -> Option<&str> {
+        WappRawPage::canonical_url(self)
+    }
+}
+
+impl WappAnalyzer {
+    /// Checks a page given as raw response parts. See [`WappRawPage::new`].
+    pub fn analyze(
+        &self,
+        url: Option<&str>,
+        headers: Option<&[(String, String)]>,
+        body: Option<&[u8]>,
+    ) -> Vec<WappCheckResult> {
+        self.check(&WappRawPage::new(url, headers, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::WappAnalyzer;
+
+    #[test]
+    fn test_analyze() {
+        let analyzer = WappAnalyzer::new_test();
+
+        let body = br#"<html><body><!-- test-html 3.1 --><p>Powered by TestText</p></body></html>"#;
+        let mut results = analyzer.analyze(Some("https://url.test/index.php"), None, Some(body));
+        results.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+        let names: Vec<_> = results.iter().map(|r| r.tech_name.as_str()).collect();
+
+        #[cfg(feature = "scraper")]
+        assert_eq!(
+            names,
+            ["Test HTML", "Test Implied", "Test Text", "Test URL"]
+        );
+        #[cfg(not(feature = "scraper"))]
+        assert_eq!(names, ["Test HTML", "Test Implied", "Test URL"]);
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_lazy_parts() {
+        use super::WappRawPage;
+        use crate::WappPage;
+
+        let body = br#"<base href="/b/"><meta name="generator" content="X"><script src="app.js"></script>"#;
+        let page = WappRawPage::new(Some("https://example.com/a"), None, Some(body));
+        assert!(page.dom.get().is_none());
+        assert_eq!(page.html(), Some(std::str::from_utf8(body).unwrap()));
+        assert!(page.dom.get().is_none());
+
+        assert_eq!(page.script_src().unwrap(), ["https://example.com/b/app.js"]);
+        assert!(page.dom.get().is_some());
+        assert!(page.meta.get().is_none());
+        assert_eq!(page.meta().unwrap(), [("generator".into(), "X".into())]);
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_client_redirect() {
+        use super::WappRawPage;
+
+        let redirect = |body: &str| {
+            WappRawPage::new(Some("https://parked.test/a/b"), None, Some(body.as_bytes()))
+                .client_redirect()
+                .map(String::from)
+        };
+        assert_eq!(
+            redirect(r#"<meta http-equiv="Refresh" content="0; URL='/landing'">"#).as_deref(),
+            Some("https://parked.test/landing")
+        );
+        assert_eq!(
+            redirect(r#"<meta http-equiv="refresh" content="5;url=https://shop.test/">"#)
+                .as_deref(),
+            Some("https://shop.test/")
+        );
+        assert_eq!(
+            redirect(r#"<script>window.location.href = "next.html";</script>"#).as_deref(),
+            Some("https://parked.test/a/next.html")
+        );
+        assert_eq!(
+            redirect(r#"<script>location.replace('//cdn.test/x')</script>"#).as_deref(),
+            Some("https://cdn.test/x")
+        );
+        assert_eq!(
+            redirect(r#"<meta http-equiv="refresh" content="30">"#),
+            None
+        );
+        assert_eq!(
+            redirect(r#"<script>if (location.href == x) {}</script>"#),
+            None
+        );
+    }
+
+    #[cfg(all(feature = "http", feature = "cookie", feature = "scraper"))]
+    #[test]
+    fn test_raw_page() {
+        use super::WappRawPage;
+        use crate::WappPage;
+
+        let headers = [
+            ("Server".to_string(), "nginx".to_string()),
+            (
+                "Set-Cookie".to_string(),
+                "sid=abc; Path=/; HttpOnly".to_string(),
+            ),
+            ("bad header".to_string(), "x".to_string()),
+        ];
+        let body = b"<p>a</p><script>var hidden;</script><style>p{}</style><p>b \xff</p>";
+        let page = WappRawPage::new(None, Some(&headers), Some(body));
+
+        assert_eq!(page.headers().unwrap().len(), 2);
+        assert_eq!(page.cookies().unwrap()[0].name(), "sid");
+        assert_eq!(page.cookies().unwrap()[0].value(), "abc");
+        assert_eq!(page.text(), Some("a b \u{FFFD}"));
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_canonical_url() {
+        use super::WappRawPage;
+        use crate::{test_dataset, WappCheckOptions};
+
+        let body = br#"<head>
+<link rel="canonical" href="/blog/post-1">
+<link rel="alternate" hreflang="de" href="https://example.de/post-1">
+<link rel="alternate" type="application/rss+xml" href="/feed">
+<link rel="alternate" hreflang="fr" href="../fr/post-1">
+</head>"#;
+        let page = WappRawPage::new(Some("https://shop.example.com/a/b"), None, Some(body));
+
+        assert_eq!(
+            page.canonical_url(),
+            Some("https://shop.example.com/blog/post-1")
+        );
+        assert_eq!(
+            page.alternate_links(),
+            [
+                ("de".to_string(), "https://example.de/post-1".to_string()),
+                (
+                    "fr".to_string(),
+                    "https://shop.example.com/fr/post-1".to_string()
+                ),
+            ]
+        );
+
+        let techs = br#"{"Blog": {"cats": [], "website": "", "url": "/blog/post-\\d+$"}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        assert!(analyzer.check(&page).is_empty());
+        let options = WappCheckOptions {
+            check_canonical_url: true,
+            ..Default::default()
+        };
+        assert_eq!(analyzer.check_with_options(&page, &options).len(), 1);
+    }
+}
@@ -0,0 +1,179 @@
+//! The tagged pattern syntax of the dataset, for tools that build on the same fingerprints (tests, editors, linters)
+//! and need the crate's exact matching semantics.
+
+use anyhow::Error;
+
+use crate::{tech::Tagged, WappLoadOptions, WappTech};
+
+/// How a dataset pattern, written for JavaScript's regular expression engine, is brought into the `regex` crate. There
+/// is no fallback engine: patterns the `regex` crate cannot express are dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WappPatternTranslation {
+    /// The pattern is compiled as written.
+    Verbatim,
+    /// The pattern is rewritten before being compiled.
+    Translated {
+        /// The rewritten regular expression, without tags.
+        regex: String,
+        /// What was rewritten, e.g. ``escaped literal `{` at offset 12``.
+        changes: Vec<String>,
+    },
+    /// The analyzer skips the pattern.
+    Dropped {
+        /// Why, e.g. the compile error for a lookahead.
+        reason: String,
+    },
+}
+
+/// How one pattern of a technologies file is translated, see [`pattern_translations`].
+#[derive(Debug, Clone)]
+pub struct WappPatternRecord {
+    pub tech_name: String,
+    /// Where the pattern is, e.g. `html` or `headers.Server`, see [`WappTech::patterns`].
+    pub field: String,
+    /// The pattern as written in the dataset, without tags unless it was dropped.
+    pub pattern: String,
+    pub translation: WappPatternTranslation,
+}
+
+/// A pattern of the dataset that does not compile, so the analyzer skips it, see [`WappTech::dropped_patterns`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WappDroppedPattern {
+    /// Where the pattern is, e.g. `html` or `headers.Server`.
+    pub field: String,
+    /// The pattern as written in the dataset, with its tags.
+    pub pattern: String,
+    /// Why it is skipped, e.g. the compile error for a lookahead.
+    pub reason: String,
+}
+
+/// Matches `input` against a single dataset pattern with its tags, e.g. `^nginx(?:/([\d.]+))?\;version:\1`, returning
+/// the confidence and the resolved version. Patterns are compiled like the analyzer does by default (see
+/// [`WappLoadOptions`]). An invalid pattern never matches; use [`check_pattern`] to find out why.
+pub fn match_pattern(pattern: &str, input: &str) -> Option<(i32, Option<String>)> {
+    let pattern = Tagged::parse_pattern(pattern, &WappLoadOptions::default()).ok()?;
+    let result = pattern.check_str(input)?;
+    Some((result.confidence, result.version))
+}
+
+/// Fails with the reason the analyzer would skip `pattern`, e.g. an invalid regular expression or tag.
+pub fn check_pattern(pattern: &str) -> Result<(), Error> {
+    Tagged::parse_pattern(pattern, &WappLoadOptions::default()).map(|_| ())
+}
+
+/// How the analyzer brings `pattern` into the `regex` crate: as written, rewritten, or not at all.
+pub fn translate_pattern(pattern: &str) -> WappPatternTranslation {
+    match Tagged::parse_pattern(pattern, &WappLoadOptions::default()) {
+        Ok(pattern) => pattern.translation(),
+        Err(e) => WappPatternTranslation::Dropped {
+            reason: format!("{e:#}"),
+        },
+    }
+}
+
+/// How every pattern of a technologies file is brought into the `regex` crate, for fingerprint authors to review how
+/// their patterns behave in this crate. The file is loaded like the analyzer does, allowing unknown fields, and the
+/// records come from [`WappTech::patterns`] and [`WappTech::dropped_patterns`], sorted by technology name. Patterns
+/// of detectors disabled by features are not loaded, so they are left out.
+pub fn pattern_translations(bytes: &[u8]) -> Result<Vec<WappPatternRecord>, Error> {
+    let options = WappLoadOptions {
+        allow_unknown_fields: true,
+        ..Default::default()
+    };
+    let mut techs: Vec<_> = WappTech::load_from_bytes(bytes, &options)?
+        .into_values()
+        .collect();
+    techs.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut records = Vec::new();
+    for tech in &techs {
+        records.extend(
+            tech.patterns()
+                .into_iter()
+                .map(|(field, pattern)| WappPatternRecord {
+                    tech_name: tech.name.clone(),
+                    field,
+                    pattern: pattern.source().into(),
+                    translation: pattern.translation(),
+                }),
+        );
+        records.extend(
+            tech.dropped_patterns
+                .iter()
+                .map(|dropped| WappPatternRecord {
+                    tech_name: tech.name.clone(),
+                    field: dropped.field.clone(),
+                    pattern: dropped.pattern.clone(),
+                    translation: WappPatternTranslation::Dropped {
+                        reason: dropped.reason.clone(),
+                    },
+                }),
+        );
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_pattern, match_pattern, pattern_translations, WappPatternTranslation};
+
+    #[test]
+    fn test_match_pattern() {
+        let nginx = r"^nginx(?:/([\d.]+))?\;version:\1\;confidence:80";
+        assert_eq!(
+            match_pattern(nginx, "NGINX/1.25.3"),
+            Some((80, Some("1.25.3".into())))
+        );
+        assert_eq!(match_pattern(nginx, "nginx"), Some((80, None)));
+        assert_eq!(match_pattern(nginx, "apache"), None);
+        assert_eq!(match_pattern("", "anything"), Some((100, None)));
+
+        assert_eq!(match_pattern("(", "("), None);
+        assert!(check_pattern("(").is_err());
+        assert!(check_pattern(r"a\;confidence:x").is_err());
+        assert!(check_pattern(nginx).is_ok());
+    }
+
+    #[test]
+    fn test_pattern_translations() {
+        let techs = br#"{
+            "Plain": {"cats": [], "website": "", "html": "<div id=\"plain\">"},
+            "Braces": {"cats": [], "website": "", "html": "init\\({\\s*key\\;confidence:50"},
+            "Lookahead": {"cats": [], "website": "", "js": {"foo.version": "^foo(?!bar)"}}
+        }"#;
+
+        let records = pattern_translations(techs).unwrap();
+        let translation = |name: &str| {
+            &records
+                .iter()
+                .find(|r| r.tech_name == name)
+                .unwrap()
+                .translation
+        };
+        assert_eq!(translation("Plain"), &WappPatternTranslation::Verbatim);
+        assert!(matches!(
+            translation("Braces"),
+            WappPatternTranslation::Translated { regex, .. } if regex == r"init\(\{\s*key"
+        ));
+        assert!(matches!(
+            translation("Lookahead"),
+            WappPatternTranslation::Dropped { .. }
+        ));
+        assert_eq!(
+            records
+                .iter()
+                .find(|r| r.tech_name == "Lookahead")
+                .unwrap()
+                .field,
+            "js.foo.version"
+        );
+        assert_eq!(
+            records
+                .iter()
+                .find(|r| r.tech_name == "Braces")
+                .unwrap()
+                .pattern,
+            r"init\({\s*key"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::{collections::BTreeSet, future::Future};
+
+use crate::{WappAnalyzer, WappCheckOptions, WappCheckResult, WappDnsRecords, WappPage};
+
+#[cfg(feature = "cookie")]
+use cookie::Cookie;
+
+#[cfg(feature = "http")]
+use http::HeaderMap;
+
+#[cfg(feature = "scraper")]
+use scraper::Html;
+
+#[cfg(feature = "scraper")]
+use crate::WappElementProperties;
+
+/// Requests the paths of `probe` patterns. The crate has no HTTP client of its own.
+pub trait WappProbeFetcher {
+    /// Fetches an absolute URL, returning the response body, or `None` when the resource does not exist (e.g. a 404)
+    /// or could not be fetched.
+    fn fetch(&self, url: &str) -> Option<String>;
+}
+
+/// Like [`WappProbeFetcher`], for hosts with an async HTTP client.
+pub trait WappAsyncProbeFetcher {
+    fn fetch(&self, url: &str) -> impl Future<Output = Option<String>> + Send;
+}
+
+impl WappAnalyzer {
+    /// Paths requested by the `probe` patterns of the dataset, e.g. `/wp-json/`.
+    pub fn probe_paths(&self) -> BTreeSet<&str> {
+        self.techs
+            .values()
+            .flat_map(|t| t.probe.iter().map(|(path, _)| path.as_str()))
+            .collect()
+    }
+
+    /// Checks the page like [`check_with_options`](Self::check_with_options), first requesting every path of
+    /// [`probe_paths`](Self::probe_paths) on the page's origin with `fetcher` for the `probe` patterns. Each path is
+    /// requested once, in order. Without a page URL, no probes are requested.
+    pub fn check_with_probes<P: WappPage>(
+        &self,
+        page: &P,
+        options: &WappCheckOptions,
+        fetcher: &dyn WappProbeFetcher,
+    ) -> Vec<WappCheckResult> {
+        let probes = self
+            .probe_urls(page)
+            .into_iter()
+            .filter_map(|(path, url)| Some((path, fetcher.fetch(&url)?)))
+            .collect();
+        self.check_with_options(&ProbedPage { page, probes }, options)
+    }
+
+    /// [`check_with_probes`](Self::check_with_probes) with an async fetcher. Paths are requested one after the other.
+    pub async fn check_with_probes_async<P: WappPage, F: WappAsyncProbeFetcher>(
+        &self,
+        page: &P,
+        options: &WappCheckOptions,
+        fetcher: &F,
+    ) -> Vec<WappCheckResult> {
+        let mut probes = Vec::new();
+        for (path, url) in self.probe_urls(page) {
+            if let Some(body) = fetcher.fetch(&url).await {
+                probes.push((path, body));
+            }
+        }
+        self.check_with_options(&ProbedPage { page, probes }, options)
+    }
+
+    /// `(path, absolute URL)` of the probes to request for `page`.
+    pub(crate) fn probe_urls<P: WappPage>(&self, page: &P) -> Vec<(String, String)> {
+        let Some(origin) = page.url().and_then(origin) else {
+            return Vec::new();
+        };
+        self.probe_paths()
+            .into_iter()
+            .map(|path| {
+                let sep = if path.starts_with('/') { "" } else { "/" };
+                (path.to_string(), format!("{origin}{sep}{path}"))
+            })
+            .collect()
+    }
+}
+
+/// `scheme://authority` of an absolute URL.
+fn origin(url: &str) -> Option<&str> {
+    let authority_start = url.find("://")? + 3;
+    let authority_end = url[authority_start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |i| authority_start + i);
+    Some(&url[..authority_end]).filter(|_| authority_end > authority_start)
+}
+
+/// A page with the responses of its probes.
+pub(crate) struct ProbedPage<'a, P> {
+    pub(crate) page: &'a P,
+    pub(crate) probes: Vec<(String, String)>,
+}
+
+impl<P: WappPage> WappPage for ProbedPage<'_, P> {
+    fn url(&self) -> Option<&str> {
+        self.page.url()
+    }
+
+    #[cfg(feature = "http")]
+    fn headers(&self) -> Option<&HeaderMap> {
+        self.page.headers()
+    }
+
+    #[cfg(feature = "cookie")]
+    fn cookies(&self) -> Option<&[Cookie<'_>]> {
+        self.page.cookies()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn dom(&self) -> Option<&Html> {
+        self.page.dom()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn meta(&self) -> Option<&[(String, String)]> {
+        self.page.meta()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn script_src(&self) -> Option<&[String]> {
+        self.page.script_src()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn scripts(&self) -> Option<&[String]> {
+        self.page.scripts()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn dom_properties(&self) -> Option<&[WappElementProperties]> {
+        self.page.dom_properties()
+    }
+
+    fn cert_issuer(&self) -> Option<&str> {
+        self.page.cert_issuer()
+    }
+
+    fn dns(&self) -> Option<&WappDnsRecords> {
+        self.page.dns()
+    }
+
+    fn js(&self) -> Option<&serde_json::Value> {
+        self.page.js()
+    }
+
+    fn probes(&self) -> Option<&[(String, String)]> {
+        Some(&self.probes)
+    }
+
+    fn css(&self) -> Option<&str> {
+        self.page.css()
+    }
+
+    fn html(&self) -> Option<&str> {
+        self.page.html()
+    }
+
+    fn text(&self) -> Option<&str> {
+        self.page.text()
+    }
+
+    fn canonical_url(&self) -> Option<&str> {
+        self.page.canonical_url()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::{origin, WappProbeFetcher};
+    use crate::{test_dataset, WappCheckOptions, WappRawPage};
+
+    #[derive(Default)]
+    struct Fetcher(Mutex<Vec<String>>);
+
+    impl WappProbeFetcher for Fetcher {
+        fn fetch(&self, url: &str) -> Option<String> {
+            self.0.lock().unwrap().push(url.into());
+            match url {
+                "https://example.com/wp-json/" => Some(r#"{"name": "Blog"}"#.into()),
+                "https://example.com/version.txt" => Some("v2.4.1".into()),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_with_probes() {
+        let techs = br#"{
+            "Exists": {"cats": [], "website": "", "probe": {"/wp-json/": ""}},
+            "Version": {"cats": [], "website": "", "probe": {"/version.txt": "^v([\\d.]+)\\;version:\\1"}},
+            "Missing": {"cats": [], "website": "", "probe": {"/admin/": ""}}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let page = WappRawPage::new(Some("https://example.com/blog/post?id=1"), None, None);
+        assert!(analyzer.check(&page).is_empty());
+
+        let fetcher = Fetcher::default();
+        let mut results = analyzer.check_with_probes(&page, &WappCheckOptions::default(), &fetcher);
+        results.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+        let results: Vec<_> = results
+            .iter()
+            .map(|r| (r.tech_name.as_str(), r.version.as_deref()))
+            .collect();
+        assert_eq!(results, [("Exists", None), ("Version", Some("2.4.1"))]);
+        assert_eq!(
+            *fetcher.0.lock().unwrap(),
+            [
+                "https://example.com/admin/",
+                "https://example.com/version.txt",
+                "https://example.com/wp-json/",
+            ]
+        );
+
+        assert_eq!(origin("http://a.test:8080"), Some("http://a.test:8080"));
+        assert_eq!(origin("/relative"), None);
+    }
+}
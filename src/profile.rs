@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use crate::{WappCheckOptions, WappDetectorKinds};
+
+/// Settings of a [`WappScanner`](crate::WappScanner) job, bundled so that operators can pick them per asset class by
+/// name, see [`WappScannerOptions::profiles`](crate::WappScannerOptions::profiles) and
+/// [`WappScanner::submit_with_profile`](crate::WappScanner::submit_with_profile).
+#[derive(Debug, Clone, Default)]
+pub struct WappScanProfile {
+    pub check: WappCheckOptions,
+    /// Number of client-side redirects followed before scanning a page, see
+    /// [`WappScannerOptions::max_client_redirects`](crate::WappScannerOptions::max_client_redirects).
+    pub max_client_redirects: usize,
+    /// Number of `probe` paths requested per page with [`WappFetcher::probe`](crate::WappFetcher::probe), in the
+    /// order of [`WappAnalyzer::probe_paths`](crate::WappAnalyzer::probe_paths). Disabled with 0.
+    pub max_probes: usize,
+}
+
+impl WappScanProfile {
+    /// One request per page, checked with the [`cheap`](WappDetectorKinds::CHEAP) detectors only.
+    pub fn fast() -> Self {
+        Self {
+            check: WappCheckOptions {
+                detectors: WappDetectorKinds::CHEAP,
+                ..Default::default()
+            },
+            max_client_redirects: 0,
+            max_probes: 0,
+        }
+    }
+
+    /// Follows a couple of client-side redirects and also matches canonical URLs.
+    pub fn balanced() -> Self {
+        Self {
+            check: WappCheckOptions {
+                check_canonical_url: true,
+                ..Default::default()
+            },
+            max_client_redirects: 2,
+            max_probes: 0,
+        }
+    }
+
+    /// Requests every probe path, and reports the technologies that were looked for but not found.
+    pub fn thorough() -> Self {
+        Self {
+            check: WappCheckOptions {
+                check_canonical_url: true,
+                report_absent: true,
+                ..Default::default()
+            },
+            max_client_redirects: 5,
+            max_probes: usize::MAX,
+        }
+    }
+
+    /// The built-in profiles, keyed `fast`, `balanced` and `thorough`.
+    pub fn builtins() -> HashMap<String, Self> {
+        HashMap::from([
+            ("fast".into(), Self::fast()),
+            ("balanced".into(), Self::balanced()),
+            ("thorough".into(), Self::thorough()),
+        ])
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::{WappFetcher, WappRawPage};
+
+/// A [`WappFetcher`] that serves responses recorded to a directory instead of going to the network, so scans can run
+/// offline and deterministically, e.g. in integration tests and demos.
+///
+/// Every URL maps to one JSON file in the directory: `{"url": "...", "headers": [["name", "value"], ...], "body":
+/// "..."}`. Fetching a URL without a recording fails.
+#[derive(Debug, Clone)]
+pub struct WappReplayFetcher {
+    dir: PathBuf,
+    failing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Recording {
+    url: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl WappReplayFetcher {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            failing: Vec::new(),
+        }
+    }
+
+    /// Makes fetches of URLs containing `pattern` fail, even if they have been recorded, to exercise error handling.
+    pub fn fail_urls_containing(mut self, pattern: impl Into<String>) -> Self {
+        self.failing.push(pattern.into());
+        self
+    }
+
+    /// Saves a response for `url`, replacing any previous recording. The body is stored as UTF-8, replacing invalid
+    /// sequences like [`WappRawPage::new`] does.
+    pub fn record(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<(), Error> {
+        let recording = Recording {
+            url: url.into(),
+            headers: headers.to_vec(),
+            body: String::from_utf8_lossy(body).into_owned(),
+        };
+        let path = self.path(url);
+        fs::create_dir_all(&self.dir).with_context(|| {
+            format!("Failed to create directory {}", self.dir.to_string_lossy())
+        })?;
+        fs::write(&path, serde_json::to_vec_pretty(&recording)?)
+            .with_context(|| format!("Failed to write file {}", path.to_string_lossy()))
+    }
+
+    /// The recording file of `url`: the URL with unsafe characters replaced, followed by a hash of the full URL.
+    fn path(&self, url: &str) -> PathBuf {
+        let name: String = url
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .take(100)
+            .collect();
+        self.dir
+            .join(format!("{name}-{:016x}.json", fnv1a(url.as_bytes())))
+    }
+}
+
+impl WappFetcher for WappReplayFetcher {
+    fn fetch(&self, url: &str) -> Result<WappRawPage, Error> {
+        if self.failing.iter().any(|p| url.contains(p.as_str())) {
+            bail!("Injected failure for {url}");
+        }
+
+        let path = self.path(url);
+        let bytes = fs::read(&path)
+            .with_context(|| format!("No recording for {url} at {}", path.to_string_lossy()))?;
+        let recording: Recording = serde_json::from_slice(&bytes)
+            .with_context(|| format!("Failed to parse recording {}", path.to_string_lossy()))?;
+
+        Ok(WappRawPage::new(
+            Some(&recording.url),
+            Some(&recording.headers),
+            Some(recording.body.as_bytes()),
+        ))
+    }
+}
+
+/// 64-bit FNV-1a, which unlike the std hashers is stable across Rust releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::WappReplayFetcher;
+    use crate::{WappAnalyzer, WappFetcher};
+
+    #[test]
+    fn test_record_replay() {
+        let dir = env::temp_dir().join(format!("wapp-replay-{}", process::id()));
+        let fetcher = WappReplayFetcher::new(&dir).fail_urls_containing("/down");
+
+        let headers = [("Server".to_string(), "TestServer/1.5".to_string())];
+        fetcher
+            .record("https://url.test/?a=1", &headers, b"<p>hi</p>")
+            .unwrap();
+        fetcher.record("https://url.test/down", &[], b"").unwrap();
+
+        let page = fetcher.fetch("https://url.test/?a=1").unwrap();
+        let names: Vec<_> = WappAnalyzer::new_test()
+            .check(&page)
+            .into_iter()
+            .map(|r| r.tech_name)
+            .collect();
+        assert!(names.contains(&"Test URL".to_string()));
+        #[cfg(feature = "http")]
+        assert!(names.contains(&"Test Header".to_string()));
+
+        assert!(fetcher.fetch("https://url.test/?a=2").is_err());
+        assert!(fetcher.fetch("https://url.test/down").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Serialize, Serializer};
+
+use crate::{
+    WappAbsentTech, WappAnalyzer, WappCheckOptions, WappCheckResult, WappPage, WappRobotsDirectives,
+};
+
+/// The results of checking one page, together with what was scanned, when, and with which dataset.
+///
+/// `started_at` is serialized as milliseconds since the Unix epoch and `duration` as milliseconds, e.g.
+/// `{"target": "https://example.com/", "started_at": 1700000000000, "duration": 12, "dataset_version": null,
+/// "robots": {"noindex": false, "nofollow": false}, "results": [...]}`.
+#[derive(Debug, Serialize)]
+pub struct WappScanReport {
+    /// URL of the scanned page, if it has one.
+    pub target: Option<String>,
+    #[serde(serialize_with = "serialize_unix_millis")]
+    pub started_at: SystemTime,
+    #[serde(serialize_with = "serialize_millis")]
+    pub duration: Duration,
+    pub dataset_version: Option<String>,
+    /// Indexing directives of the page, for consumers that leave out pages that opt out of indexing.
+    pub robots: WappRobotsDirectives,
+    pub results: Vec<WappCheckResult>,
+    /// Technologies checked for but not detected, when [`WappCheckOptions::report_absent`] is set.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub absent: Vec<WappAbsentTech>,
+    /// The context the page was submitted with to a [`WappScanner`](crate::WappScanner), see
+    /// [`submit_with_context`](crate::WappScanner::submit_with_context).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<WappScanContext>,
+}
+
+/// Data of the caller carried from [`WappScanner::submit_with_context`](crate::WappScanner::submit_with_context) to
+/// the report of the job, so that reports can be matched with the caller's asset inventory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WappScanContext {
+    /// Identifier of the target in the caller's inventory.
+    pub target_id: Option<String>,
+    /// Free-form labels, e.g. `env` set to `prod`.
+    pub labels: BTreeMap<String, String>,
+}
+
+fn serialize_unix_millis<S: Serializer>(
+    time: &SystemTime,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let millis = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    serializer.serialize_u64(millis as u64)
+}
+
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
+impl WappAnalyzer {
+    /// Checks a page like [`check`](Self::check) and wraps the results in a [`WappScanReport`].
+    ///
+    /// Reads the system clock, which is unavailable on `wasm32-unknown-unknown`.
+    pub fn scan<P: WappPage>(&self, page: &P) -> WappScanReport {
+        self.scan_with_options(page, &WappCheckOptions::default())
+    }
+
+    pub fn scan_with_options<P: WappPage>(
+        &self,
+        page: &P,
+        options: &WappCheckOptions,
+    ) -> WappScanReport {
+        let started_at = SystemTime::now();
+        let start = Instant::now();
+        let mut absent = Vec::new();
+        let results = self.check_techs_reporting(
+            page,
+            options,
+            self.techs.values(),
+            true,
+            options.report_absent.then_some(&mut absent),
+        );
+        absent.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+
+        WappScanReport {
+            target: page.url().map(String::from),
+            started_at,
+            duration: start.elapsed(),
+            dataset_version: self.dataset_version.clone(),
+            robots: WappRobotsDirectives::from_page(page),
+            results,
+            absent,
+            context: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{WappAnalyzer, WappRawPage};
+
+    #[test]
+    fn test_scan_report() {
+        let mut analyzer = WappAnalyzer::new_test();
+        analyzer.dataset_version = Some("v1".into());
+
+        let report = analyzer.scan(&WappRawPage::new(Some("https://url.test/"), None, None));
+        assert_eq!(report.target.as_deref(), Some("https://url.test/"));
+        assert_eq!(report.results.len(), 1);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert!(json["started_at"].as_u64().unwrap() > 0);
+        assert!(json["duration"].is_u64());
+        assert_eq!(json["dataset_version"], "v1");
+        assert_eq!(json["robots"]["noindex"], false);
+        assert_eq!(json["results"][0]["tech_name"], "Test URL");
+
+        #[cfg(feature = "scraper")]
+        {
+            let html = br#"<meta name="robots" content="noindex">"#;
+            let report = analyzer.scan(&WappRawPage::new(None, None, Some(html)));
+            assert!(report.robots.noindex && !report.robots.nofollow);
+        }
+    }
+
+    #[test]
+    fn test_scan_report_absent() {
+        use crate::{test_dataset, WappCheckOptions};
+
+        let techs = br#"{
+            "CMS": {"cats": [], "website": "", "url": "/cms/", "html": "<meta cms>"},
+            "Theme": {"cats": [], "website": "", "html": "<div theme>", "requires": "CMS"},
+            "Meta": {"cats": [], "website": "", "meta": {"generator": "meta"}}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let page = WappRawPage::new(Some("https://a/"), None, Some(b"<div theme>"));
+
+        assert!(analyzer.scan(&page).absent.is_empty());
+
+        let options = WappCheckOptions {
+            report_absent: true,
+            ..Default::default()
+        };
+        let report = analyzer.scan_with_options(&page, &options);
+        assert!(report.results.is_empty());
+        let absent: Vec<_> = report
+            .absent
+            .iter()
+            .map(|a| (a.tech_name.as_str(), a.detectors.as_slice()))
+            .collect();
+        #[cfg(feature = "scraper")]
+        assert_eq!(
+            absent,
+            [
+                ("CMS", ["url", "html"].as_slice()),
+                ("Meta", ["meta"].as_slice()),
+                ("Theme", ["html"].as_slice()),
+            ]
+        );
+        #[cfg(not(feature = "scraper"))]
+        assert_eq!(absent.len(), 3);
+
+        let absent = analyzer
+            .check_tech(&page, "CMS", &options)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(absent.detectors, ["url", "html"]);
+        assert!(analyzer
+            .check_tech(&page, "Theme", &options)
+            .unwrap()
+            .is_ok());
+        assert!(analyzer.check_tech(&page, "Unknown", &options).is_none());
+    }
+}
@@ -0,0 +1,118 @@
+use serde::Serialize;
+
+use crate::WappPage;
+
+#[cfg(feature = "scraper")]
+use std::sync::LazyLock;
+
+#[cfg(feature = "scraper")]
+use scraper::Selector;
+
+/// Indexing directives a page declares through `<meta name="robots">` (or a crawler-specific name such as
+/// `googlebot`) and the `X-Robots-Tag` header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WappRobotsDirectives {
+    pub noindex: bool,
+    pub nofollow: bool,
+}
+
+impl WappRobotsDirectives {
+    pub fn from_page<P: WappPage>(page: &P) -> Self {
+        #[allow(unused_mut)]
+        let mut directives = Self::default();
+
+        #[cfg(feature = "http")]
+        if let Some(headers) = page.headers() {
+            for value in headers.get_all("x-robots-tag") {
+                if let Ok(value) = value.to_str() {
+                    directives.apply(value);
+                }
+            }
+        }
+
+        #[cfg(feature = "scraper")]
+        if let Some(dom) = page.dom() {
+            static META_SELECTOR: LazyLock<Selector> =
+                LazyLock::new(|| Selector::parse("meta[name][content]").unwrap());
+            for el in dom.select(&META_SELECTOR) {
+                let (Some(name), Some(content)) = (el.attr("name"), el.attr("content")) else {
+                    continue;
+                };
+                let name = name.to_ascii_lowercase();
+                if name == "robots" || name.ends_with("bot") {
+                    directives.apply(content);
+                }
+            }
+        }
+
+        #[cfg(not(any(feature = "http", feature = "scraper")))]
+        let _ = page;
+
+        directives
+    }
+
+    /// Applies a comma-separated directive list, optionally prefixed with a user agent (`googlebot: noindex`).
+    #[cfg_attr(not(any(feature = "http", feature = "scraper")), allow(dead_code))]
+    fn apply(&mut self, value: &str) {
+        for directive in value.split(',') {
+            let directive = directive.rsplit(':').next().unwrap_or_default().trim();
+            if directive.eq_ignore_ascii_case("noindex") || directive.eq_ignore_ascii_case("none") {
+                self.noindex = true;
+            }
+            if directive.eq_ignore_ascii_case("nofollow") || directive.eq_ignore_ascii_case("none")
+            {
+                self.nofollow = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WappRobotsDirectives;
+
+    #[test]
+    fn test_apply() {
+        let mut d = WappRobotsDirectives::default();
+        d.apply("index, follow");
+        assert_eq!(d, WappRobotsDirectives::default());
+
+        d.apply("googlebot: NoIndex");
+        assert!(d.noindex && !d.nofollow);
+
+        let mut d = WappRobotsDirectives::default();
+        d.apply("none");
+        assert!(d.noindex && d.nofollow);
+    }
+
+    #[cfg(all(feature = "http", feature = "scraper"))]
+    #[test]
+    fn test_from_page() {
+        use http::HeaderMap;
+        use scraper::Html;
+
+        use crate::WappPage;
+
+        struct Page(HeaderMap, Html);
+
+        impl WappPage for Page {
+            fn headers(&self) -> Option<&HeaderMap> {
+                Some(&self.0)
+            }
+
+            fn dom(&self) -> Option<&Html> {
+                Some(&self.1)
+            }
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-robots-tag", "nofollow".parse().unwrap());
+        let dom = Html::parse_document(r#"<meta name="ROBOTS" content="noindex">"#);
+
+        let d = WappRobotsDirectives::from_page(&Page(headers, dom));
+        assert!(d.noindex && d.nofollow);
+
+        let d = WappRobotsDirectives::from_page(&Page(HeaderMap::new(), Html::new_document()));
+        assert_eq!(d, WappRobotsDirectives::default());
+    }
+}
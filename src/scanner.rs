@@ -0,0 +1,689 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+        Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+};
+
+use anyhow::{anyhow, Error};
+
+use crate::{
+    probe::ProbedPage, SharedAnalyzer, WappPage, WappRawPage, WappScanContext, WappScanProfile,
+    WappScanReport, WappScanScope,
+};
+
+/// Fetches the pages scanned by a [`WappScanner`]. The crate has no HTTP client of its own.
+pub trait WappFetcher: Send + Sync {
+    fn fetch(&self, url: &str) -> Result<WappRawPage, Error>;
+
+    /// Fetches the URL of a `probe` pattern, like [`WappProbeFetcher::fetch`](crate::WappProbeFetcher::fetch). Only
+    /// called for profiles with [`max_probes`](WappScanProfile::max_probes). Probes nothing by default.
+    fn probe(&self, _url: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Receives the outcome of every job of a [`WappScanner`]. Called from the worker threads.
+pub trait WappResultSink: Send + Sync {
+    fn scanned(&self, url: &str, result: Result<WappScanReport, Error>);
+
+    /// Receives the progress of every job as it happens, before [`scanned`](Self::scanned) is called with its
+    /// outcome. Ignored by default.
+    fn event(&self, _event: WappScanEvent) {}
+}
+
+/// What happens to a job of a [`WappScanner`], in order: it is started, its page fetched, each technology detected
+/// reported, and it finishes with the outcome given to [`WappResultSink::scanned`]. A job that fails only starts and
+/// finishes.
+#[derive(Debug)]
+pub enum WappScanEvent {
+    Started {
+        url: String,
+    },
+    /// The page was fetched. `page_url` is the URL of the page scanned, after client-side redirects.
+    Fetched {
+        url: String,
+        page_url: Option<String>,
+    },
+    Detected {
+        url: String,
+        tech_name: String,
+        confidence: i32,
+    },
+    Finished {
+        url: String,
+        result: Result<WappScanReport, Error>,
+    },
+}
+
+/// A [`WappResultSink`] that sends the events of every job to a channel, for consumers that process them as a stream
+/// at their own pace. Workers block while the channel is full, and stop sending once the receiver is dropped.
+#[derive(Debug, Clone)]
+pub struct WappChannelSink(SyncSender<WappScanEvent>);
+
+impl WappChannelSink {
+    /// Creates a sink whose channel holds up to `bound` events, and the receiver of its events.
+    pub fn new(bound: usize) -> (Self, Receiver<WappScanEvent>) {
+        let (sender, receiver) = mpsc::sync_channel(bound);
+        (Self(sender), receiver)
+    }
+}
+
+impl WappResultSink for WappChannelSink {
+    fn scanned(&self, url: &str, result: Result<WappScanReport, Error>) {
+        self.event(WappScanEvent::Finished {
+            url: url.into(),
+            result,
+        });
+    }
+
+    fn event(&self, event: WappScanEvent) {
+        let _ = self.0.send(event);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WappScannerOptions {
+    /// Number of worker threads.
+    pub workers: usize,
+    /// Number of queued URLs after which [`WappScanner::submit`] blocks.
+    pub queue_size: usize,
+    /// URLs out of this scope are reported as errors without being fetched.
+    pub scope: WappScanScope,
+    /// Number of client-side redirects (see [`WappRawPage::client_redirect`]) followed before scanning a page, for
+    /// parked and landing pages that hide the real site. Each target is checked against the scope. Disabled with 0.
+    pub max_client_redirects: usize,
+    /// Profiles [`WappScanner::submit_with_profile`] can pick by name. The built-in ones by default.
+    pub profiles: HashMap<String, WappScanProfile>,
+    /// Treat `www.example.com` and `example.com` as the same site when deduplicating submitted URLs.
+    pub fold_www: bool,
+}
+
+impl Default for WappScannerOptions {
+    fn default() -> Self {
+        Self {
+            workers: 4,
+            queue_size: 64,
+            scope: WappScanScope::default(),
+            max_client_redirects: 0,
+            profiles: WappScanProfile::builtins(),
+            fold_www: false,
+        }
+    }
+}
+
+/// A pool of worker threads that fetch queued URLs, check them with the current analyzer of a [`SharedAnalyzer`] and
+/// hand the reports to a [`WappResultSink`].
+///
+/// Submitted URLs are canonicalized, see [`submit`](Self::submit), and each is only scanned once.
+///
+/// Dropping the scanner shuts it down gracefully, like [`shutdown`](Self::shutdown).
+pub struct WappScanner {
+    sender: Option<SyncSender<Job>>,
+    profiles: HashMap<String, Arc<WappScanProfile>>,
+    fold_www: bool,
+    /// Canonical URLs submitted so far.
+    submitted: Mutex<HashSet<String>>,
+    workers: Vec<JoinHandle<()>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl WappScanner {
+    pub fn start(
+        fetcher: Arc<dyn WappFetcher>,
+        analyzer: Arc<SharedAnalyzer>,
+        sink: Arc<dyn WappResultSink>,
+        options: &WappScannerOptions,
+    ) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<Job>(options.queue_size);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let workers = (0..options.workers.max(1))
+            .map(|_| {
+                let worker = Worker {
+                    fetcher: fetcher.clone(),
+                    analyzer: analyzer.clone(),
+                    sink: sink.clone(),
+                    receiver: receiver.clone(),
+                    stopped: stopped.clone(),
+                    scope: options.scope.clone(),
+                    profile: Arc::new(WappScanProfile {
+                        max_client_redirects: options.max_client_redirects,
+                        ..Default::default()
+                    }),
+                };
+                thread::spawn(move || worker.run())
+            })
+            .collect();
+
+        Self {
+            sender: Some(sender),
+            profiles: options
+                .profiles
+                .iter()
+                .map(|(name, profile)| (name.clone(), Arc::new(profile.clone())))
+                .collect(),
+            fold_www: options.fold_www,
+            submitted: Mutex::default(),
+            workers,
+            stopped,
+        }
+    }
+
+    /// Queues a URL, blocking while the queue is full. It is scanned with default check options, following
+    /// [`max_client_redirects`](WappScannerOptions::max_client_redirects).
+    ///
+    /// The URL is canonicalized first: `https://` is assumed without a scheme, the scheme and host are lowercased,
+    /// default ports and the fragment are dropped, and an empty path becomes `/`. Returns false without queueing
+    /// anything if the canonical URL was already submitted; the sink gets the canonical URL.
+    pub fn submit(&self, url: impl AsRef<str>) -> Result<bool, Error> {
+        self.send(url.as_ref(), None, None)
+    }
+
+    /// Queues a URL to scan with the named profile of [`WappScannerOptions::profiles`], like
+    /// [`submit`](Self::submit).
+    pub fn submit_with_profile(&self, url: impl AsRef<str>, profile: &str) -> Result<bool, Error> {
+        self.send(url.as_ref(), Some(self.profile(profile)?), None)
+    }
+
+    /// Queues a URL like [`submit`](Self::submit), with the named profile if any, copying `context` into the report
+    /// the sink gets for it. Jobs that fail are only reported with their URL.
+    pub fn submit_with_context(
+        &self,
+        url: impl AsRef<str>,
+        profile: Option<&str>,
+        context: WappScanContext,
+    ) -> Result<bool, Error> {
+        let profile = profile.map(|name| self.profile(name)).transpose()?;
+        self.send(url.as_ref(), profile, Some(context))
+    }
+
+    fn profile(&self, name: &str) -> Result<Arc<WappScanProfile>, Error> {
+        self.profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unknown scan profile {name}"))
+    }
+
+    fn send(
+        &self,
+        url: &str,
+        profile: Option<Arc<WappScanProfile>>,
+        context: Option<WappScanContext>,
+    ) -> Result<bool, Error> {
+        let url = canonicalize(url, self.fold_www);
+        let mut submitted = self
+            .submitted
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if submitted.contains(&url) {
+            return Ok(false);
+        }
+        let sender = self
+            .sender
+            .as_ref()
+            .ok_or_else(|| anyhow!("Scanner workers have exited"))?;
+        sender
+            .send(Job {
+                url: url.clone(),
+                profile,
+                context,
+            })
+            .map_err(|_| anyhow!("Scanner workers have exited"))?;
+        submitted.insert(url);
+        Ok(true)
+    }
+
+    /// Stops accepting URLs, scans the queued ones and waits for the workers to finish.
+    pub fn shutdown(mut self) {
+        self.join();
+    }
+
+    /// Stops accepting URLs and waits for the jobs in progress to finish. Queued URLs are discarded without being
+    /// reported.
+    pub fn stop(mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        self.join();
+    }
+
+    fn join(&mut self) {
+        self.sender = None;
+        for worker in self.workers.drain(..) {
+            // A panicking fetcher or sink only takes its own worker down.
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for WappScanner {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+impl fmt::Debug for WappScanner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WappScanner")
+            .field("workers", &self.workers.len())
+            .field("stopped", &self.stopped.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+/// The canonical form of a submitted URL, see [`WappScanner::submit`]. `www.` is dropped from the host with
+/// `fold_www`.
+fn canonicalize(url: &str, fold_www: bool) -> String {
+    let url = url.trim();
+    let url = url.split('#').next().unwrap_or_default();
+    let (scheme, rest) = url.split_once("://").unwrap_or(("https", url));
+    let scheme = scheme.to_ascii_lowercase();
+
+    let authority_end = rest.find(['/', '?']).unwrap_or(rest.len());
+    let (authority, path) = rest.split_at(authority_end);
+    let (userinfo, host) = match authority.rsplit_once('@') {
+        Some((userinfo, host)) => (format!("{userinfo}@"), host),
+        None => (String::new(), authority),
+    };
+    let mut host = host.to_ascii_lowercase();
+    let default_port = match scheme.as_str() {
+        "http" => Some(":80"),
+        "https" => Some(":443"),
+        _ => None,
+    };
+    if let Some(stripped) = default_port.and_then(|port| host.strip_suffix(port)) {
+        host = stripped.into();
+    }
+    if fold_www {
+        if let Some(stripped) = host.strip_prefix("www.") {
+            host = stripped.into();
+        }
+    }
+    let slash = if path.starts_with('/') { "" } else { "/" };
+    format!("{scheme}://{userinfo}{host}{slash}{path}")
+}
+
+/// A queued URL, with the profile and context it was submitted with, if any.
+struct Job {
+    url: String,
+    profile: Option<Arc<WappScanProfile>>,
+    context: Option<WappScanContext>,
+}
+
+struct Worker {
+    fetcher: Arc<dyn WappFetcher>,
+    analyzer: Arc<SharedAnalyzer>,
+    sink: Arc<dyn WappResultSink>,
+    receiver: Arc<Mutex<Receiver<Job>>>,
+    stopped: Arc<AtomicBool>,
+    scope: WappScanScope,
+    /// The profile of URLs submitted without one.
+    profile: Arc<WappScanProfile>,
+}
+
+impl Worker {
+    fn run(self) {
+        loop {
+            let job = self
+                .receiver
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .recv();
+            let Ok(Job {
+                url,
+                profile,
+                context,
+            }) = job
+            else {
+                break;
+            };
+            if self.stopped.load(Ordering::Relaxed) {
+                continue;
+            }
+
+            self.sink.event(WappScanEvent::Started { url: url.clone() });
+            let profile = profile.as_deref().unwrap_or(&self.profile);
+            let result = self.fetch(&url, profile).map(|page| {
+                self.sink.event(WappScanEvent::Fetched {
+                    url: url.clone(),
+                    page_url: page.url().map(String::from),
+                });
+                let mut report = self.scan(&page, profile);
+                report.context = context;
+                for r in &report.results {
+                    self.sink.event(WappScanEvent::Detected {
+                        url: url.clone(),
+                        tech_name: r.tech_name.clone(),
+                        confidence: r.confidence,
+                    });
+                }
+                report
+            });
+            self.sink.scanned(&url, result);
+        }
+    }
+
+    fn fetch(&self, url: &str, profile: &WappScanProfile) -> Result<WappRawPage, Error> {
+        self.scope.check(url)?;
+        let mut page = self.fetcher.fetch(url)?;
+        for _ in 0..profile.max_client_redirects {
+            let Some(target) = page.client_redirect().filter(|&t| t != url) else {
+                break;
+            };
+            let target = target.to_string();
+            self.scope.check(&target)?;
+            page = self.fetcher.fetch(&target)?;
+        }
+        Ok(page)
+    }
+
+    fn scan(&self, page: &WappRawPage, profile: &WappScanProfile) -> WappScanReport {
+        let analyzer = self.analyzer.load();
+        if profile.max_probes == 0 {
+            return analyzer.scan_with_options(page, &profile.check);
+        }
+
+        // Probes are requested on the page's origin, so they cannot leave the scope of the page itself.
+        let probes = analyzer
+            .probe_urls(page)
+            .into_iter()
+            .take(profile.max_probes)
+            .filter_map(|(path, url)| Some((path, self.fetcher.probe(&url)?)))
+            .collect();
+        analyzer.scan_with_options(&ProbedPage { page, probes }, &profile.check)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::{bail, Error};
+
+    use super::{canonicalize, WappFetcher, WappResultSink, WappScanner, WappScannerOptions};
+    use crate::{
+        test_dataset, SharedAnalyzer, WappAnalyzer, WappRawPage, WappScanReport, WappScanScope,
+    };
+
+    struct Fetcher;
+
+    impl WappFetcher for Fetcher {
+        fn fetch(&self, url: &str) -> Result<WappRawPage, Error> {
+            if url.contains("fail") {
+                bail!("connection refused");
+            }
+            if url.contains("parked") {
+                let body = br#"<meta http-equiv="refresh" content="0;url=https://url.test/">"#;
+                return Ok(WappRawPage::new(Some(url), None, Some(body)));
+            }
+            Ok(WappRawPage::new(Some(url), None, None))
+        }
+
+        fn probe(&self, url: &str) -> Option<String> {
+            url.ends_with("/status").then(|| "ok".into())
+        }
+    }
+
+    /// The default scope without DNS lookups, which would make the tests depend on the network.
+    fn test_scope() -> WappScanScope {
+        WappScanScope {
+            resolve_hosts: false,
+            ..Default::default()
+        }
+    }
+
+    #[derive(Default)]
+    struct Sink(Mutex<Vec<(String, bool)>>);
+
+    impl WappResultSink for Sink {
+        fn scanned(&self, url: &str, result: Result<WappScanReport, Error>) {
+            let detected = result.is_ok_and(|r| !r.results.is_empty());
+            self.0.lock().unwrap().push((url.into(), detected));
+        }
+    }
+
+    #[test]
+    fn test_scanner_shutdown() {
+        let sink = Arc::new(Sink::default());
+        let scanner = WappScanner::start(
+            Arc::new(Fetcher),
+            Arc::new(SharedAnalyzer::new(WappAnalyzer::new_test())),
+            sink.clone(),
+            &WappScannerOptions {
+                workers: 2,
+                queue_size: 1,
+                scope: test_scope(),
+                ..Default::default()
+            },
+        );
+
+        for url in [
+            "https://url.test/",
+            "https://other.test/",
+            "https://fail.test/",
+            "http://127.0.0.1/",
+        ] {
+            scanner.submit(url).unwrap();
+        }
+        scanner.shutdown();
+
+        let mut scanned = sink.0.lock().unwrap().clone();
+        scanned.sort();
+        assert_eq!(
+            scanned,
+            [
+                ("http://127.0.0.1/".into(), false),
+                ("https://fail.test/".into(), false),
+                ("https://other.test/".into(), false),
+                ("https://url.test/".into(), true),
+            ]
+        );
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_scanner_client_redirects() {
+        for (max_client_redirects, detected) in [(0, false), (1, true)] {
+            let sink = Arc::new(Sink::default());
+            let scanner = WappScanner::start(
+                Arc::new(Fetcher),
+                Arc::new(SharedAnalyzer::new(WappAnalyzer::new_test())),
+                sink.clone(),
+                &WappScannerOptions {
+                    workers: 1,
+                    max_client_redirects,
+                    scope: test_scope(),
+                    ..Default::default()
+                },
+            );
+            scanner.submit("https://parked.test/").unwrap();
+            scanner.shutdown();
+
+            assert_eq!(
+                *sink.0.lock().unwrap(),
+                [("https://parked.test/".into(), detected)]
+            );
+        }
+    }
+
+    #[test]
+    fn test_scanner_profiles() {
+        let techs = br#"{"Status": {"cats": [], "website": "", "probe": {"/status": "^ok$"}}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let sink = Arc::new(Sink::default());
+        let scanner = WappScanner::start(
+            Arc::new(Fetcher),
+            Arc::new(SharedAnalyzer::new(analyzer)),
+            sink.clone(),
+            &WappScannerOptions {
+                workers: 1,
+                scope: test_scope(),
+                ..Default::default()
+            },
+        );
+        scanner.submit("https://a.test/").unwrap();
+        scanner
+            .submit_with_profile("https://b.test/", "fast")
+            .unwrap();
+        scanner
+            .submit_with_profile("https://c.test/", "thorough")
+            .unwrap();
+        assert!(scanner
+            .submit_with_profile("https://d.test/", "unknown")
+            .is_err());
+        scanner.shutdown();
+
+        assert_eq!(
+            *sink.0.lock().unwrap(),
+            [
+                ("https://a.test/".into(), false),
+                ("https://b.test/".into(), false),
+                ("https://c.test/".into(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        for (url, canonical) in [
+            ("https://Example.COM", "https://example.com/"),
+            ("HTTPS://example.com:443/a?b#c", "https://example.com/a?b"),
+            ("http://example.com:80?q", "http://example.com/?q"),
+            ("http://example.com:443/", "http://example.com:443/"),
+            ("https://user@[::1]:443/", "https://user@[::1]/"),
+            ("example.com/Path", "https://example.com/Path"),
+            ("https://www.example.com/", "https://www.example.com/"),
+        ] {
+            assert_eq!(canonicalize(url, false), canonical, "{url}");
+        }
+        assert_eq!(
+            canonicalize("https://WWW.example.com", true),
+            "https://example.com/"
+        );
+    }
+
+    #[test]
+    fn test_scanner_dedupes() {
+        let sink = Arc::new(Sink::default());
+        let scanner = WappScanner::start(
+            Arc::new(Fetcher),
+            Arc::new(SharedAnalyzer::new(WappAnalyzer::new_test())),
+            sink.clone(),
+            &WappScannerOptions {
+                workers: 1,
+                scope: test_scope(),
+                ..Default::default()
+            },
+        );
+        assert!(scanner.submit("https://URL.test:443/#top").unwrap());
+        assert!(!scanner.submit("url.test").unwrap());
+        assert!(!scanner
+            .submit_with_profile("https://url.test/", "fast")
+            .unwrap());
+        assert!(scanner.submit("https://www.url.test/").unwrap());
+        scanner.shutdown();
+
+        assert_eq!(
+            *sink.0.lock().unwrap(),
+            [
+                ("https://url.test/".into(), true),
+                ("https://www.url.test/".into(), false),
+            ]
+        );
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_scanner_events() {
+        use super::{WappChannelSink, WappScanEvent};
+
+        let (sink, events) = WappChannelSink::new(16);
+        let scanner = WappScanner::start(
+            Arc::new(Fetcher),
+            Arc::new(SharedAnalyzer::new(WappAnalyzer::new_test())),
+            Arc::new(sink),
+            &WappScannerOptions {
+                workers: 1,
+                max_client_redirects: 1,
+                scope: test_scope(),
+                ..Default::default()
+            },
+        );
+        scanner.submit("https://parked.test/").unwrap();
+        scanner.submit("https://fail.test/").unwrap();
+        scanner.shutdown();
+
+        let events: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                WappScanEvent::Started { url } => format!("started {url}"),
+                WappScanEvent::Fetched { url, page_url } => {
+                    format!("fetched {url} {}", page_url.unwrap())
+                }
+                WappScanEvent::Detected { url, tech_name, .. } => {
+                    format!("detected {url} {tech_name}")
+                }
+                WappScanEvent::Finished { url, result } => {
+                    format!("finished {url} {}", result.is_ok())
+                }
+            })
+            .collect();
+        assert_eq!(
+            events,
+            [
+                "started https://parked.test/",
+                "fetched https://parked.test/ https://url.test/",
+                "detected https://parked.test/ Test URL",
+                "finished https://parked.test/ true",
+                "started https://fail.test/",
+                "finished https://fail.test/ false",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scanner_context() {
+        use std::collections::BTreeMap;
+
+        use crate::WappScanContext;
+
+        #[derive(Default)]
+        struct ContextSink(Mutex<Vec<Option<WappScanContext>>>);
+
+        impl WappResultSink for ContextSink {
+            fn scanned(&self, _url: &str, result: Result<WappScanReport, Error>) {
+                self.0.lock().unwrap().push(result.unwrap().context);
+            }
+        }
+
+        let sink = Arc::new(ContextSink::default());
+        let scanner = WappScanner::start(
+            Arc::new(Fetcher),
+            Arc::new(SharedAnalyzer::new(WappAnalyzer::new_test())),
+            sink.clone(),
+            &WappScannerOptions {
+                workers: 1,
+                scope: test_scope(),
+                ..Default::default()
+            },
+        );
+        let context = WappScanContext {
+            target_id: Some("asset-42".into()),
+            labels: BTreeMap::from([("env".into(), "prod".into())]),
+        };
+        scanner
+            .submit_with_context("https://a.test/", Some("fast"), context.clone())
+            .unwrap();
+        assert!(scanner
+            .submit_with_context("https://b.test/", Some("unknown"), context.clone())
+            .is_err());
+        scanner.submit("https://c.test/").unwrap();
+        scanner.shutdown();
+
+        assert_eq!(*sink.0.lock().unwrap(), [Some(context), None]);
+    }
+}
@@ -0,0 +1,274 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
+
+use anyhow::{anyhow, bail, Context, Error};
+
+/// Which URLs a [`WappScanner`](crate::WappScanner) may fetch, so it can be pointed at large third-party lists without
+/// probing internal or out-of-scope infrastructure. By default any public host is in scope, and private, loopback and
+/// link-local addresses are not.
+///
+/// Hostnames are checked against the addresses they resolve to when the URL is checked. The fetcher resolves them again
+/// when it connects, so a host that changes its records in between (DNS rebinding) is not caught here; fetchers that
+/// need that guarantee should connect to the checked addresses.
+#[derive(Debug, Clone)]
+pub struct WappScanScope {
+    /// Domains whose hosts (the domain itself and its subdomains) are in scope. When empty, every domain is.
+    pub allowed_domains: Vec<String>,
+    /// Networks out of scope, as `(address, prefix length)`. See [`exclude_network`](Self::exclude_network).
+    pub excluded_networks: Vec<(IpAddr, u8)>,
+    /// Allow private, loopback, link-local and other non-public addresses.
+    pub allow_private: bool,
+    /// Resolve hostnames to check their addresses against the excluded networks and private ranges. When disabled,
+    /// only URLs with IP address hosts are checked against them.
+    pub resolve_hosts: bool,
+    /// Resolves hostnames for [`resolve_hosts`](Self::resolve_hosts), giving no addresses for hosts that do not
+    /// resolve. Defaults to the system resolver.
+    pub resolver: fn(&str) -> Vec<IpAddr>,
+}
+
+impl Default for WappScanScope {
+    fn default() -> Self {
+        Self {
+            allowed_domains: Vec::new(),
+            excluded_networks: Vec::new(),
+            allow_private: false,
+            resolve_hosts: true,
+            resolver: system_resolver,
+        }
+    }
+}
+
+fn system_resolver(host: &str) -> Vec<IpAddr> {
+    (host, 0)
+        .to_socket_addrs()
+        .map(|addrs| addrs.map(|addr| addr.ip()).collect())
+        .unwrap_or_default()
+}
+
+impl WappScanScope {
+    /// A scope that lets every URL through, for scans of trusted lists or tests against local servers.
+    pub fn unrestricted() -> Self {
+        Self {
+            allow_private: true,
+            resolve_hosts: false,
+            ..Default::default()
+        }
+    }
+
+    /// Restricts the scope to `domain` and its subdomains, in addition to any domain allowed before.
+    pub fn allow_domain(mut self, domain: impl Into<String>) -> Self {
+        self.allowed_domains.push(domain.into());
+        self
+    }
+
+    /// Excludes a network in CIDR notation, e.g. `10.0.0.0/8` or `2001:db8::/32`. A bare address excludes only itself.
+    pub fn exclude_network(mut self, cidr: &str) -> Result<Self, Error> {
+        let (addr, len) = match cidr.split_once('/') {
+            Some((addr, len)) => (addr, Some(len)),
+            None => (cidr, None),
+        };
+        let addr: IpAddr = addr
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid network address {cidr}"))?;
+        let max_len = if addr.is_ipv4() { 32 } else { 128 };
+        let len = match len {
+            Some(len) => len
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|&len| len <= max_len)
+                .ok_or_else(|| anyhow!("Invalid prefix length in {cidr}"))?,
+            None => max_len,
+        };
+        self.excluded_networks.push((addr, len));
+        Ok(self)
+    }
+
+    /// Fails with the reason when `url` is out of scope.
+    pub fn check(&self, url: &str) -> Result<(), Error> {
+        let host = host(url).ok_or_else(|| anyhow!("No host in {url}"))?;
+
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            if !self.allowed_domains.is_empty() {
+                bail!("{host} is not in an allowed domain");
+            }
+            return self.check_ip(ip);
+        }
+
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        if !self.allowed_domains.is_empty()
+            && !self.allowed_domains.iter().any(|d| in_domain(&host, d))
+        {
+            bail!("{host} is not in an allowed domain");
+        }
+        if self.resolve_hosts && (!self.allow_private || !self.excluded_networks.is_empty()) {
+            // Hosts that do not resolve cannot be fetched either, so they are left to fail in the fetcher.
+            for ip in (self.resolver)(&host) {
+                self.check_ip(ip)
+                    .with_context(|| format!("{host} resolves out of scope"))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_ip(&self, ip: IpAddr) -> Result<(), Error> {
+        if !self.allow_private && !is_public(ip) {
+            bail!("{ip} is not a public address");
+        }
+        if let Some((net, len)) = self
+            .excluded_networks
+            .iter()
+            .find(|(net, len)| in_network(ip, *net, *len))
+        {
+            bail!("{ip} is in excluded network {net}/{len}");
+        }
+        Ok(())
+    }
+}
+
+/// The host of an absolute URL, without brackets for IPv6 addresses.
+pub(crate) fn host(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, a)| a);
+    let host = match authority.strip_prefix('[') {
+        Some(rest) => rest.split_once(']')?.0,
+        None => authority.split(':').next()?,
+    };
+    Some(host).filter(|h| !h.is_empty())
+}
+
+fn in_domain(host: &str, domain: &str) -> bool {
+    let domain = domain.trim_matches('.');
+    host.eq_ignore_ascii_case(domain)
+        || host.len() > domain.len()
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+            && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+}
+
+fn in_network(ip: IpAddr, net: IpAddr, len: u8) -> bool {
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        v4 => v4,
+    };
+    match (ip, net) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => {
+            let mask = u32::MAX.checked_shl(32 - len as u32).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) => {
+            let mask = u128::MAX.checked_shl(128 - len as u32).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(net) & mask
+        }
+        _ => false,
+    }
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_public_v4(v4),
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_public_v4(v4),
+            None => is_public_v6(v6),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let shared = in_network(ip.into(), Ipv4Addr::new(100, 64, 0, 0).into(), 10);
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || shared
+        || ip.octets()[0] == 0)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let unique_local = in_network(
+        ip.into(),
+        Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0).into(),
+        7,
+    );
+    let link_local = in_network(
+        ip.into(),
+        Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0).into(),
+        10,
+    );
+    !(ip.is_loopback() || ip.is_unspecified() || unique_local || link_local)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::{host, WappScanScope};
+
+    fn resolver(host: &str) -> Vec<IpAddr> {
+        match host {
+            "localhost" => vec!["127.0.0.1".parse().unwrap(), "::1".parse().unwrap()],
+            "www.example.com" | "example.com" => vec!["93.184.215.14".parse().unwrap()],
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_host() {
+        assert_eq!(
+            host("https://user@Example.com:8080/a?b"),
+            Some("Example.com")
+        );
+        assert_eq!(host("http://[::1]:80/"), Some("::1"));
+        assert_eq!(host("https://example.com#x"), Some("example.com"));
+        assert_eq!(host("example.com"), None);
+    }
+
+    #[test]
+    fn test_scope() {
+        let scope = WappScanScope {
+            resolver,
+            ..Default::default()
+        };
+        assert!(scope.check("https://93.184.215.14/").is_ok());
+        assert!(scope.check("http://127.0.0.1:8080/").is_err());
+        assert!(scope.check("http://[::ffff:10.1.2.3]/").is_err());
+        assert!(scope.check("http://[fd00::1]/").is_err());
+        assert!(scope
+            .check("http://169.254.169.254/latest/meta-data")
+            .is_err());
+        assert!(scope.check("http://localhost/").is_err());
+        assert!(scope.check("not a url").is_err());
+
+        let scope = WappScanScope {
+            resolver,
+            ..Default::default()
+        }
+        .allow_domain("example.com")
+        .exclude_network("10.0.0.0/8")
+        .unwrap();
+        assert!(scope.check("https://www.example.com/").is_ok());
+        assert!(scope.check("https://EXAMPLE.com./").is_ok());
+        assert!(scope.check("https://unresolved.example.com/").is_ok());
+        assert!(scope.check("https://badexample.com/").is_err());
+        assert!(scope.check("https://93.184.215.14/").is_err());
+
+        let scope = WappScanScope {
+            resolver,
+            ..Default::default()
+        }
+        .exclude_network("93.184.0.0/16")
+        .unwrap();
+        assert!(scope.check("https://93.184.215.14/").is_err());
+        assert!(scope.check("https://93.185.215.14/").is_ok());
+        assert!(scope.check("https://example.com/").is_err());
+        assert!(WappScanScope::default()
+            .exclude_network("10.0.0.0/33")
+            .is_err());
+
+        assert!(WappScanScope::unrestricted()
+            .check("http://127.0.0.1/")
+            .is_ok());
+    }
+}
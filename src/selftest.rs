@@ -0,0 +1,312 @@
+use serde::Serialize;
+
+use crate::{test_dataset, WappAnalyzer, WappDnsRecords, WappPage, WappRawPage};
+
+#[cfg(feature = "cookie")]
+use cookie::Cookie;
+
+#[cfg(feature = "http")]
+use http::HeaderMap;
+
+#[cfg(feature = "scraper")]
+use scraper::Html;
+
+#[cfg(feature = "scraper")]
+use crate::WappElementProperties;
+
+/// A page every `Test <detector>` technology of [`WappAnalyzer::new_test`] is detected on.
+const HTML: &str = r#"<html><head>
+<meta name="generator" content="TestMeta 1.1">
+<style>.test-css-22 { display: none; }</style>
+<script src="/assets/test-script.min.js"></script>
+<script>var testScript = "1.3";</script>
+</head><body>
+<!-- test-html 1.4 -->
+<div id="test-dom" data-version="1.5"></div>
+<p>Powered by TestText</p>
+</body></html>"#;
+
+/// A page that resembles [`HTML`] closely but none of the technologies should be detected on.
+const NEGATIVE_HTML: &str = r#"<html><head>
+<meta name="generator" content="OtherMeta 1.1">
+<style>.other-css-22 { display: none; }</style>
+<script src="/assets/other-script.min.js"></script>
+<script>var otherScript = "1.3";</script>
+</head><body>
+<!-- other-html 1.4 -->
+<div id="test-dom"></div>
+<p>Powered by OtherText</p>
+</body></html>"#;
+
+/// `(detector, test technology, expected version)` of the detectors enabled in this build, in check order.
+const EXPECTED: &[(&str, &str, Option<&str>)] = &[
+    ("url", "Test URL", None),
+    ("cert_issuer", "Test Cert Issuer", None),
+    ("dns", "Test DNS", Some("2.0")),
+    ("js", "Test JS", Some("1.7")),
+    #[cfg(feature = "http")]
+    ("headers", "Test Header", Some("1.8")),
+    #[cfg(feature = "cookie")]
+    ("cookies", "Test Cookie", None),
+    #[cfg(feature = "scraper")]
+    ("meta", "Test Meta", Some("1.1")),
+    #[cfg(feature = "scraper")]
+    ("script_src", "Test Script", None),
+    #[cfg(feature = "scraper")]
+    ("scripts", "Test Scripts", Some("1.3")),
+    ("probe", "Test Probe", Some("2.2")),
+    #[cfg(feature = "scraper")]
+    ("css", "Test CSS", Some("22")),
+    #[cfg(feature = "scraper")]
+    ("dom", "Test DOM", Some("1.5")),
+    #[cfg(feature = "scraper")]
+    ("dom_properties", "Test DOM Properties", Some("2.1")),
+    ("html", "Test HTML", Some("1.4")),
+    #[cfg(feature = "scraper")]
+    ("text", "Test Text", None),
+    ("implies", "Test Implied", None),
+];
+
+/// Outcome of [`WappAnalyzer::self_test`].
+#[derive(Debug, Clone, Serialize)]
+pub struct WappSelfTestReport {
+    /// Why the built-in test dataset failed to load, in which case every detector failed.
+    pub load_error: Option<String>,
+    /// Outcome of every detector enabled in this build, in check order.
+    pub detectors: Vec<WappSelfTestDetector>,
+    /// Number of technologies of the analyzer's own dataset.
+    pub tech_count: usize,
+    /// Technologies of the analyzer's own dataset that refer to categories it does not have, sorted.
+    pub unknown_categories: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WappSelfTestDetector {
+    /// Detector name, as reported in [`WappScanStats`](crate::WappScanStats).
+    pub detector: &'static str,
+    pub passed: bool,
+    /// What went wrong when the detector failed.
+    pub error: Option<String>,
+}
+
+impl WappSelfTestReport {
+    /// Whether every detector passed and the analyzer's dataset is loaded and consistent.
+    pub fn passed(&self) -> bool {
+        self.load_error.is_none()
+            && self.detectors.iter().all(|d| d.passed)
+            && self.tech_count > 0
+            && self.unknown_categories.is_empty()
+    }
+}
+
+impl WappAnalyzer {
+    /// Checks built-in synthetic pages against the [test dataset](WappAnalyzer::new_test), which has one technology per
+    /// detector, and reports which detectors fired as expected, along with basic sanity checks of this analyzer's
+    /// dataset. Every detector is checked on a page it should fire on and on a near miss it should not fire on. Meant
+    /// as a smoke test when deploying a build, to catch missing features, a missing dataset or broken pattern
+    /// translation.
+    pub fn self_test(&self) -> WappSelfTestReport {
+        let mut unknown_categories: Vec<_> = self
+            .techs
+            .values()
+            .filter(|t| t.cats.iter().any(|id| !self.cats.contains_key(id)))
+            .map(|t| t.name.clone())
+            .collect();
+        unknown_categories.sort();
+
+        let (load_error, results, negative_results) = match test_dataset::load() {
+            Ok(test) => (
+                None,
+                test.check(&ProbePage::positive()),
+                test.check(&ProbePage::negative()),
+            ),
+            Err(err) => (Some(format!("{err:#}")), Vec::new(), Vec::new()),
+        };
+
+        let detectors = EXPECTED
+            .iter()
+            .map(|&(detector, tech_name, version)| {
+                let error = match results.iter().find(|r| r.tech_name == tech_name) {
+                    None => Some("not detected".to_string()),
+                    Some(r) if r.version.as_deref() != version => Some(format!(
+                        "detected version {:?} instead of {version:?}",
+                        r.version
+                    )),
+                    Some(_) if negative_results.iter().any(|r| r.tech_name == tech_name) => {
+                        Some("detected on a page without it".to_string())
+                    }
+                    Some(_) => None,
+                };
+                WappSelfTestDetector {
+                    detector,
+                    passed: error.is_none(),
+                    error,
+                }
+            })
+            .collect();
+
+        WappSelfTestReport {
+            load_error,
+            detectors,
+            tech_count: self.techs.len(),
+            unknown_categories,
+        }
+    }
+}
+
+/// A synthetic page, with what [`WappRawPage`] cannot derive from the response provided on top.
+struct ProbePage {
+    raw: WappRawPage,
+    cert_issuer: &'static str,
+    dns: WappDnsRecords,
+    js: serde_json::Value,
+    probes: Vec<(String, String)>,
+    #[cfg(feature = "scraper")]
+    dom_properties: Vec<WappElementProperties>,
+}
+
+impl ProbePage {
+    /// The page every detector should fire on.
+    fn positive() -> Self {
+        Self {
+            raw: raw_page(
+                "https://url.test/",
+                "TestServer/1.8",
+                "test_session=1.9",
+                HTML,
+            ),
+            cert_issuer: "Test CA Ltd",
+            dns: WappDnsRecords {
+                txt: vec!["test-verification=2.0".into()],
+                ..Default::default()
+            },
+            js: serde_json::json!({"test": {"version": "1.7"}}),
+            probes: vec![("/test.txt".into(), "test 2.2".into())],
+            #[cfg(feature = "scraper")]
+            dom_properties: vec![WappElementProperties {
+                selector: "#test-props".into(),
+                properties: vec![("testVersion".into(), "2.1".into())],
+            }],
+        }
+    }
+
+    /// The near miss no detector should fire on.
+    fn negative() -> Self {
+        Self {
+            raw: raw_page(
+                "https://other.test/url.test/",
+                "OtherServer/1.8",
+                "other_session=1.9",
+                NEGATIVE_HTML,
+            ),
+            cert_issuer: "Other CA Ltd",
+            dns: WappDnsRecords {
+                txt: vec!["other-verification=2.0".into()],
+                ..Default::default()
+            },
+            js: serde_json::json!({"test": {"build": "1.7"}}),
+            probes: vec![("/test.txt".into(), "other 2.2".into())],
+            #[cfg(feature = "scraper")]
+            dom_properties: vec![WappElementProperties {
+                selector: "#test-props".into(),
+                properties: vec![("otherVersion".into(), "2.1".into())],
+            }],
+        }
+    }
+}
+
+fn raw_page(url: &str, server: &str, cookie: &str, html: &str) -> WappRawPage {
+    let headers = [
+        ("Server".to_string(), server.to_string()),
+        ("Set-Cookie".to_string(), cookie.to_string()),
+    ];
+    WappRawPage::new(Some(url), Some(&headers), Some(html.as_bytes()))
+}
+
+impl WappPage for ProbePage {
+    fn url(&self) -> Option<&str> {
+        self.raw.url()
+    }
+
+    #[cfg(feature = "http")]
+    fn headers(&self) -> Option<&HeaderMap> {
+        self.raw.headers()
+    }
+
+    #[cfg(feature = "cookie")]
+    fn cookies(&self) -> Option<&[Cookie<'_>]> {
+        self.raw.cookies()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn dom(&self) -> Option<&Html> {
+        self.raw.dom()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn meta(&self) -> Option<&[(String, String)]> {
+        self.raw.meta()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn script_src(&self) -> Option<&[String]> {
+        self.raw.script_src()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn scripts(&self) -> Option<&[String]> {
+        self.raw.scripts()
+    }
+
+    #[cfg(feature = "scraper")]
+    fn dom_properties(&self) -> Option<&[WappElementProperties]> {
+        Some(&self.dom_properties)
+    }
+
+    fn cert_issuer(&self) -> Option<&str> {
+        Some(self.cert_issuer)
+    }
+
+    fn dns(&self) -> Option<&WappDnsRecords> {
+        Some(&self.dns)
+    }
+
+    fn js(&self) -> Option<&serde_json::Value> {
+        Some(&self.js)
+    }
+
+    fn probes(&self) -> Option<&[(String, String)]> {
+        Some(&self.probes)
+    }
+
+    fn css(&self) -> Option<&str> {
+        self.raw.css()
+    }
+
+    fn html(&self) -> Option<&str> {
+        self.raw.html()
+    }
+
+    fn text(&self) -> Option<&str> {
+        self.raw.text()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::WappAnalyzer;
+
+    #[test]
+    fn test_self_test() {
+        let report = WappAnalyzer::new_test().self_test();
+        assert_eq!(report.load_error, None);
+        for detector in &report.detectors {
+            assert!(detector.passed, "{detector:?}");
+        }
+        assert!(report.passed());
+
+        let report = WappAnalyzer::new_empty().self_test();
+        assert!(report.detectors.iter().all(|d| d.passed));
+        assert!(!report.passed());
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use crate::{WappAnalyzer, WappTech};
+
+/// A handle to an analyzer that can be replaced atomically while other threads keep checking pages.
+///
+/// Readers call [`load`](Self::load) to get a snapshot and keep using it for as long as they need; a concurrent
+/// [`swap`](Self::swap) only affects later loads, and the old analyzer is dropped once its last snapshot is gone.
+///
+/// Technologies can also be changed in place with [`update`](Self::update), e.g. to adjust custom fingerprints in a
+/// long-running service without pausing scanning.
+#[derive(Debug)]
+pub struct SharedAnalyzer {
+    inner: RwLock<Arc<WappAnalyzer>>,
+    update: Mutex<()>,
+}
+
+impl SharedAnalyzer {
+    pub fn new(analyzer: WappAnalyzer) -> Self {
+        Self {
+            inner: RwLock::new(Arc::new(analyzer)),
+            update: Mutex::new(()),
+        }
+    }
+
+    /// Returns the current analyzer.
+    pub fn load(&self) -> Arc<WappAnalyzer> {
+        // The lock is only held to clone or replace an `Arc`, which cannot leave it in an inconsistent state.
+        self.inner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replaces the current analyzer, returning the previous one.
+    pub fn swap(&self, analyzer: WappAnalyzer) -> Arc<WappAnalyzer> {
+        let mut guard = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        std::mem::replace(&mut *guard, Arc::new(analyzer))
+    }
+
+    /// Applies `f` to a copy of the current analyzer and makes the copy current, returning what `f` returns. Checks in
+    /// progress keep the analyzer they loaded, and loads are not blocked while `f` runs. Updates are serialized, so
+    /// concurrent ones are not lost, but a concurrent [`swap`](Self::swap) may be overwritten.
+    pub fn update<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut WappAnalyzer) -> R,
+    {
+        let _update = self.update.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut analyzer = (*self.load()).clone();
+        let result = f(&mut analyzer);
+        self.swap(analyzer);
+        result
+    }
+
+    /// Adds a technology, replacing and returning any technology of the same name.
+    pub fn add_tech(&self, tech: WappTech) -> Option<WappTech> {
+        self.update(|analyzer| {
+            analyzer.custom_techs.remove(&tech.name);
+            analyzer.shadowed_techs.remove(&tech.name);
+            analyzer.techs.insert(tech.name.clone(), tech)
+        })
+    }
+
+    /// Removes the named technology, returning it if it existed.
+    pub fn remove_tech(&self, name: &str) -> Option<WappTech> {
+        self.update(|analyzer| {
+            analyzer.custom_techs.remove(name);
+            analyzer.shadowed_techs.remove(name);
+            analyzer.techs.remove(name)
+        })
+    }
+}
+
+impl From<WappAnalyzer> for SharedAnalyzer {
+    fn from(analyzer: WappAnalyzer) -> Self {
+        Self::new(analyzer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use super::SharedAnalyzer;
+    use crate::WappAnalyzer;
+
+    #[test]
+    fn test_swap_keeps_loaded_snapshot() {
+        let shared = Arc::new(SharedAnalyzer::new(WappAnalyzer::new_empty()));
+        let before = shared.load();
+
+        let next = WappAnalyzer::new_test();
+
+        let handle = {
+            let shared = shared.clone();
+            thread::spawn(move || shared.swap(next))
+        };
+        let previous = handle.join().unwrap();
+
+        assert!(Arc::ptr_eq(&before, &previous));
+        assert!(before.techs.is_empty());
+        assert_eq!(shared.load().techs.len(), 16);
+    }
+
+    #[test]
+    fn test_concurrent_updates() {
+        let custom = WappAnalyzer::new_test();
+        let shared = Arc::new(SharedAnalyzer::new(WappAnalyzer::new_empty()));
+        let before = shared.load();
+
+        let handles: Vec<_> = custom
+            .techs
+            .into_values()
+            .map(|tech| {
+                let shared = shared.clone();
+                thread::spawn(move || shared.add_tech(tech))
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap().is_none());
+        }
+
+        assert!(before.techs.is_empty());
+        assert_eq!(shared.load().techs.len(), 16);
+        assert_eq!(shared.remove_tech("Test URL").unwrap().name, "Test URL");
+        assert!(shared.remove_tech("Test URL").is_none());
+        assert_eq!(shared.load().techs.len(), 15);
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+
+use crate::{WappAnalyzer, WappCheckOptions, WappCheckResult, WappDetectorKinds, WappPage};
+
+impl WappAnalyzer {
+    /// First stage of a staged check: runs only the [`cheap`](WappDetectorKinds::CHEAP) detectors of
+    /// [`WappCheckOptions::detectors`] for every technology. The results are candidates for
+    /// [`deep_check`](Self::deep_check) and are not post-processed.
+    ///
+    /// [`WappCheckOptions::min_confidence`] is left to [`deep_check`](Self::deep_check), as the expensive detectors
+    /// may confirm a weak candidate.
+    pub fn quick_check<P: WappPage>(
+        &self,
+        page: &P,
+        options: &WappCheckOptions,
+    ) -> Vec<WappCheckResult> {
+        let options = WappCheckOptions {
+            min_confidence: 0,
+            detectors: options.detectors & WappDetectorKinds::CHEAP,
+            ..options.clone()
+        };
+        self.check_techs(page, &options, self.techs.values(), false)
+    }
+
+    /// Second stage of a staged check: checks every signal of a page, but only for the `candidates` from
+    /// [`quick_check`](Self::quick_check) and the technologies that require one of them, by name or by category.
+    ///
+    /// Technologies that cannot be detected from cheap signals are only found if they require a candidate, which is
+    /// the trade-off for not running the expensive detectors for every technology.
+    pub fn deep_check<P: WappPage>(
+        &self,
+        page: &P,
+        candidates: &[WappCheckResult],
+        options: &WappCheckOptions,
+    ) -> Vec<WappCheckResult> {
+        let names: HashSet<&str> = candidates.iter().map(|r| r.tech_name.as_str()).collect();
+        let cats: HashSet<i32> = names
+            .iter()
+            .filter_map(|name| self.techs.get(*name))
+            .flat_map(|tech| tech.cats.iter().copied())
+            .collect();
+
+        let techs = self.techs.values().filter(|tech| {
+            names.contains(tech.name.as_str())
+                || tech.requires.iter().any(|r| names.contains(r.as_str()))
+                || tech.requires_category.iter().any(|c| cats.contains(c))
+        });
+        self.check_techs(page, options, techs, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_dataset, WappCheckOptions, WappDetectorKinds, WappRawPage};
+
+    #[test]
+    fn test_staged_check() {
+        let techs = br#"{
+            "CMS": {"cats": [1], "website": "", "url": "/cms/", "html": "cms/([\\d.]+)\\;version:\\1"},
+            "Theme": {"cats": [2], "website": "", "html": "theme", "requires": "CMS"},
+            "Plugin": {"cats": [3], "website": "", "html": "plugin", "requiresCategory": 1},
+            "Other": {"cats": [4], "website": "", "html": "other"}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let page = WappRawPage::new(
+            Some("https://example.com/cms/"),
+            None,
+            Some(b"cms/1.0 theme plugin other"),
+        );
+
+        let options = WappCheckOptions::default();
+        let candidates = analyzer.quick_check(&page, &options);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].version, None);
+
+        let mut results = analyzer.deep_check(&page, &candidates, &options);
+        results.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+        let names: Vec<_> = results.iter().map(|r| r.tech_name.as_str()).collect();
+        assert_eq!(names, ["CMS", "Plugin", "Theme"]);
+        assert_eq!(results[0].version.as_deref(), Some("1.0"));
+
+        let options = WappCheckOptions {
+            detectors: WappDetectorKinds::URL,
+            ..Default::default()
+        };
+        let results = analyzer.deep_check(&page, &candidates, &options);
+        let names: Vec<_> = results.iter().map(|r| r.tech_name.as_str()).collect();
+        assert_eq!(names, ["CMS"]);
+        assert_eq!(results[0].version, None);
+
+        let options = WappCheckOptions {
+            detectors: WappDetectorKinds::HTML,
+            ..Default::default()
+        };
+        assert!(analyzer.quick_check(&page, &options).is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+use std::{collections::HashMap, io::Read};
+
+use anyhow::{Context, Error};
+use regex::Regex;
+
+use crate::{
+    tech::{Tagged, WappTechCheckResult},
+    WappAnalyzer, WappCheckResult, WappTech,
+};
+
+/// Window sizes used when checking a page from a reader.
+#[derive(Debug, Clone)]
+pub struct WappStreamOptions {
+    /// Number of bytes checked per window.
+    pub window: usize,
+    /// Number of bytes carried over from the previous window, so matches straddling a boundary are still found.
+    /// Defaults to the length of the longest pattern source, which bounds any literal a pattern can match. Patterns
+    /// with unbounded repetitions can still miss matches that are longer than this.
+    pub overlap: Option<usize>,
+}
+
+impl Default for WappStreamOptions {
+    fn default() -> Self {
+        Self {
+            window: 1 << 20,
+            overlap: None,
+        }
+    }
+}
+
+impl WappAnalyzer {
+    /// Same as checking [`WappPage::html`](crate::WappPage::html), but reads the HTML source from `reader` in windows
+    /// so that memory use stays constant regardless of page size.
+    pub fn check_html_reader<R: Read>(
+        &self,
+        reader: R,
+        options: &WappStreamOptions,
+    ) -> Result<Vec<WappCheckResult>, Error> {
+        self.check_reader(reader, options, |t| &t.html, WappTech::check_html)
+    }
+
+    /// Same as checking [`WappPage::text`](crate::WappPage::text), but reads the text from `reader` in windows so that
+    /// memory use stays constant regardless of page size.
+    pub fn check_text_reader<R: Read>(
+        &self,
+        reader: R,
+        options: &WappStreamOptions,
+    ) -> Result<Vec<WappCheckResult>, Error> {
+        self.check_reader(reader, options, |t| &t.text, WappTech::check_text)
+    }
+
+    fn check_reader<R, P, C>(
+        &self,
+        mut reader: R,
+        options: &WappStreamOptions,
+        patterns: P,
+        check: C,
+    ) -> Result<Vec<WappCheckResult>, Error>
+    where
+        R: Read,
+        P: Fn(&WappTech) -> &Vec<Tagged<Regex>>,
+        C: Fn(&WappTech, &str) -> Option<WappTechCheckResult>,
+    {
+        let overlap = options.overlap.unwrap_or_else(|| {
+            self.techs
+                .values()
+                .flat_map(|t| patterns(t).iter())
+                .map(|p| p.inner.as_str().len())
+                .max()
+                .unwrap_or(0)
+        });
+        // Always make progress, even with a window smaller than a multi-byte character.
+        let window = options.window.max(4);
+
+        let techs: Vec<&WappTech> = self
+            .techs
+            .values()
+            .filter(|t| !patterns(t).is_empty())
+            .collect();
+        let mut best: HashMap<&str, WappCheckResult> = HashMap::new();
+        let mut buf = Vec::with_capacity(window + overlap);
+
+        loop {
+            let want = (window + overlap - buf.len()) as u64;
+            reader
+                .by_ref()
+                .take(want)
+                .read_to_end(&mut buf)
+                .context("Failed to read page")?;
+            let eof = buf.len() < window + overlap;
+
+            let cut = if eof { buf.len() } else { complete_len(&buf) };
+            let chunk = String::from_utf8_lossy(&buf[..cut]);
+
+            for tech in &techs {
+                let Some(r) = check(tech, &chunk) else {
+                    continue;
+                };
+                let better = best
+                    .get(tech.name.as_str())
+                    .is_none_or(|b| r.confidence > b.confidence);
+                if better {
+                    best.insert(
+                        &tech.name,
+                        WappCheckResult {
+                            tech_name: tech.name.clone(),
+                            confidence: r.confidence,
+                            version: r.version,
+                            version_captures: r.version_captures,
+                            version_candidates: r.version_candidates,
+                            sources: Vec::new(),
+                            evidence: Vec::new(),
+                        },
+                    );
+                }
+            }
+
+            if eof {
+                break;
+            }
+            let keep_from = char_start(&buf, cut.saturating_sub(overlap));
+            buf.drain(..keep_from.max(1));
+        }
+
+        let mut results: Vec<_> = best.into_values().collect();
+        results.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+        Ok(results)
+    }
+}
+
+/// Returns the length of `buf` without a trailing incomplete UTF-8 sequence.
+fn complete_len(buf: &[u8]) -> usize {
+    let len = buf.len();
+    for back in 1..=len.min(3) {
+        let b = buf[len - back];
+        if b & 0b1100_0000 == 0b1000_0000 {
+            continue;
+        }
+        let need = match b {
+            0xF0.. => 4,
+            0xE0.. => 3,
+            0xC0.. => 2,
+            _ => 1,
+        };
+        return if need > back { len - back } else { len };
+    }
+    len
+}
+
+/// Moves `pos` back to the start of the UTF-8 character it falls in.
+fn char_start(buf: &[u8], mut pos: usize) -> usize {
+    while pos > 0 && pos < buf.len() && buf[pos] & 0b1100_0000 == 0b1000_0000 {
+        pos -= 1;
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WappStreamOptions;
+    use crate::test_dataset;
+
+    #[test]
+    fn test_check_html_reader_across_windows() {
+        let techs = br#"{
+            "Boundary": {"cats": [], "website": "", "html": "<meta name=\"boundary\" content=\"v([\\d.]+)\"\\;version:\\1"},
+            "Late": {"cats": [], "website": "", "html": "<late-tag>"}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+
+        let mut page = "\u{1F980}".repeat(30);
+        page.push_str(r#"<meta name="boundary" content="v1.2.3">"#);
+        page.push_str(&"x".repeat(500));
+        page.push_str("<late-tag>");
+
+        let options = WappStreamOptions {
+            window: 128,
+            overlap: None,
+        };
+        let mut results = analyzer
+            .check_html_reader(page.as_bytes(), &options)
+            .unwrap();
+        results.sort_by(|a, b| a.tech_name.cmp(&b.tech_name));
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].tech_name, "Boundary");
+        assert_eq!(results[0].version.as_deref(), Some("1.2.3"));
+        assert_eq!(results[0].version_captures[1].as_deref(), Some("1.2.3"));
+        assert_eq!(results[1].tech_name, "Late");
+    }
+
+    #[test]
+    fn test_complete_len() {
+        let s = "a\u{1F980}".as_bytes();
+        assert_eq!(super::complete_len(s), 5);
+        assert_eq!(super::complete_len(&s[..4]), 1);
+        assert_eq!(super::complete_len(&s[..2]), 1);
+        assert_eq!(super::complete_len(&s[..1]), 1);
+    }
+}
@@ -1,37 +1,57 @@
 use regex::{Captures, Regex};
 
-use crate::WappPage;
+use std::{borrow::Cow, collections::HashMap, time::Instant};
 
-use super::{Tagged, WappTech, WappTechCheckResult, WappTechVersionPattern, WappTechVersionValue};
+use crate::{
+    WappCheckOptions, WappDetectorKinds, WappDnsRecordType, WappDnsRecords, WappPage,
+    WappResultSource, WappScanStats,
+};
+
+use super::{
+    explain::Explain, Tagged, WappTech, WappTechCheckResult, WappTechVersionPattern,
+    WappTechVersionValue,
+};
 
 #[cfg(feature = "cookie")]
 use cookie::Cookie;
 
+#[cfg(feature = "cookie")]
+use super::WappCookieName;
+
 #[cfg(feature = "http")]
 use http::{HeaderMap, HeaderValue};
 
 #[cfg(feature = "scraper")]
-use std::sync::LazyLock;
+use scraper::Html;
 
 #[cfg(feature = "scraper")]
-use scraper::{Html, Selector};
+use super::WappTechDomPatttern;
 
 #[cfg(feature = "scraper")]
-use super::WappTechDomPatttern;
+use crate::{
+    page::{inline_scripts, meta_tags, script_srcs, style_blocks},
+    url, WappElementProperties,
+};
 
+/// Resolves version templates against the captures of a match. Patterns that only test for presence (e.g. dom
+/// `exists`) resolve without captures: variables are treated as unmatched groups, so `\\1` yields no version and
+/// `\\1?a:b` yields `b`.
 trait ResolveVersion {
     type Version;
 
-    fn resolve(&self, captures: Captures) -> Self::Version;
+    fn resolve(&self, captures: Option<&Captures>) -> Self::Version;
 }
 
 impl ResolveVersion for WappTechVersionValue {
-    type Version = String;
+    type Version = Option<String>;
 
-    fn resolve(&self, captures: Captures) -> Self::Version {
+    /// Returns `None` when the referenced capture group does not exist or did not participate in the match.
+    fn resolve(&self, captures: Option<&Captures>) -> Self::Version {
         match self {
-            WappTechVersionValue::Const(s) => s.clone(),
-            WappTechVersionValue::Var(i) => captures[*i].into(),
+            WappTechVersionValue::Const(s) => Some(s.clone()),
+            WappTechVersionValue::Var(i) => {
+                captures.and_then(|c| c.get(*i)).map(|m| m.as_str().into())
+            }
         }
     }
 }
@@ -39,96 +59,330 @@ impl ResolveVersion for WappTechVersionValue {
 impl ResolveVersion for Option<WappTechVersionValue> {
     type Version = Option<String>;
 
-    fn resolve(&self, captures: Captures) -> Self::Version {
-        self.as_ref().map(|x| x.resolve(captures))
+    fn resolve(&self, captures: Option<&Captures>) -> Self::Version {
+        self.as_ref().and_then(|x| x.resolve(captures))
     }
 }
 
+impl ResolveVersion for WappTechVersionPattern {
+    type Version = Option<String>;
+
+    fn resolve(&self, captures: Option<&Captures>) -> Self::Version {
+        match self {
+            WappTechVersionPattern::Always(s) => s.resolve(captures),
+            WappTechVersionPattern::Conditional {
+                cond_var,
+                true_expr,
+                false_expr,
+            } => match captures.and_then(|c| c.get(*cond_var)) {
+                Some(_) => true_expr.resolve(captures),
+                None => false_expr.resolve(captures),
+            },
+        }
+    }
+}
+
+/// Adds the result of one pattern to those of the patterns checked before it, like upstream does: confidences add up
+/// to at most 100 and the version comes from the most confident pattern that has one. Every pattern is checked, even
+/// once the combined confidence reaches 100, so that the versions of all of them are among the candidates.
 macro_rules! handle_check_result {
     ($check_call:expr, $best_result:ident) => {
         if let Some(__result) = $check_call {
-            if __result.confidence >= 100 {
-                return Some(__result);
+            $best_result = Some(match $best_result.take() {
+                Some(__best) => __best.combine(__result),
+                None => __result,
+            });
+        }
+    };
+}
+
+impl WappTechCheckResult {
+    fn combine(mut self, mut other: Self) -> Self {
+        let mut version_candidates = std::mem::take(&mut self.version_candidates);
+        add_version_candidates(
+            &mut version_candidates,
+            other.version_candidates.iter().cloned(),
+        );
+        let mut sources = std::mem::take(&mut self.sources);
+        sources.append(&mut other.sources);
+
+        let confidence = self.confidence.saturating_add(other.confidence).min(100);
+        let (best, other) = if other.confidence > self.confidence {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        if best.version.is_none() && other.version.is_some() {
+            Self {
+                confidence,
+                version_candidates,
+                sources,
+                ..other
             }
-            if __result.confidence > $best_result.as_ref().map(|x| x.confidence).unwrap_or(0) {
-                $best_result = Some(__result);
+        } else {
+            Self {
+                confidence,
+                version_candidates,
+                sources,
+                ..best
             }
         }
-    };
+    }
+
+    /// Attributes the whole result to `detector`.
+    fn attributed_to(mut self, detector: &'static str) -> Self {
+        self.sources = vec![WappResultSource {
+            detector,
+            confidence: self.confidence,
+        }];
+        self
+    }
+
+    /// Caps the contribution of each detector, and adds them up again.
+    fn cap_sources(&mut self, caps: &HashMap<String, i32>) {
+        for source in &mut self.sources {
+            if let Some(&cap) = caps.get(source.detector) {
+                source.confidence = source.confidence.min(cap);
+            }
+        }
+        self.confidence = self
+            .sources
+            .iter()
+            .fold(0, |sum: i32, s| sum.saturating_add(s.confidence))
+            .min(100);
+    }
+}
+
+fn add_version_candidates(
+    candidates: &mut Vec<String>,
+    versions: impl IntoIterator<Item = String>,
+) {
+    for version in versions {
+        if !candidates.contains(&version) {
+            candidates.push(version);
+        }
+    }
+}
+
+/// Checks a pattern against several inputs. The pattern counts once, for the first input it matches, but the versions
+/// of its other matches are added to the candidates, e.g. for two copies of a library loaded from different URLs.
+///
+/// When the inputs are URLs, `page_site` is the [site](crate::url::site) of the page, to classify the evidence of each
+/// input as first- or third-party.
+#[cfg(feature = "scraper")]
+fn check_each<'i>(
+    pat: &Tagged<Regex>,
+    inputs: impl IntoIterator<Item = &'i str>,
+    mut explain: Option<&mut Explain>,
+    page_site: Option<&str>,
+) -> Option<WappTechCheckResult> {
+    let mut found: Option<WappTechCheckResult> = None;
+    for input in inputs {
+        let mut explain = explain.as_deref_mut().filter(|_| found.is_none());
+        if let (Some(explain), Some(page_site)) = (explain.as_deref_mut(), page_site) {
+            explain.third_party =
+                url::site(input).map(|site| !site.eq_ignore_ascii_case(page_site));
+        }
+        let Some(result) = pat.check_explained(input, explain) else {
+            continue;
+        };
+        match &mut found {
+            Some(found) => {
+                add_version_candidates(&mut found.version_candidates, result.version_candidates)
+            }
+            // Inputs after the first match can only add versions.
+            None if pat.version.is_none() => return Some(result),
+            None => found = Some(result),
+        }
+    }
+    found
+}
+
+/// [`check_each`] for every pattern of a detector.
+#[cfg(feature = "scraper")]
+fn check_each_pattern(
+    pats: &[Tagged<Regex>],
+    inputs: &[String],
+    mut explain: Option<&mut Explain>,
+    page_site: Option<&str>,
+) -> Option<WappTechCheckResult> {
+    let mut best_result: Option<WappTechCheckResult> = None;
+
+    for pat in pats {
+        handle_check_result!(
+            check_each(
+                pat,
+                inputs.iter().map(String::as_str),
+                explain.as_deref_mut(),
+                page_site
+            ),
+            best_result
+        );
+    }
+
+    best_result
+}
+
+/// Runs a detector, adding its running time to `$stats` when collecting telemetry. The result, and the evidence
+/// `$explain` records meanwhile, are attributed to the detector.
+macro_rules! timed {
+    ($stats:ident, $explain:ident, $detector:literal, $call:expr) => {{
+        if let Some(__explain) = $explain.as_deref_mut() {
+            __explain.detector = $detector;
+            __explain.key = None;
+            __explain.third_party = None;
+        }
+        let __result: Option<WappTechCheckResult> = match $stats.as_deref_mut() {
+            Some(__stats) => {
+                let __start = Instant::now();
+                let __result = $call;
+                __stats.add_timing($detector, __start.elapsed());
+                __result
+            }
+            None => $call,
+        };
+        __result.map(|r| r.attributed_to($detector))
+    }};
 }
 
 trait WappTechCheck<T> {
-    fn check(&self, input: T) -> Option<WappTechCheckResult>;
+    /// Checks `input`, recording the patterns that match in `explain` when given.
+    fn check_explained(
+        &self,
+        input: T,
+        explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult>;
+
+    fn check(&self, input: T) -> Option<WappTechCheckResult> {
+        self.check_explained(input, None)
+    }
 }
 
+/// Presence patterns have nothing to show for themselves, so their callers record the evidence.
 impl WappTechCheck<()> for Tagged<()> {
-    fn check(&self, _input: ()) -> Option<WappTechCheckResult> {
+    fn check_explained(
+        &self,
+        _input: (),
+        _explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
+        let version = self.version.as_ref().and_then(|v| v.resolve(None));
         Some(WappTechCheckResult {
             confidence: self.confidence,
-            version: match &self.version {
-                Some(WappTechVersionPattern::Always(WappTechVersionValue::Const(s))) => Some(s.clone()),
-                Some(_) => unreachable!(),
-                None => None,
-            },
+            version_candidates: version.iter().cloned().collect(),
+            version,
+            version_captures: Vec::new(),
+            sources: Vec::new(),
         })
     }
 }
 
 impl WappTechCheck<&str> for Tagged<Regex> {
-    fn check(&self, input: &str) -> Option<WappTechCheckResult> {
+    fn check_explained(
+        &self,
+        input: &str,
+        explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
         let captures = self.inner.captures(input)?;
+        if let Some(explain) = explain {
+            explain.push(self.inner.as_str(), &captures[0]);
+        }
+        let version = self
+            .version
+            .as_ref()
+            .and_then(|v| v.resolve(Some(&captures)));
 
         Some(WappTechCheckResult {
             confidence: self.confidence,
-            version: match &self.version {
-                None => None,
-                Some(WappTechVersionPattern::Always(s)) => Some(s.resolve(captures)),
-                Some(WappTechVersionPattern::Conditional {
-                    cond_var,
-                    true_expr,
-                    false_expr,
-                }) => match captures.get(*cond_var) {
-                    Some(_) => true_expr.resolve(captures),
-                    None => false_expr.resolve(captures),
-                },
+            version_candidates: version.iter().cloned().collect(),
+            version,
+            version_captures: match self.version {
+                Some(_) => captures
+                    .iter()
+                    .map(|m| m.map(|m| m.as_str().to_string()))
+                    .collect(),
+                None => Vec::new(),
             },
+            sources: Vec::new(),
         })
     }
 }
 
+impl Tagged<Regex> {
+    pub(crate) fn check_str(&self, input: &str) -> Option<WappTechCheckResult> {
+        self.check(input)
+    }
+}
+
 impl<P, T> WappTechCheck<T> for Vec<P>
 where
     P: WappTechCheck<T>,
     T: Copy,
 {
-    fn check(&self, input: T) -> Option<WappTechCheckResult> {
+    fn check_explained(
+        &self,
+        input: T,
+        mut explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
         let mut best_result: Option<WappTechCheckResult> = None;
 
         for pat in self {
-            handle_check_result!(pat.check(input), best_result);
+            handle_check_result!(
+                pat.check_explained(input, explain.as_deref_mut()),
+                best_result
+            );
         }
 
         best_result
     }
 }
 
+/// Values that are not visible ASCII are decoded as UTF-8, replacing invalid sequences, so that presence patterns
+/// (`""`) still match them.
 #[cfg(feature = "http")]
 impl WappTechCheck<&HeaderValue> for Tagged<Regex> {
-    fn check(&self, input: &HeaderValue) -> Option<WappTechCheckResult> {
-        self.check(input.to_str().ok()?)
+    fn check_explained(
+        &self,
+        input: &HeaderValue,
+        explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
+        match input.to_str() {
+            Ok(value) => self.check_explained(value, explain),
+            Err(_) => {
+                self.check_explained(String::from_utf8_lossy(input.as_bytes()).as_ref(), explain)
+            }
+        }
     }
 }
 
+/// Each pattern counts once, for the first value of its header that it matches. A repeated header is also matched as
+/// one value joined with `, `, the way upstream sees it through `Headers.get`.
 #[cfg(feature = "http")]
 impl WappTechCheck<&HeaderMap> for Vec<(String, Vec<Tagged<Regex>>)> {
-    fn check(&self, input: &HeaderMap) -> Option<WappTechCheckResult> {
+    fn check_explained(
+        &self,
+        input: &HeaderMap,
+        mut explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
         let mut best_result: Option<WappTechCheckResult> = None;
 
-        for (header_key, header_value) in input {
-            for (pat_key, pat) in self {
-                if pat_key.eq_ignore_ascii_case(header_key.as_str()) {
-                    handle_check_result!(pat.check(header_value), best_result);
+        for (pat_key, pats) in self {
+            if let Some(explain) = explain.as_deref_mut() {
+                explain.key = Some(pat_key.clone());
+            }
+            let values = input.get_all(pat_key.as_str());
+            let joined = match values.iter().count() {
+                0 | 1 => None,
+                _ => {
+                    let bytes: Vec<_> = values.iter().map(HeaderValue::as_bytes).collect();
+                    HeaderValue::from_bytes(&bytes.join(&b", "[..])).ok()
                 }
+            };
+            for pat in pats {
+                let found = values
+                    .iter()
+                    .chain(&joined)
+                    .find_map(|v| pat.check_explained(v, explain.as_deref_mut()));
+                handle_check_result!(found, best_result);
             }
         }
 
@@ -136,16 +390,58 @@ impl WappTechCheck<&HeaderMap> for Vec<(String, Vec<Tagged<Regex>>)> {
     }
 }
 
+/// Each pattern counts once, for the first cookie of a matching name that it matches.
 #[cfg(feature = "cookie")]
-impl WappTechCheck<&[Cookie<'_>]> for Vec<(String, Vec<Tagged<Regex>>)> {
-    fn check(&self, input: &[Cookie]) -> Option<WappTechCheckResult> {
+impl WappTechCheck<&[Cookie<'_>]> for Vec<(WappCookieName, Vec<Tagged<Regex>>)> {
+    fn check_explained(
+        &self,
+        input: &[Cookie],
+        mut explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
         let mut best_result: Option<WappTechCheckResult> = None;
 
-        for cookie in input {
-            for (pat_key, pat) in self {
-                if pat_key == cookie.name() {
-                    handle_check_result!(pat.check(cookie.value()), best_result);
-                }
+        for (pat_key, pats) in self {
+            for pat in pats {
+                let found = input
+                    .iter()
+                    .filter(|c| pat_key.matches(c.name()))
+                    .find_map(|c| {
+                        if let Some(explain) = explain.as_deref_mut() {
+                            explain.key = Some(c.name().into());
+                        }
+                        pat.check_explained(c.value(), explain.as_deref_mut())
+                    });
+                handle_check_result!(found, best_result);
+            }
+        }
+
+        best_result
+    }
+}
+
+/// Keyed inputs such as meta tags as `(name, content)` or probe responses as `(path, body)`. Each pattern counts once,
+/// for the first input of its key that it matches.
+impl WappTechCheck<&[(String, String)]> for Vec<(String, Vec<Tagged<Regex>>)> {
+    fn check_explained(
+        &self,
+        input: &[(String, String)],
+        mut explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
+        let mut best_result: Option<WappTechCheckResult> = None;
+
+        for (pat_key, pats) in self {
+            if let Some(explain) = explain.as_deref_mut() {
+                explain.key = Some(pat_key.clone());
+            }
+            for pat in pats {
+                let found =
+                    input
+                        .iter()
+                        .filter(|(name, _)| name == pat_key)
+                        .find_map(|(_, content)| {
+                            pat.check_explained(content.as_str(), explain.as_deref_mut())
+                        });
+                handle_check_result!(found, best_result);
             }
         }
 
@@ -153,18 +449,81 @@ impl WappTechCheck<&[Cookie<'_>]> for Vec<(String, Vec<Tagged<Regex>>)> {
     }
 }
 
+/// Each pattern counts once, however many elements match the selector. Text patterns are matched against the text
+/// content of each element, like `textContent`.
 #[cfg(feature = "scraper")]
 impl WappTechCheck<&Html> for WappTechDomPatttern {
-    fn check(&self, input: &Html) -> Option<WappTechCheckResult> {
+    fn check_explained(
+        &self,
+        input: &Html,
+        mut explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
         let mut best_result: Option<WappTechCheckResult> = None;
 
-        for el in input.select(&self.selector) {
-            handle_check_result!(self.exists.check(()), best_result);
+        let elements: Vec<_> = input.select(&self.selector).collect();
+        if elements.is_empty() {
+            return None;
+        }
 
-            for (attr_pat_key, attr_pat) in &self.attributes {
-                if let Some(attr_value) = el.attr(attr_pat_key) {
-                    handle_check_result!(attr_pat.check(attr_value), best_result);
-                }
+        if let Some(explain) = explain.as_deref_mut() {
+            explain.key = Some(self.selector().into());
+        }
+        if let Some(exists) = &self.exists {
+            if let Some(explain) = explain.as_deref_mut() {
+                explain.push("", &elements[0].html());
+            }
+            handle_check_result!(exists.check(()), best_result);
+        }
+
+        if let Some(text_pat) = &self.text {
+            let found = elements.iter().find_map(|el| {
+                let text: String = el.text().collect();
+                text_pat.check_explained(text.as_str(), explain.as_deref_mut())
+            });
+            handle_check_result!(found, best_result);
+        }
+
+        for (attr_pat_key, attr_pats) in &self.attributes {
+            if let Some(explain) = explain.as_deref_mut() {
+                explain.key = Some(format!("{}[{attr_pat_key}]", self.selector()));
+            }
+            for attr_pat in attr_pats {
+                let found = elements
+                    .iter()
+                    .filter_map(|el| el.attr(attr_pat_key))
+                    .find_map(|v| attr_pat.check_explained(v, explain.as_deref_mut()));
+                handle_check_result!(found, best_result);
+            }
+        }
+
+        best_result
+    }
+}
+
+/// Only the elements of the pattern's selector are considered, and each property pattern counts once.
+#[cfg(feature = "scraper")]
+impl WappTechCheck<&[WappElementProperties]> for WappTechDomPatttern {
+    fn check_explained(
+        &self,
+        input: &[WappElementProperties],
+        mut explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
+        let mut best_result: Option<WappTechCheckResult> = None;
+
+        for (prop_pat_key, prop_pats) in &self.properties {
+            if let Some(explain) = explain.as_deref_mut() {
+                explain.key = Some(format!("{}.{prop_pat_key}", self.selector()));
+            }
+            for prop_pat in prop_pats {
+                let found = input
+                    .iter()
+                    .filter(|el| el.selector == self.selector())
+                    .flat_map(|el| &el.properties)
+                    .filter(|(name, _)| name == prop_pat_key)
+                    .find_map(|(_, value)| {
+                        prop_pat.check_explained(value.as_str(), explain.as_deref_mut())
+                    });
+                handle_check_result!(found, best_result);
             }
         }
 
@@ -172,11 +531,96 @@ impl WappTechCheck<&Html> for WappTechDomPatttern {
     }
 }
 
+/// A `window` snapshot. Each pattern counts once. Strings, numbers and booleans are matched as text, while other
+/// values only match patterns that match an empty string, such as presence patterns (`""`).
+impl WappTechCheck<&serde_json::Value> for Vec<(String, Vec<Tagged<Regex>>)> {
+    fn check_explained(
+        &self,
+        input: &serde_json::Value,
+        mut explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
+        let mut best_result: Option<WappTechCheckResult> = None;
+
+        for (pat_key, pats) in self {
+            let Some(value) = js_property(input, pat_key) else {
+                continue;
+            };
+            if let Some(explain) = explain.as_deref_mut() {
+                explain.key = Some(pat_key.clone());
+            }
+            let value = match value {
+                serde_json::Value::String(s) => Cow::Borrowed(s.as_str()),
+                serde_json::Value::Number(n) => Cow::Owned(n.to_string()),
+                serde_json::Value::Bool(b) => Cow::Owned(b.to_string()),
+                _ => Cow::Borrowed(""),
+            };
+            for pat in pats {
+                handle_check_result!(
+                    pat.check_explained(&*value, explain.as_deref_mut()),
+                    best_result
+                );
+            }
+        }
+
+        best_result
+    }
+}
+
+/// Each pattern counts once, for the first record of its type that it matches.
+impl WappTechCheck<&WappDnsRecords> for Vec<(WappDnsRecordType, Vec<Tagged<Regex>>)> {
+    fn check_explained(
+        &self,
+        input: &WappDnsRecords,
+        mut explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
+        let mut best_result: Option<WappTechCheckResult> = None;
+
+        for (record_type, pats) in self {
+            if let Some(explain) = explain.as_deref_mut() {
+                explain.key = Some(record_type.as_str().into());
+            }
+            for pat in pats {
+                let found = input.get(*record_type).iter().find_map(|record| {
+                    pat.check_explained(record.as_str(), explain.as_deref_mut())
+                });
+                handle_check_result!(found, best_result);
+            }
+        }
+
+        best_result
+    }
+}
+
+/// Walks a dotted property path such as `a.b.0` or `a.b[0].c`. Array elements are addressed by index.
+fn js_property<'a>(window: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split(['.', '[', ']'])
+        .filter(|segment| !segment.is_empty())
+        .try_fold(window, |value, segment| match value {
+            serde_json::Value::Object(o) => o.get(segment),
+            serde_json::Value::Array(a) => a.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
 impl WappTech {
     pub fn check_url(&self, url: &str) -> Option<WappTechCheckResult> {
         self.url.check(url)
     }
 
+    /// Checks the issuer of the site's TLS certificate, see [`WappPage::cert_issuer`].
+    pub fn check_cert_issuer(&self, issuer: &str) -> Option<WappTechCheckResult> {
+        self.cert_issuer_patterns.check(issuer)
+    }
+
+    pub fn check_dns(&self, records: &WappDnsRecords) -> Option<WappTechCheckResult> {
+        self.dns.check(records)
+    }
+
+    /// Checks a JSON snapshot of `window`, see [`WappPage::js`].
+    pub fn check_js(&self, window: &serde_json::Value) -> Option<WappTechCheckResult> {
+        self.js.check(window)
+    }
+
     #[cfg(feature = "http")]
     pub fn check_headers(&self, headers: &HeaderMap) -> Option<WappTechCheckResult> {
         self.headers.check(headers)
@@ -187,67 +631,879 @@ impl WappTech {
         self.cookies.check(cookies)
     }
 
+    /// The cookies that match any cookie pattern of the technology, e.g. to show which of several cookies of the same
+    /// name (set for different paths or domains) gave the technology away. Every instance is checked, in order.
+    #[cfg(feature = "cookie")]
+    pub fn matched_cookies<'c>(&self, cookies: &'c [Cookie<'c>]) -> Vec<&'c Cookie<'c>> {
+        cookies
+            .iter()
+            .filter(|c| {
+                self.cookies.iter().any(|(pat_key, pats)| {
+                    pat_key.matches(c.name()) && pats.iter().any(|p| p.check(c.value()).is_some())
+                })
+            })
+            .collect()
+    }
+
+    /// Checks `(name, content)` pairs of meta tags, with names lowercased.
     #[cfg(feature = "scraper")]
-    pub fn check_dom(&self, dom: &Html) -> Option<WappTechCheckResult> {
-        let mut best_result: Option<WappTechCheckResult> = None;
+    pub fn check_meta(&self, meta: &[(String, String)]) -> Option<WappTechCheckResult> {
+        self.meta.check(meta)
+    }
 
-        handle_check_result!(self.dom.check(dom), best_result);
+    /// Checks absolute script URLs. Each pattern counts once, for the first URL it matches, and gives version
+    /// candidates for every URL it matches.
+    #[cfg(feature = "scraper")]
+    pub fn check_script_src(&self, srcs: &[String]) -> Option<WappTechCheckResult> {
+        check_each_pattern(&self.script_src, srcs, None, None)
+    }
 
-        if self.script_src.is_empty() && !self.scripts.is_empty() {
-            return best_result;
-        }
-        static SCRIPT_SELECTOR: LazyLock<Selector> =
-            LazyLock::new(|| Selector::parse("script").unwrap());
-        for el in dom.select(&SCRIPT_SELECTOR) {
-            if let Some(src) = el.attr("src") {
-                for pat in &self.script_src {
-                    handle_check_result!(pat.check(src), best_result);
-                }
-            }
+    /// Checks JavaScript sources. Each pattern counts once, for the first source it matches, and gives version
+    /// candidates for every source it matches.
+    #[cfg(feature = "scraper")]
+    pub fn check_scripts(&self, scripts: &[String]) -> Option<WappTechCheckResult> {
+        check_each_pattern(&self.scripts, scripts, None, None)
+    }
 
-            for text in el.text() {
-                for pat in &self.text {
-                    handle_check_result!(pat.check(text), best_result);
-                }
-            }
-        }
+    #[cfg(feature = "scraper")]
+    pub fn check_dom(&self, dom: &Html) -> Option<WappTechCheckResult> {
+        self.dom.check(dom)
+    }
 
-        best_result
+    /// Checks the `properties` of `dom` patterns against property snapshots, see [`WappPage::dom_properties`].
+    #[cfg(feature = "scraper")]
+    pub fn check_dom_properties(
+        &self,
+        elements: &[WappElementProperties],
+    ) -> Option<WappTechCheckResult> {
+        self.dom.check(elements)
     }
 
     pub fn check_html(&self, html: &str) -> Option<WappTechCheckResult> {
         self.html.check(html)
     }
 
+    /// Checks `(path, body)` pairs of the probes that exist on the site. See [`WappPage::probes`].
+    pub fn check_probes(&self, probes: &[(String, String)]) -> Option<WappTechCheckResult> {
+        self.probe.check(probes)
+    }
+
+    /// Checks stylesheet text, e.g. the contents of the page's `<style>` elements and linked stylesheets.
+    pub fn check_css(&self, css: &str) -> Option<WappTechCheckResult> {
+        self.css.check(css)
+    }
+
     pub fn check_text(&self, text: &str) -> Option<WappTechCheckResult> {
         self.text.check(text)
     }
 
     pub fn check<P: WappPage>(&self, page: &P) -> Option<WappTechCheckResult> {
+        self.check_with_options(page, &WappCheckOptions::default())
+    }
+
+    pub fn check_with_options<P: WappPage>(
+        &self,
+        page: &P,
+        options: &WappCheckOptions,
+    ) -> Option<WappTechCheckResult> {
+        self.check_timed(page, options, None, None)
+    }
+
+    /// Checks the page like [`check_timed`](Self::check_timed), also returning the detectors that had both patterns for
+    /// the technology and input from the page.
+    pub(crate) fn check_attempted<P: WappPage>(
+        &self,
+        page: &P,
+        options: &WappCheckOptions,
+        mut stats: Option<&mut WappScanStats>,
+        explain: Option<&mut Explain>,
+    ) -> (Option<WappTechCheckResult>, Vec<&'static str>) {
+        let mut own_stats = WappScanStats::default();
+        let result = self.check_timed(page, options, Some(&mut own_stats), explain);
+
+        let counts = self.pattern_counts();
+        let has_patterns = |detector: &str| {
+            let detector = if detector == "dom_properties" {
+                "dom"
+            } else {
+                detector
+            };
+            counts.iter().any(|&(d, n)| d == detector && n > 0)
+        };
+        let mut detectors = Vec::new();
+        for (detector, duration) in own_stats.detector_timings {
+            if let Some(stats) = stats.as_deref_mut() {
+                stats.add_timing(detector, duration);
+            }
+            // URL patterns are timed even when the page has no URL.
+            if has_patterns(detector) && (detector != "url" || page.url().is_some()) {
+                detectors.push(detector);
+            }
+        }
+        (result, detectors)
+    }
+
+    /// Same as [`check_with_options`](Self::check_with_options), also adding the time spent per detector to `stats`
+    /// and recording the patterns that match in `explain` when given.
+    pub(crate) fn check_timed<P: WappPage>(
+        &self,
+        page: &P,
+        options: &WappCheckOptions,
+        mut stats: Option<&mut WappScanStats>,
+        mut explain: Option<&mut Explain>,
+    ) -> Option<WappTechCheckResult> {
         let mut best_result: Option<WappTechCheckResult> = None;
+        let on = |kind| options.detectors.contains(kind);
 
-        if let Some(url) = page.url() {
-            handle_check_result!(self.check_url(url), best_result);
+        let canonical_url = page.canonical_url().filter(|_| options.check_canonical_url);
+        let urls = page.url().into_iter().chain(canonical_url);
+        if on(WappDetectorKinds::URL) {
+            handle_check_result!(
+                timed!(
+                    stats,
+                    explain,
+                    "url",
+                    urls.filter_map(|url| self.url.check_explained(url, explain.as_deref_mut()))
+                        .max_by_key(|r| r.confidence)
+                ),
+                best_result
+            );
+        }
+        if let Some(issuer) = page
+            .cert_issuer()
+            .filter(|_| !self.cert_issuer_patterns.is_empty() && on(WappDetectorKinds::CERT_ISSUER))
+        {
+            handle_check_result!(
+                timed!(
+                    stats,
+                    explain,
+                    "cert_issuer",
+                    self.cert_issuer_patterns
+                        .check_explained(issuer, explain.as_deref_mut())
+                ),
+                best_result
+            );
+        }
+        if let Some(records) = page
+            .dns()
+            .filter(|_| !self.dns.is_empty() && on(WappDetectorKinds::DNS))
+        {
+            handle_check_result!(
+                timed!(
+                    stats,
+                    explain,
+                    "dns",
+                    self.dns.check_explained(records, explain.as_deref_mut())
+                ),
+                best_result
+            );
+        }
+        if let Some(window) = page
+            .js()
+            .filter(|_| !self.js.is_empty() && on(WappDetectorKinds::JS))
+        {
+            handle_check_result!(
+                timed!(
+                    stats,
+                    explain,
+                    "js",
+                    self.js.check_explained(window, explain.as_deref_mut())
+                ),
+                best_result
+            );
         }
         #[cfg(feature = "http")]
-        if let Some(headers) = page.headers() {
-            handle_check_result!(self.check_headers(headers), best_result);
+        if let Some(headers) = page.headers().filter(|_| on(WappDetectorKinds::HEADERS)) {
+            handle_check_result!(
+                timed!(
+                    stats,
+                    explain,
+                    "headers",
+                    self.headers
+                        .check_explained(headers, explain.as_deref_mut())
+                ),
+                best_result
+            );
         }
         #[cfg(feature = "cookie")]
-        if let Some(cookies) = page.cookies() {
-            handle_check_result!(self.check_cookies(cookies), best_result);
+        if let Some(cookies) = page.cookies().filter(|_| on(WappDetectorKinds::COOKIES)) {
+            handle_check_result!(
+                timed!(
+                    stats,
+                    explain,
+                    "cookies",
+                    self.cookies
+                        .check_explained(cookies, explain.as_deref_mut())
+                ),
+                best_result
+            );
+        }
+        #[cfg(feature = "scraper")]
+        if !self.meta.is_empty() && on(WappDetectorKinds::META) {
+            let meta = match page.meta() {
+                Some(meta) => Some(Cow::Borrowed(meta)),
+                None => page.dom().map(|dom| Cow::Owned(meta_tags(dom))),
+            };
+            if let Some(meta) = meta {
+                handle_check_result!(
+                    timed!(
+                        stats,
+                        explain,
+                        "meta",
+                        self.meta.check_explained(&*meta, explain.as_deref_mut())
+                    ),
+                    best_result
+                );
+            }
+        }
+        #[cfg(feature = "scraper")]
+        if !self.script_src.is_empty() && on(WappDetectorKinds::SCRIPT_SRC) {
+            let srcs = match page.script_src() {
+                Some(srcs) => Some(Cow::Borrowed(srcs)),
+                None => page
+                    .dom()
+                    .map(|dom| Cow::Owned(script_srcs(dom, page.url()))),
+            };
+            if let Some(srcs) = srcs {
+                handle_check_result!(
+                    timed!(
+                        stats,
+                        explain,
+                        "script_src",
+                        check_each_pattern(
+                            &self.script_src,
+                            &srcs,
+                            explain.as_deref_mut(),
+                            page.url().and_then(url::site)
+                        )
+                    ),
+                    best_result
+                );
+            }
+        }
+        #[cfg(feature = "scraper")]
+        if !self.scripts.is_empty() && on(WappDetectorKinds::SCRIPTS) {
+            let scripts = match page.scripts() {
+                Some(scripts) => Some(Cow::Borrowed(scripts)),
+                None => page.dom().map(|dom| Cow::Owned(inline_scripts(dom))),
+            };
+            if let Some(scripts) = scripts {
+                handle_check_result!(
+                    timed!(
+                        stats,
+                        explain,
+                        "scripts",
+                        check_each_pattern(&self.scripts, &scripts, explain.as_deref_mut(), None)
+                    ),
+                    best_result
+                );
+            }
+        }
+        if let Some(probes) = page
+            .probes()
+            .filter(|_| !self.probe.is_empty() && on(WappDetectorKinds::PROBE))
+        {
+            handle_check_result!(
+                timed!(
+                    stats,
+                    explain,
+                    "probe",
+                    self.probe.check_explained(probes, explain.as_deref_mut())
+                ),
+                best_result
+            );
+        }
+        if !self.css.is_empty() && on(WappDetectorKinds::CSS) {
+            let css = match page.css() {
+                Some(css) => Some(Cow::Borrowed(css)),
+                #[cfg(feature = "scraper")]
+                None => page.dom().map(|dom| Cow::Owned(style_blocks(dom))),
+                #[cfg(not(feature = "scraper"))]
+                None => None,
+            };
+            if let Some(css) = css {
+                handle_check_result!(
+                    timed!(
+                        stats,
+                        explain,
+                        "css",
+                        self.css.check_explained(&*css, explain.as_deref_mut())
+                    ),
+                    best_result
+                );
+            }
+        }
+        #[cfg(feature = "scraper")]
+        if let Some(dom) = page.dom().filter(|_| on(WappDetectorKinds::DOM)) {
+            handle_check_result!(
+                timed!(
+                    stats,
+                    explain,
+                    "dom",
+                    self.dom.check_explained(dom, explain.as_deref_mut())
+                ),
+                best_result
+            );
         }
         #[cfg(feature = "scraper")]
-        if let Some(dom) = page.dom() {
-            handle_check_result!(self.check_dom(dom), best_result);
+        if let Some(elements) = page.dom_properties().filter(|_| on(WappDetectorKinds::DOM)) {
+            handle_check_result!(
+                timed!(
+                    stats,
+                    explain,
+                    "dom_properties",
+                    self.dom.check_explained(elements, explain.as_deref_mut())
+                ),
+                best_result
+            );
         }
-        if let Some(html) = page.html() {
-            handle_check_result!(self.check_html(html), best_result);
+        if let Some(html) = page.html().filter(|_| on(WappDetectorKinds::HTML)) {
+            handle_check_result!(
+                timed!(
+                    stats,
+                    explain,
+                    "html",
+                    self.html.check_explained(html, explain.as_deref_mut())
+                ),
+                best_result
+            );
         }
-        if let Some(text) = page.text() {
-            handle_check_result!(self.check_text(text), best_result);
+        if let Some(text) = page.text().filter(|_| on(WappDetectorKinds::TEXT)) {
+            handle_check_result!(
+                timed!(
+                    stats,
+                    explain,
+                    "text",
+                    self.text.check_explained(text, explain)
+                ),
+                best_result
+            );
         }
 
+        if !options.detector_caps.is_empty() {
+            if let Some(best) = &mut best_result {
+                best.cap_sources(&options.detector_caps);
+            }
+        }
         best_result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::{Tagged, WappTechCheck, WappTechVersionPattern, WappTechVersionValue};
+    use crate::test_dataset;
+
+    fn presence(version: WappTechVersionPattern) -> Option<String> {
+        let pat = Tagged {
+            inner: (),
+            confidence: 100,
+            version: Some(version),
+            rewritten: None,
+        };
+        pat.check(()).unwrap().version
+    }
+
+    fn conditional(
+        true_expr: Option<&str>,
+        false_expr: Option<WappTechVersionValue>,
+    ) -> WappTechVersionPattern {
+        WappTechVersionPattern::Conditional {
+            cond_var: 1,
+            true_expr: true_expr.map(|s| WappTechVersionValue::Const(s.into())),
+            false_expr,
+        }
+    }
+
+    #[test]
+    fn test_check_presence_version() {
+        use WappTechVersionValue::{Const, Var};
+
+        assert_eq!(
+            presence(WappTechVersionPattern::Always(Const("v1".into()))),
+            Some("v1".into())
+        );
+        assert_eq!(presence(WappTechVersionPattern::Always(Var(1))), None);
+        assert_eq!(
+            presence(conditional(Some("a"), Some(Const("b".into())))),
+            Some("b".into())
+        );
+        assert_eq!(presence(conditional(Some("a"), None)), None);
+        assert_eq!(presence(conditional(Some("a"), Some(Var(2)))), None);
+    }
+
+    #[test]
+    fn test_version_candidates() {
+        use crate::{test_dataset, WappRawPage};
+
+        let techs = br#"{
+            "Lib": {"cats": [], "website": "", "html": ["lib-([\\d.]+)\\;version:\\1", "<lib v([\\d.]+)\\;version:\\1\\;confidence:50"]}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let page = WappRawPage::new(None, None, Some(b"lib-1.0 <lib v2.0> lib-1.0"));
+        let result = analyzer.techs["Lib"].check(&page).unwrap();
+        assert_eq!(result.version.as_deref(), Some("1.0"));
+        assert_eq!(result.version_candidates, ["1.0", "2.0"]);
+
+        #[cfg(feature = "scraper")]
+        {
+            let techs = br#"{"jQuery": {"cats": [], "website": "", "scriptSrc": "jquery-([\\d.]+)\\.js\\;version:\\1"}}"#;
+            let analyzer = test_dataset::with_techs(techs);
+            let html = br#"<script src="/jquery-1.12.4.js"></script><script src="/jquery-3.7.1.js"></script>"#;
+            let page = WappRawPage::new(Some("https://a.test/"), None, Some(html));
+            let results = analyzer.check(&page);
+            assert_eq!(results[0].version.as_deref(), Some("1.12.4"));
+            assert_eq!(results[0].version_candidates, ["1.12.4", "3.7.1"]);
+        }
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_check_script_src_base() {
+        use crate::{test_dataset, WappRawPage};
+
+        let techs = br#"{"Lib": {"cats": [], "website": "", "scriptSrc": "^https://cdn\\.test/js/lib\\.js$"}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let check = |url: Option<&str>, html: &str| {
+            let page = WappRawPage::new(url, None, Some(html.as_bytes()));
+            analyzer.techs["Lib"].check(&page).is_some()
+        };
+
+        let script = r#"<script src="lib.js"></script>"#;
+        assert!(check(Some("https://cdn.test/js/index.html"), script));
+        assert!(check(
+            Some("https://example.com/"),
+            &format!(r#"<base href="//cdn.test/js/">{script}"#)
+        ));
+        assert!(!check(Some("https://example.com/"), script));
+        assert!(!check(None, script));
+
+        let srcs = ["https://cdn.test/js/lib.js".to_string()];
+        assert!(analyzer.techs["Lib"].check_script_src(&srcs).is_some());
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_check_dom_text() {
+        use crate::{test_dataset, WappRawPage};
+
+        let techs = br#"{"Gen": {"cats": [], "website": "", "dom": {"footer .credit": {"text": "Built with Gen v([\\d.]+)\\;version:\\1"}}}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let tech = &analyzer.techs["Gen"];
+
+        let html = br#"<footer><span class="credit">Hosted</span><span class="credit">Built with <b>Gen</b> v1.4</span></footer>"#;
+        let result = tech
+            .check(&WappRawPage::new(None, None, Some(html)))
+            .unwrap();
+        assert_eq!(result.version.as_deref(), Some("1.4"));
+
+        let html = br#"<footer><span class="credit">Hosted by Foo</span></footer>"#;
+        assert!(tech
+            .check(&WappRawPage::new(None, None, Some(html)))
+            .is_none());
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_check_dom_exists() {
+        use crate::{test_dataset, WappRawPage};
+
+        let techs = br#"{
+            "Bare": {"cats": [], "website": "", "dom": "footer .credit\\;confidence:50"},
+            "Exists": {"cats": [], "website": "", "dom": {"footer .credit\\;confidence:40": {"exists": ""}}},
+            "Text": {"cats": [], "website": "", "dom": {"footer .credit": {"exists": "\\;confidence:20", "text": "Gen"}}}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let html = br#"<footer><span class="credit">Hosted by Foo</span></footer>"#;
+        let page = WappRawPage::new(None, None, Some(html));
+        let confidence = |name: &str| analyzer.techs[name].check(&page).map(|r| r.confidence);
+
+        assert_eq!(confidence("Bare"), Some(50));
+        assert_eq!(confidence("Exists"), Some(40));
+        assert_eq!(confidence("Text"), Some(20));
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_check_dom_src() {
+        use crate::{test_dataset, WappRawPage};
+
+        let techs = br#"{"Embed": {"cats": [], "website": "", "dom": {"iframe": {"src": "embed\\.test/v([\\d]+)/\\;version:\\1"}}}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let check = |html: &str| {
+            analyzer.techs["Embed"].check(&WappRawPage::new(None, None, Some(html.as_bytes())))
+        };
+
+        let result = check(r#"<iframe src="https://embed.test/v2/x"></iframe>"#).unwrap();
+        assert_eq!(result.version.as_deref(), Some("2"));
+        assert!(check(r#"<iframe src="https://other.test/v2/x"></iframe>"#).is_none());
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_check_dom_properties() {
+        use crate::{test_dataset, WappElementProperties, WappPage};
+
+        struct Snapshot(Vec<WappElementProperties>);
+
+        impl WappPage for Snapshot {
+            fn dom_properties(&self) -> Option<&[WappElementProperties]> {
+                Some(&self.0)
+            }
+        }
+
+        let techs = br#"{"Widget": {"cats": [], "website": "", "dom": {"div.widget\\;confidence:50": {"properties": {"_widgetVersion": "([\\d.]+)\\;version:\\1"}}}}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let tech = &analyzer.techs["Widget"];
+
+        let selectors = analyzer.dom_property_selectors();
+        assert_eq!(
+            selectors["div.widget"].iter().collect::<Vec<_>>(),
+            [&"_widgetVersion"]
+        );
+
+        let snapshot = |selector: &str| {
+            Snapshot(vec![WappElementProperties {
+                selector: selector.into(),
+                properties: vec![("_widgetVersion".into(), "3.0".into())],
+            }])
+        };
+        let result = tech.check(&snapshot("div.widget")).unwrap();
+        assert_eq!(result.version.as_deref(), Some("3.0"));
+        assert!(tech.check(&snapshot("div.other")).is_none());
+
+        // The selector alone does not detect rules with only properties.
+        let html = br#"<div class="widget"></div>"#;
+        assert!(tech
+            .check(&crate::WappRawPage::new(None, None, Some(html)))
+            .is_none());
+        let snapshot = Snapshot(vec![WappElementProperties {
+            selector: "div.widget".into(),
+            properties: vec![("_widgetVersion".into(), "unknown".into())],
+        }]);
+        assert!(tech.check(&snapshot).is_none());
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_check_scripts() {
+        use crate::{WappAnalyzer, WappRawPage};
+
+        let analyzer = WappAnalyzer::new_test();
+        let tech = &analyzer.techs["Test Scripts"];
+
+        let html = br#"<script src="x.js"></script><script>testScript = "1.2"</script>"#;
+        assert!(tech.check_scripts(&[]).is_none());
+        let result = tech
+            .check(&WappRawPage::new(None, None, Some(html)))
+            .unwrap();
+        assert_eq!(result.version.as_deref(), Some("1.2"));
+
+        let external = [r#"testScript = "2.0""#.to_string()];
+        assert_eq!(
+            tech.check_scripts(&external).unwrap().version.as_deref(),
+            Some("2.0")
+        );
+    }
+
+    #[test]
+    fn test_check_combined_confidence() {
+        use crate::{test_dataset, WappRawPage};
+
+        let techs = br#"{"A": {
+            "cats": [], "website": "",
+            "html": ["a\\;confidence:25", "b\\;confidence:25", "x\\;confidence:25"],
+            "url": "a\\;confidence:25"
+        }}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let check = |url: &str, html: &str| {
+            let page = WappRawPage::new(Some(url), None, Some(html.as_bytes()));
+            analyzer.techs["A"].check(&page).map(|r| r.confidence)
+        };
+
+        assert_eq!(check("/", "a"), Some(25));
+        assert_eq!(check("/", "a a a"), Some(25));
+        assert_eq!(check("/", "ab"), Some(50));
+        assert_eq!(check("/a", "ab"), Some(75));
+        assert_eq!(check("/a", "abx"), Some(100));
+        assert_eq!(check("/", ""), None);
+    }
+
+    #[test]
+    fn test_check_keeps_later_version() {
+        use crate::{test_dataset, WappRawPage};
+
+        let techs =
+            br#"{"A": {"cats": [], "website": "", "html": ["a", "a/([\\d.]+)\\;version:\\1"]}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+
+        let page = WappRawPage::new(None, None, Some(b"a/1.2"));
+        let result = analyzer.techs["A"].check(&page).unwrap();
+        assert_eq!(result.confidence, 100);
+        assert_eq!(result.version.as_deref(), Some("1.2"));
+    }
+
+    #[cfg(all(feature = "http", feature = "cookie"))]
+    #[test]
+    fn test_check_presence_patterns() {
+        use http::{HeaderMap, HeaderValue};
+
+        use crate::{test_dataset, WappRawPage};
+
+        let techs = br#"{"Edge": {"cats": [], "website": "", "headers": {"X-Edge": ""}, "cookies": {"edge_id": ""}}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let tech = &analyzer.techs["Edge"];
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-edge", HeaderValue::from_bytes(b"caf\xe9").unwrap());
+        assert!(tech.check_headers(&headers).is_some());
+        headers.insert("x-edge", HeaderValue::from_static(""));
+        assert!(tech.check_headers(&headers).is_some());
+        assert!(tech.check_headers(&HeaderMap::new()).is_none());
+
+        let cookies = [("Set-Cookie".to_string(), "edge_id=".to_string())];
+        assert!(tech
+            .check(&WappRawPage::new(None, Some(&cookies), None))
+            .is_some());
+    }
+
+    #[test]
+    fn test_check_dns() {
+        use crate::{test_dataset, WappDnsRecords, WappPage};
+
+        struct Host(WappDnsRecords);
+
+        impl WappPage for Host {
+            fn dns(&self) -> Option<&WappDnsRecords> {
+                Some(&self.0)
+            }
+        }
+
+        let techs = br#"{
+            "Mail": {"cats": [], "website": "", "dns": {"MX": "\\.mail\\.test\\.$", "TXT": "mail-verification="}},
+            "Bogus": {"cats": [], "website": "", "dns": {"AAAA": ""}}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        assert!(analyzer.techs["Bogus"].dns.is_empty());
+
+        let host = Host(WappDnsRecords {
+            txt: vec!["v=spf1 -all".into()],
+            mx: vec!["10 in1.mail.test.".into()],
+            ..Default::default()
+        });
+        let result = analyzer.techs["Mail"].check(&host).unwrap();
+        assert_eq!(result.confidence, 100);
+        assert!(analyzer.techs["Mail"]
+            .check(&Host(WappDnsRecords::default()))
+            .is_none());
+    }
+
+    #[test]
+    fn test_check_js() {
+        use serde_json::json;
+
+        use crate::{test_dataset, WappPage};
+
+        struct Window(serde_json::Value);
+
+        impl WappPage for Window {
+            fn js(&self) -> Option<&serde_json::Value> {
+                Some(&self.0)
+            }
+        }
+
+        let techs = br#"{
+            "jQuery": {"cats": [], "website": "", "js": {"jQuery.fn.jquery": "([\\d.]+)\\;version:\\1"}},
+            "Flags": {"cats": [], "website": "", "js": {"app.flags[1].on": "^true$"}},
+            "Global": {"cats": [], "website": "", "js": {"__APP__": ""}}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        assert_eq!(
+            analyzer.js_properties().into_iter().collect::<Vec<_>>(),
+            ["__APP__", "app.flags[1].on", "jQuery.fn.jquery"]
+        );
+
+        let window = Window(json!({
+            "jQuery": {"fn": {"jquery": "3.7.1"}},
+            "app": {"flags": [{"on": false}, {"on": true}]},
+            "__APP__": {"nested": 1}
+        }));
+        let results = analyzer.check(&window);
+        let detected = |name: &str| results.iter().find(|r| r.tech_name == name);
+        assert_eq!(
+            detected("jQuery").unwrap().version.as_deref(),
+            Some("3.7.1")
+        );
+        assert!(detected("Flags").is_some());
+        assert!(detected("Global").is_some());
+
+        assert!(analyzer
+            .check(&Window(json!({"jQuery": {"fn": {}}})))
+            .is_empty());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_check_repeated_headers() {
+        use http::{HeaderMap, HeaderValue};
+
+        let techs = br#"{
+            "Express": {"cats": [], "website": "", "headers": {"X-Powered-By": "^Express$"}},
+            "Stack": {"cats": [], "website": "", "headers": {"X-Powered-By": "^PHP/[\\d.]+, Express$"}}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+
+        let mut headers = HeaderMap::new();
+        headers.append("x-powered-by", HeaderValue::from_static("PHP/8.1"));
+        headers.append("x-powered-by", HeaderValue::from_static("Express"));
+        assert!(analyzer.techs["Express"].check_headers(&headers).is_some());
+        assert!(analyzer.techs["Stack"].check_headers(&headers).is_some());
+    }
+
+    #[cfg(feature = "cookie")]
+    #[test]
+    fn test_check_cookie_names() {
+        use cookie::Cookie;
+
+        let techs = br#"{"Sessions": {"cats": [], "website": "", "cookies": {"_sess_\\d+": "", "PHPSESSID": "", "a.b": "", "a+b": "", "a[b": ""}}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let check = |name: &str| {
+            analyzer.techs["Sessions"]
+                .check_cookies(&[Cookie::new(name.to_string(), "1")])
+                .is_some()
+        };
+
+        assert!(check("_sess_42"));
+        assert!(!check("_sess_42x"));
+        assert!(!check("x_sess_42"));
+        assert!(check("phpsessid"));
+        assert!(check("A.B"));
+        assert!(!check("axb"));
+        assert!(check("a+b"));
+        assert!(!check("aab"));
+        assert!(!check("a[b"));
+        let dropped = &analyzer.techs["Sessions"].dropped_patterns;
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(
+            (dropped[0].field.as_str(), dropped[0].pattern.as_str()),
+            ("cookies", "a[b")
+        );
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_check_css() {
+        use crate::{WappAnalyzer, WappRawPage};
+
+        let analyzer = WappAnalyzer::new_test();
+        let tech = &analyzer.techs["Test CSS"];
+        assert!(tech.check_css(".test-css { display: grid; }").is_none());
+
+        let body = br#"<html><head><style>.test-css-3 { float: left; }</style></head></html>"#;
+        let page = WappRawPage::new(Some("https://example.com/"), None, Some(body));
+        assert_eq!(tech.check(&page).unwrap().version.as_deref(), Some("3"));
+
+        let body = br#"<html><head><link rel="stylesheet" href="/grid.css"></head></html>"#;
+        let mut page = WappRawPage::new(Some("https://example.com/a/"), None, Some(body));
+        assert!(tech.check(&page).is_none());
+        assert_eq!(page.stylesheet_urls(), ["https://example.com/grid.css"]);
+        page.fetch_stylesheets(|_| Some(".test-css-4 {}".into()));
+        assert_eq!(tech.check(&page).unwrap().version.as_deref(), Some("4"));
+    }
+
+    #[test]
+    fn test_check_cert_issuer() {
+        let techs = br#"{
+            "Cdn": {"cats": [], "website": "", "certIssuer": "^CdnCo"},
+            "Odd": {"cats": [], "website": "", "certIssuer": "Odd (Trust"}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        assert!(analyzer.techs["Cdn"]
+            .check_cert_issuer("CdnCo Inc")
+            .is_some());
+        assert!(analyzer.techs["Cdn"]
+            .check_cert_issuer("Other CdnCo")
+            .is_none());
+        assert!(analyzer.techs["Odd"]
+            .check_cert_issuer("odd (trust services)")
+            .is_some());
+        assert_eq!(
+            analyzer.techs["Odd"].cert_issuer.as_deref(),
+            Some("Odd (Trust")
+        );
+
+        // Too large to compile even when escaped, so dropped rather than matched.
+        let huge = "Huge (".repeat(200_000);
+        let techs = format!(r#"{{"Huge": {{"cats": [], "website": "", "certIssuer": "{huge}"}}}}"#);
+        let analyzer = test_dataset::with_techs(techs.as_bytes());
+        let tech = &analyzer.techs["Huge"];
+        assert!(tech.check_cert_issuer(&huge).is_none());
+        assert_eq!(tech.dropped_patterns.len(), 1);
+        assert_eq!(tech.dropped_patterns[0].field, "certIssuer");
+    }
+
+    #[cfg(feature = "cookie")]
+    #[test]
+    fn test_check_same_name_cookies() {
+        use cookie::Cookie;
+
+        let techs = br#"{"Shop": {"cats": [], "website": "", "cookies": {"cart": "^shop-(\\d+)\\;version:\\1"}}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let tech = &analyzer.techs["Shop"];
+
+        let cookies = [
+            Cookie::parse("cart=empty; Path=/").unwrap(),
+            Cookie::parse("cart=shop-2; Path=/checkout").unwrap(),
+        ];
+        assert_eq!(
+            tech.check_cookies(&cookies).unwrap().version.as_deref(),
+            Some("2")
+        );
+        let matched = tech.matched_cookies(&cookies);
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].path(), Some("/checkout"));
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_check_meta() {
+        use crate::{test_dataset, WappRawPage};
+
+        let techs = br#"{"WordPress": {"cats": [], "website": "", "meta": {"Generator": "^WordPress ?([\\d.]+)?\\;version:\\1"}}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let tech = &analyzer.techs["WordPress"];
+
+        let html = br#"<meta name="GENERATOR" content="WordPress 6.4"><meta property="og:type" content="x">"#;
+        let result = tech
+            .check(&WappRawPage::new(None, None, Some(html)))
+            .unwrap();
+        assert_eq!(result.version.as_deref(), Some("6.4"));
+
+        let meta = [("generator".to_string(), "Hugo".to_string())];
+        assert!(tech.check_meta(&meta).is_none());
+    }
+
+    #[test]
+    fn test_check_regex_version() {
+        let pat = Tagged {
+            inner: Regex::new(r"foo(?:/([\d.]+))?").unwrap(),
+            confidence: 100,
+            version: Some(WappTechVersionPattern::Conditional {
+                cond_var: 1,
+                true_expr: Some(WappTechVersionValue::Var(1)),
+                false_expr: Some(WappTechVersionValue::Const("legacy".into())),
+            }),
+            rewritten: None,
+        };
+
+        assert_eq!(pat.check("foo/1.2").unwrap().version, Some("1.2".into()));
+        assert_eq!(
+            pat.check("foo/1.2").unwrap().version_captures,
+            [Some("foo/1.2".into()), Some("1.2".into())]
+        );
+        assert_eq!(pat.check("foo").unwrap().version, Some("legacy".into()));
+        assert!(pat.check("bar").is_none());
+    }
+}
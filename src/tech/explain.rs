@@ -0,0 +1,211 @@
+use serde::Serialize;
+
+use super::WappTech;
+use crate::{WappCheckOptions, WappPage};
+
+/// A pattern that matched the page, see [`WappCheckOptions::explain`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WappEvidence {
+    /// The detector, as named in [`WappScanStats::detector_timings`](crate::WappScanStats::detector_timings), e.g.
+    /// `headers`.
+    pub detector: &'static str,
+    /// What the input was found under, for detectors with keyed inputs: the header or cookie name, meta name, `js`
+    /// property path, DNS record type or probe path. For `dom`, the selector, followed by `[attr]` for attribute
+    /// patterns and `.prop` for property patterns.
+    pub key: Option<String>,
+    /// The pattern as compiled, without its tags. Empty for `dom` selectors that only need to select an element.
+    pub pattern: String,
+    /// The part of the input that the pattern matched. Of longer matches, e.g. a whole `<body>` selected by `dom`, only
+    /// the first and last [`EVIDENCE_WINDOW`](Self::EVIDENCE_WINDOW) characters are kept.
+    pub matched: String,
+    /// For `script_src` evidence, whether the script is served from another site than the page, comparing the
+    /// registrable domains of their URLs (e.g. `example.com` for `cdn.example.com`), so that embedded trackers can be
+    /// told apart from the site's own stack. `None` for other detectors, and when either URL has no host.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub third_party: Option<bool>,
+}
+
+impl WappEvidence {
+    /// Number of characters kept at each end of [`matched`](Self::matched).
+    pub const EVIDENCE_WINDOW: usize = 80;
+
+    fn bounded(matched: &str) -> String {
+        let window = Self::EVIDENCE_WINDOW;
+        let len = matched.chars().count();
+        if len <= 2 * window {
+            return matched.into();
+        }
+        let head: String = matched.chars().take(window).collect();
+        let tail: String = matched.chars().skip(len - window).collect();
+        format!("{head}…{tail}")
+    }
+}
+
+/// Where the checks of a technology record the patterns that match, when explaining. The detector and key being
+/// checked are set by the callers as they go, so that patterns only need to record what they matched.
+pub(crate) struct Explain<'a> {
+    evidence: &'a mut Vec<WappEvidence>,
+    pub(crate) detector: &'static str,
+    pub(crate) key: Option<String>,
+    /// See [`WappEvidence::third_party`].
+    pub(crate) third_party: Option<bool>,
+}
+
+impl<'a> Explain<'a> {
+    pub(crate) fn new(evidence: &'a mut Vec<WappEvidence>) -> Self {
+        Self {
+            evidence,
+            detector: "",
+            key: None,
+            third_party: None,
+        }
+    }
+
+    /// Records a match of `pattern` under the current detector and key. A pattern is only recorded once per key, for
+    /// the first input it matches, and long matches are cut down to a window, see [`WappEvidence::matched`].
+    pub(crate) fn push(&mut self, pattern: &str, matched: &str) {
+        let recorded = self
+            .evidence
+            .iter()
+            .any(|e| e.detector == self.detector && e.key == self.key && e.pattern == pattern);
+        if !recorded {
+            self.evidence.push(WappEvidence {
+                detector: self.detector,
+                key: self.key.clone(),
+                pattern: pattern.into(),
+                matched: WappEvidence::bounded(matched),
+                third_party: self.third_party,
+            });
+        }
+    }
+}
+
+impl WappTech {
+    /// The patterns of the technology that match the page, in the order [`check`](Self::check) tries them, with what
+    /// they matched. Each pattern is listed once, for the first input it matches.
+    pub fn explain<P: WappPage>(&self, page: &P, options: &WappCheckOptions) -> Vec<WappEvidence> {
+        let mut evidence = Vec::new();
+        self.check_timed(page, options, None, Some(&mut Explain::new(&mut evidence)));
+        evidence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_dataset, WappCheckOptions, WappRawPage};
+
+    #[test]
+    fn test_explain() {
+        let techs = br#"{
+            "A": {"cats": [], "website": "", "url": "^https://a\\.test/", "html": ["<a-app", "<b-app"]}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let page = WappRawPage::new(
+            Some("https://a.test/x"),
+            None,
+            Some(b"<html><a-app x></html>"),
+        );
+
+        let evidence: Vec<_> = analyzer.techs["A"]
+            .explain(&page, &WappCheckOptions::default())
+            .into_iter()
+            .map(|e| (e.detector, e.pattern, e.matched))
+            .collect();
+        assert_eq!(
+            evidence,
+            [
+                ("url", r"^https://a\.test/".into(), "https://a.test/".into()),
+                ("html", "<a-app".into(), "<a-app".into()),
+            ]
+        );
+
+        let options = WappCheckOptions {
+            explain: true,
+            ..Default::default()
+        };
+        let results = analyzer.check_with_options(&page, &options);
+        assert_eq!(results[0].evidence.len(), 2);
+        assert!(analyzer.check(&page)[0].evidence.is_empty());
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_explain_bounded() {
+        use crate::{test_dataset, WappEvidence};
+
+        let techs = br#"{"A": {"cats": [], "website": "", "dom": "body"}}"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let html = format!("<body>{}</body>", "é".repeat(10_000));
+        let page = WappRawPage::new(None, None, Some(html.as_bytes()));
+
+        let evidence = analyzer.techs["A"].explain(&page, &WappCheckOptions::default());
+        let matched = &evidence[0].matched;
+        assert_eq!(
+            matched.chars().count(),
+            2 * WappEvidence::EVIDENCE_WINDOW + 1
+        );
+        assert!(matched.starts_with("<body>é"));
+        assert!(matched.ends_with("é</body>"));
+    }
+
+    #[cfg(feature = "scraper")]
+    #[test]
+    fn test_explain_third_party() {
+        let techs = br#"{
+            "A": {"cats": [], "website": "", "scriptSrc": ["/own\\.js", "/tracker\\.js"], "html": "<html"}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let html = br#"<html><script src="https://cdn.example.co.uk/own.js"></script>
+            <script src="https://tracker.test/tracker.js"></script></html>"#;
+
+        let third_party = |url| -> Vec<_> {
+            let page = WappRawPage::new(url, None, Some(html));
+            analyzer.techs["A"]
+                .explain(&page, &WappCheckOptions::default())
+                .into_iter()
+                .map(|e| (e.detector, e.third_party))
+                .collect()
+        };
+        assert_eq!(
+            third_party(Some("https://www.example.co.uk/")),
+            [
+                ("script_src", Some(false)),
+                ("script_src", Some(true)),
+                ("html", None),
+            ]
+        );
+        assert_eq!(
+            third_party(None),
+            [("script_src", None), ("script_src", None), ("html", None)]
+        );
+    }
+
+    #[cfg(all(feature = "http", feature = "scraper"))]
+    #[test]
+    fn test_explain_keyed() {
+        let techs = br#"{
+            "A": {
+                "cats": [], "website": "",
+                "headers": {"Server": "^srv/([\\d.]+)\\;version:\\1"},
+                "dom": {"div": {"attributes": {"data-ver": "\\d+"}}}
+            }
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let headers = [("Server".to_string(), "srv/1.2 (unix)".to_string())];
+        let html = br#"<div data-ver="v7"></div>"#;
+        let page = WappRawPage::new(None, Some(&headers), Some(html));
+
+        let evidence: Vec<_> = analyzer.techs["A"]
+            .explain(&page, &WappCheckOptions::default())
+            .into_iter()
+            .map(|e| (e.detector, e.key.unwrap(), e.matched))
+            .collect();
+        assert_eq!(
+            evidence,
+            [
+                ("headers", "Server".into(), "srv/1.2".into()),
+                ("dom", "div[data-ver]".into(), "7".into()),
+            ]
+        );
+    }
+}
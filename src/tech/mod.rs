@@ -1,25 +1,41 @@
 mod check;
+mod explain;
 mod parse;
 
+pub(crate) use explain::Explain;
+pub use explain::WappEvidence;
+
+use std::fmt::{self, Display};
+
 use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pattern::{WappDroppedPattern, WappPatternTranslation},
+    WappDnsRecordType, WappResultSource,
+};
+
+#[cfg(feature = "cookie")]
+use anyhow::{Context, Error};
+#[cfg(feature = "cookie")]
+use regex::RegexBuilder;
 
 #[cfg(feature = "scraper")]
 use scraper::Selector;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct WappTech {
     pub name: String,
     /// One or more categories.
     pub cats: Vec<i32>,
-    /// URL of the application's website.
+    /// URL of the application's website. Empty with the `slim-metadata` feature.
     pub website: String,
     /// A short description of the technology in British English (max. 250 characters). Write in a neutral, factual
-    /// tone; not like an ad.
+    /// tone; not like an ad. `None` with the `slim-metadata` feature.
     pub description: Option<String>,
-    /// Application icon filename.
-    #[allow(dead_code)]
-    pub icon: (),
+    /// Application icon filename, e.g. `WordPress.svg`. `default.svg` when the dataset has none, like upstream. See
+    /// [`WappIconStore`](crate::WappIconStore) for loading icons.
+    pub icon: String,
     /// [CPE](https://nvd.nist.gov/products/cpe) is a structured naming scheme for technologies. To check if a CPE is
     /// valid and exists (using v2.3), use the [search](https://nvd.nist.gov/products/cpe/search).
     pub cpe: Option<String>,
@@ -28,9 +44,12 @@ pub struct WappTech {
     /// The technology has an open-source license.
     pub oss: Option<bool>,
     /// Cost indicator (based on a typical plan or average monthly price) and available pricing models. For paid
-    /// products only.
+    /// products only. Empty with the `slim-metadata` feature.
     pub pricing: Vec<WappTechPricing>,
+    /// Issuer of the site's TLS certificate, e.g. the CDN or hosting provider that issued it. Matched like a pattern.
     pub cert_issuer: Option<String>,
+    /// [`cert_issuer`](Self::cert_issuer) compiled, or matched literally when it is not a valid pattern.
+    pub(crate) cert_issuer_patterns: Vec<Tagged<Regex>>,
     /// The presence of one application can imply the presence of another, e.g. WordPress means PHP is also in use.
     pub implies: Vec<Tagged<String>>,
     /// Similar to implies but detection only runs if the required technology has been identified. Useful for themes for
@@ -38,23 +57,23 @@ pub struct WappTech {
     pub requires: Vec<String>,
     /// Similar to requires; detection only runs if a technology in the required category has been identified.
     pub requires_category: Vec<i32>,
-    /// Opposite of implies. The presence of one application can exclude the presence of another.
-    pub excludes: Vec<String>,
+    /// Opposite of implies. The presence of one application can exclude the presence of another. Accepts a string or an
+    /// array, with optional tags like `implies`.
+    pub excludes: Vec<Tagged<String>>,
 
-    /// Cookies.
+    /// Cookies, matched by name case-insensitively, see [`WappCookieName`].
     #[cfg(feature = "cookie")]
-    pub cookies: Vec<(String, Vec<Tagged<Regex>>)>,
+    pub cookies: Vec<(WappCookieName, Vec<Tagged<Regex>>)>,
 
     /// Uses a [query selector](https://developer.mozilla.org/en-US/docs/Web/API/Document/querySelectorAll) to inspect
     /// element properties, attributes and text content.
     #[cfg(feature = "scraper")]
     pub dom: Vec<WappTechDomPatttern>,
 
-    #[allow(dead_code)]
-    pub dns: (),
+    /// DNS records of the host, keyed by record type.
+    pub dns: Vec<(WappDnsRecordType, Vec<Tagged<Regex>>)>,
     /// JavaScript properties (case sensitive). Avoid short property names to prevent matching minified code.
-    #[allow(dead_code)]
-    pub js: (),
+    pub js: Vec<(String, Vec<Tagged<Regex>>)>,
 
     /// HTTP response headers.
     #[cfg(feature = "http")]
@@ -67,11 +86,9 @@ pub struct WappTech {
     pub text: Vec<Tagged<Regex>>,
     /// CSS rules. Unavailable when a website enforces a same-origin policy. For performance reasons, only a portion of
     /// the available CSS rules are used to find matches.
-    #[allow(dead_code)]
-    pub css: (),
-    /// Request a URL to test for its existence or match text content (NPM driver only).
-    #[allow(dead_code)]
-    pub probe: (),
+    pub css: Vec<Tagged<Regex>>,
+    /// Request a URL to test for its existence or match text content (NPM driver only). Keyed by path.
+    pub probe: Vec<(String, Vec<Tagged<Regex>>)>,
     /// Robots.txt contents.
     #[allow(dead_code)]
     pub robots: (),
@@ -90,9 +107,195 @@ pub struct WappTech {
     /// possible and use `js` instead.
     #[cfg(feature = "scraper")]
     pub scripts: Vec<Tagged<Regex>>,
+
+    /// Fields of the dataset entry this crate does not know, sorted. Always empty unless
+    /// [`WappLoadOptions::allow_unknown_fields`](crate::WappLoadOptions::allow_unknown_fields) is set.
+    pub unknown_fields: Vec<String>,
+    /// Patterns of the dataset entry that do not compile and are skipped, e.g. ones with a lookahead. Patterns of
+    /// detectors disabled by features are not loaded, so they are not listed either.
+    pub dropped_patterns: Vec<WappDroppedPattern>,
+}
+
+impl WappTech {
+    /// URL-safe identifier of the technology, derived from its name the same way upstream Wappalyzer does.
+    pub fn slug(&self) -> String {
+        slugify(&self.name)
+    }
+
+    /// Whether the technology is a paid product, i.e. it has any pricing information. Always `false` with the
+    /// `slim-metadata` feature.
+    pub fn is_paid(&self) -> bool {
+        !self.pricing.is_empty()
+    }
+
+    /// Whether the technology has an open-source license.
+    pub fn is_oss(&self) -> bool {
+        self.oss.unwrap_or(false)
+    }
+
+    /// Whether the technology is offered as a Software-as-a-Service.
+    pub fn is_saas(&self) -> bool {
+        self.saas.unwrap_or(false)
+    }
+
+    /// Names of the excluded technologies, with the confidence of their `excludes` tag.
+    pub fn excludes(&self) -> impl Iterator<Item = (&str, i32)> {
+        self.excludes
+            .iter()
+            .map(|e| (e.inner.as_str(), e.confidence))
+    }
+
+    /// Number of loaded patterns per detector, in a fixed order. Detectors disabled by features are omitted.
+    pub fn pattern_counts(&self) -> Vec<(&'static str, usize)> {
+        #[allow(unused_mut)]
+        let mut counts = vec![
+            ("url", self.url.len()),
+            ("cert_issuer", self.cert_issuer_patterns.len()),
+            ("js", map_len(&self.js)),
+            ("dns", map_len(&self.dns)),
+            ("css", self.css.len()),
+            ("probe", map_len(&self.probe)),
+        ];
+        #[cfg(feature = "http")]
+        counts.push(("headers", map_len(&self.headers)));
+        #[cfg(feature = "cookie")]
+        counts.push(("cookies", map_len(&self.cookies)));
+        #[cfg(feature = "scraper")]
+        counts.extend([
+            ("dom", self.dom.len()),
+            ("meta", map_len(&self.meta)),
+            ("script_src", self.script_src.len()),
+            ("scripts", self.scripts.len()),
+        ]);
+        counts.extend([("html", self.html.len()), ("text", self.text.len())]);
+        counts
+    }
+
+    /// Every loaded pattern along with where it is, e.g. `html` or `headers.Server`, by detector in the order of
+    /// [`pattern_counts`](Self::pattern_counts). Cookie names are given as written in the dataset, and `src` patterns
+    /// of `dom` selectors as `attributes.src`.
+    pub fn patterns(&self) -> Vec<(String, &Tagged<Regex>)> {
+        let mut fields: Vec<(String, &[Tagged<Regex>])> = vec![
+            ("url".into(), &self.url),
+            ("certIssuer".into(), &self.cert_issuer_patterns),
+        ];
+        for (key, pats) in &self.js {
+            fields.push((format!("js.{key}"), pats));
+        }
+        for (record_type, pats) in &self.dns {
+            fields.push((format!("dns.{}", record_type.as_str()), pats));
+        }
+        fields.push(("css".into(), &self.css));
+        for (path, pats) in &self.probe {
+            fields.push((format!("probe.{path}"), pats));
+        }
+        #[cfg(feature = "http")]
+        for (name, pats) in &self.headers {
+            fields.push((format!("headers.{name}"), pats));
+        }
+        #[cfg(feature = "cookie")]
+        for (name, pats) in &self.cookies {
+            fields.push((format!("cookies.{}", name.name), pats));
+        }
+        #[cfg(feature = "scraper")]
+        for dom in &self.dom {
+            let field = format!("dom.{}", dom.selector_source);
+            fields.push((format!("{field}.text"), dom.text.as_slice()));
+            for (name, pats) in &dom.attributes {
+                fields.push((format!("{field}.attributes.{name}"), pats));
+            }
+            for (name, pats) in &dom.properties {
+                fields.push((format!("{field}.properties.{name}"), pats));
+            }
+        }
+        #[cfg(feature = "scraper")]
+        {
+            for (name, pats) in &self.meta {
+                fields.push((format!("meta.{name}"), pats));
+            }
+            fields.push(("scriptSrc".into(), &self.script_src));
+            fields.push(("scripts".into(), &self.scripts));
+        }
+        fields.push(("html".into(), &self.html));
+        fields.push(("text".into(), &self.text));
+        fields
+            .into_iter()
+            .flat_map(|(field, pats)| pats.iter().map(move |p| (field.clone(), p)))
+            .collect()
+    }
+}
+
+/// Lowercases `name`, replaces anything other than `a-z`, `0-9` and `-` with `-`, collapses repeated dashes and trims
+/// dashes from both ends, e.g. `"Apache HTTP Server"` => `"apache-http-server"`.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        let c = if c.is_ascii_lowercase() || c.is_ascii_digit() {
+            c
+        } else {
+            '-'
+        };
+        if c != '-' || !slug.ends_with('-') {
+            slug.push(c);
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+fn map_len<K, T>(map: &[(K, Vec<T>)]) -> usize {
+    map.iter().map(|(_, v)| v.len()).sum()
+}
+
+impl Display for WappTech {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.name)?;
+        let mut empty = true;
+        for (detector, count) in self.pattern_counts() {
+            if count > 0 {
+                write!(f, " {detector} {count}")?;
+                empty = false;
+            }
+        }
+        if empty {
+            write!(f, " no patterns")?;
+        }
+        Ok(())
+    }
+}
+
+/// The descriptive parts of a technology, for "about this technology" panels in UIs and APIs.
+#[derive(Debug, Clone, Serialize)]
+pub struct WappTechInfo {
+    pub name: String,
+    pub slug: String,
+    pub website: String,
+    pub description: Option<String>,
+    pub cats: Vec<i32>,
+    pub icon: String,
+    pub pricing: Vec<WappTechPricing>,
+    pub saas: Option<bool>,
+    pub oss: Option<bool>,
+    pub cpe: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+impl From<&WappTech> for WappTechInfo {
+    fn from(tech: &WappTech) -> Self {
+        Self {
+            name: tech.name.clone(),
+            slug: tech.slug(),
+            website: tech.website.clone(),
+            description: tech.description.clone(),
+            cats: tech.cats.clone(),
+            icon: tech.icon.clone(),
+            pricing: tech.pricing.clone(),
+            saas: tech.saas,
+            oss: tech.oss,
+            cpe: tech.cpe.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 /// Cost indicator (based on a typical plan or average monthly price) and available pricing models. For paid products
 /// only.
@@ -116,19 +319,37 @@ pub enum WappTechPricing {
 }
 
 #[cfg(feature = "scraper")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct WappTechDomPatttern {
     selector: Selector,
-    exists: Tagged<()>,
+    /// The selector as written in the dataset, without tags.
+    selector_source: String,
+    /// Detects the technology when the selector matches. Set for selectors given as strings, and for objects only with
+    /// an `exists` key, so that objects with `text`, `attributes` or `properties` only detect when those match.
+    exists: Option<Tagged<()>>,
     text: Option<Tagged<Regex>>,
     attributes: Vec<(String, Vec<Tagged<Regex>>)>,
-    #[allow(dead_code)]
-    properties: (),
+    /// JavaScript properties of the elements, only known from a property snapshot (see [`WappPage::dom_properties`]).
+    ///
+    /// [`WappPage::dom_properties`]: crate::WappPage::dom_properties
+    properties: Vec<(String, Vec<Tagged<Regex>>)>,
+}
+
+#[cfg(feature = "scraper")]
+impl WappTechDomPatttern {
+    pub fn selector(&self) -> &str {
+        &self.selector_source
+    }
+
+    /// Names of the JavaScript properties matched on the selected elements.
+    pub fn property_names(&self) -> impl Iterator<Item = &str> {
+        self.properties.iter().map(|(name, _)| name.as_str())
+    }
 }
 
 /// Tags (a non-standard syntax) can be appended to patterns (and implies and excludes, separated by \\;) to store
 /// additional information.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Tagged<T> {
     pub inner: T,
 
@@ -138,9 +359,34 @@ pub struct Tagged<T> {
 
     /// Gets the version number from a pattern match using a special syntax.
     pub version: Option<WappTechVersionPattern>,
+
+    /// The regular expression as written in the dataset and what was rewritten in it, for patterns that had to be
+    /// rewritten for the `regex` crate, see [`Tagged::translation`]. `None` for other patterns and for tagged values
+    /// that are not patterns.
+    pub rewritten: Option<(String, Vec<String>)>,
 }
 
-#[derive(Debug, PartialEq)]
+impl Tagged<Regex> {
+    /// The regular expression as written in the dataset, without tags.
+    pub fn source(&self) -> &str {
+        self.rewritten
+            .as_ref()
+            .map_or(self.inner.as_str(), |(source, _)| source)
+    }
+
+    /// How the pattern was brought into the `regex` crate when it was loaded.
+    pub fn translation(&self) -> WappPatternTranslation {
+        match &self.rewritten {
+            None => WappPatternTranslation::Verbatim,
+            Some((_, changes)) => WappPatternTranslation::Translated {
+                regex: self.inner.as_str().into(),
+                changes: changes.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum WappTechVersionPattern {
     Always(WappTechVersionValue),
     Conditional {
@@ -150,14 +396,85 @@ pub enum WappTechVersionPattern {
     },
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum WappTechVersionValue {
     Const(String),
     Var(usize),
 }
 
+impl WappTechVersionPattern {
+    /// The highest capture group index the template refers to, if any.
+    pub(crate) fn max_var(&self) -> Option<usize> {
+        let var = |v: &Option<WappTechVersionValue>| match v {
+            Some(WappTechVersionValue::Var(i)) => Some(*i),
+            _ => None,
+        };
+        match self {
+            Self::Always(WappTechVersionValue::Var(i)) => Some(*i),
+            Self::Always(WappTechVersionValue::Const(_)) => None,
+            Self::Conditional {
+                cond_var,
+                true_expr,
+                false_expr,
+            } => [Some(*cond_var), var(true_expr), var(false_expr)]
+                .into_iter()
+                .flatten()
+                .max(),
+        }
+    }
+}
+
+/// A cookie name of a technology, matched case-insensitively since browsers and servers do not agree on the case of
+/// cookie names. Names are compared literally, unless they have characters that cookie names cannot have (see
+/// [RFC 6265](https://www.rfc-editor.org/rfc/rfc6265#section-4.1.1)), e.g. `_sess_\d+`: those are patterns matching
+/// whole names.
+#[cfg(feature = "cookie")]
+#[derive(Debug, Clone)]
+pub struct WappCookieName {
+    /// The name as written in the dataset.
+    pub name: String,
+    pattern: Option<Regex>,
+}
+
+#[cfg(feature = "cookie")]
+impl WappCookieName {
+    /// Characters cookie names cannot have, besides whitespace and control characters.
+    const SEPARATORS: &'static str = "()<>@,;:\\\"/[]?={}";
+
+    pub(crate) fn new(name: String) -> Result<Self, Error> {
+        let is_pattern = name
+            .chars()
+            .any(|c| Self::SEPARATORS.contains(c) || c.is_whitespace() || c.is_control());
+        let pattern = is_pattern
+            .then(|| {
+                RegexBuilder::new(&format!("^(?:{name})$"))
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| format!("Failed parsing cookie name pattern {name}"))
+            })
+            .transpose()?;
+        Ok(Self { name, pattern })
+    }
+
+    /// Whether a cookie called `name` matches, ignoring ASCII case.
+    pub fn matches(&self, name: &str) -> bool {
+        match &self.pattern {
+            Some(pattern) => pattern.is_match(name),
+            None => self.name.eq_ignore_ascii_case(name),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct WappTechCheckResult {
     pub confidence: i32,
     pub version: Option<String>,
+    /// Capture groups of the matched pattern, indexed like `\N` in version templates. Only filled in when the pattern
+    /// carries a version template.
+    pub version_captures: Vec<Option<String>>,
+    /// Every distinct version the matching patterns gave, in the order they were found, `version` included.
+    pub version_candidates: Vec<String>,
+    /// What each detector contributed to `confidence`, see
+    /// [`WappCheckResult::sources`](crate::WappCheckResult::sources).
+    pub sources: Vec<WappResultSource>,
 }
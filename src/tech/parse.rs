@@ -1,25 +1,41 @@
-use std::{collections::HashMap, sync::OnceLock};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::OnceLock,
+};
 
 use anyhow::{anyhow, bail, Context, Error};
-use regex::Regex;
-use serde::Deserialize;
+use regex::{Regex, RegexBuilder};
+use serde::{
+    de::{self, MapAccess, Visitor},
+    Deserialize, Deserializer as _,
+};
+
+#[cfg(feature = "fs")]
+use std::io;
 
 use super::{Tagged, WappTech, WappTechPricing, WappTechVersionPattern, WappTechVersionValue};
+use crate::{pattern::WappDroppedPattern, WappLoadOptions, WappTechDefinition};
 
 #[cfg(feature = "scraper")]
 use scraper::Selector;
 
+#[cfg(feature = "cookie")]
+use super::WappCookieName;
+
 #[cfg(feature = "scraper")]
 use super::WappTechDomPatttern;
 
+#[cfg(feature = "scraper")]
+use crate::WappDomDefinition;
+
 #[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 struct WappTechRaw {
     pub cats: Vec<i32>,
     pub website: String,
     pub description: Option<String>,
-    #[allow(dead_code)]
-    pub icon: Option<serde_json::Value>,
+    pub icon: Option<String>,
     pub cpe: Option<String>,
     pub saas: Option<bool>,
     pub oss: Option<bool>,
@@ -29,33 +45,32 @@ struct WappTechRaw {
     pub requires: Option<serde_json::Value>,
     pub requires_category: Option<serde_json::Value>,
     pub excludes: Option<serde_json::Value>,
-    #[allow(dead_code)]
+    #[cfg_attr(not(feature = "cookie"), allow(dead_code))]
     pub cookies: Option<serde_json::Value>,
-    #[allow(dead_code)]
+    #[cfg_attr(not(feature = "scraper"), allow(dead_code))]
     pub dom: Option<serde_json::Value>,
-    #[allow(dead_code)]
     pub dns: Option<serde_json::Value>,
-    #[allow(dead_code)]
     pub js: Option<serde_json::Value>,
-    #[allow(dead_code)]
+    #[cfg_attr(not(feature = "http"), allow(dead_code))]
     pub headers: Option<serde_json::Value>,
     pub html: Option<serde_json::Value>,
     pub text: Option<serde_json::Value>,
-    #[allow(dead_code)]
     pub css: Option<serde_json::Value>,
-    #[allow(dead_code)]
     pub probe: Option<serde_json::Value>,
     #[allow(dead_code)]
     pub robots: Option<serde_json::Value>,
     #[allow(dead_code)]
     pub xhr: Option<serde_json::Value>,
     pub url: Option<serde_json::Value>,
-    #[allow(dead_code)]
+    #[cfg_attr(not(feature = "scraper"), allow(dead_code))]
     pub meta: Option<serde_json::Value>,
-    #[allow(dead_code)]
+    #[cfg_attr(not(feature = "scraper"), allow(dead_code))]
     pub script_src: Option<serde_json::Value>,
-    #[allow(dead_code)]
+    #[cfg_attr(not(feature = "scraper"), allow(dead_code))]
     pub scripts: Option<serde_json::Value>,
+    /// Fields this crate does not know, e.g. added upstream since.
+    #[serde(flatten)]
+    pub unknown: BTreeMap<String, serde_json::Value>,
 }
 
 /// Transform a `Option<serde_json::Value>` to a `Vec<T>` with `f`.
@@ -67,9 +82,9 @@ struct WappTechRaw {
 /// `"str"` => `vec![f("str")]`
 ///
 /// `[v1, v2, ...]` => `vec![f(v1), f(v2), ...]`
-fn to_vec<T, F>(value: Option<serde_json::Value>, f: F) -> Vec<T>
+fn to_vec<T, F>(value: Option<serde_json::Value>, mut f: F) -> Vec<T>
 where
-    F: Fn(serde_json::Value) -> Result<T, Error>,
+    F: FnMut(serde_json::Value) -> Result<T, Error>,
 {
     match value {
         None => Vec::new(),
@@ -105,84 +120,439 @@ fn to_tagged_string_vec(value: Option<serde_json::Value>) -> Vec<Tagged<String>>
     })
 }
 
-fn to_pattern_vec(value: Option<serde_json::Value>) -> Vec<Tagged<Regex>> {
+/// Compiles a pattern case-insensitively, like upstream evaluates its regular expressions with the JavaScript `i` flag,
+/// unless [`WappLoadOptions::case_sensitive`] is set. JavaScript syntax the `regex` crate reads differently is
+/// translated first (see [`translate_js_regex`]), and what was rewritten is returned along with the regular expression.
+fn compile_regex(
+    pattern: &str,
+    options: &WappLoadOptions,
+) -> Result<(Regex, Option<Vec<String>>), Error> {
+    let translated = translate_js_regex(pattern);
+    let source = translated.as_ref().map_or(pattern, |(source, _)| source);
+    let regex = RegexBuilder::new(source)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .with_context(|| format!("Failed parsing regular expresion {pattern}"))?;
+    Ok((regex, translated.map(|(_, changes)| changes)))
+}
+
+/// Rewrites the JavaScript regular expression syntax that the `regex` crate rejects although upstream accepts it, and
+/// describes each rewrite. `None` when the pattern is used verbatim.
+///
+/// Only literal braces are rewritten: JavaScript reads a `{` that does not start a `{n}`, `{n,}` or `{n,m}` quantifier,
+/// and an unmatched `}`, as literal characters, e.g. in `function\(\){`. Lookarounds and backreferences have no
+/// equivalent, so patterns using them fail to compile and are dropped.
+pub(crate) fn translate_js_regex(pattern: &str) -> Option<(String, Vec<String>)> {
+    static QUANTIFIER: OnceLock<Regex> = OnceLock::new();
+    let quantifier = QUANTIFIER.get_or_init(|| Regex::new(r"^\{\d+(?:,\d*)?\}").unwrap());
+
+    let mut out = String::with_capacity(pattern.len());
+    let mut changes = Vec::new();
+    let mut chars = pattern.char_indices();
+    let mut in_class = false;
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                out.push(c);
+                if let Some((_, next)) = chars.next() {
+                    out.push(next);
+                }
+                continue;
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '{' if !in_class => {
+                if let Some(m) = quantifier.find(&pattern[i..]) {
+                    out.push_str(m.as_str());
+                    // The quantifier is ASCII, so skipping its remaining bytes skips as many chars.
+                    chars.nth(m.len() - 2);
+                    continue;
+                }
+                out.push('\\');
+                changes.push(format!("escaped literal `{{` at offset {i}"));
+            }
+            '}' if !in_class => {
+                out.push('\\');
+                changes.push(format!("escaped literal `}}` at offset {i}"));
+            }
+            _ => {}
+        }
+        out.push(c);
+    }
+
+    (!changes.is_empty()).then_some((out, changes))
+}
+
+impl Tagged<Regex> {
+    /// Parses a single tagged pattern, e.g. `^nginx(?:/([\d.]+))?\;version:\1`. A version template that refers to a
+    /// capture group the pattern does not have is always a mistake in the dataset: it is dropped and the pattern kept,
+    /// so the technology is still detected, without a version. [`lint_technologies`](crate::lint_technologies) reports
+    /// such templates.
+    pub(crate) fn parse_pattern(input: &str, options: &WappLoadOptions) -> Result<Self, Error> {
+        let mut pattern = Self::parse_pattern_unchecked(input, options)?;
+        if pattern.version_var_out_of_range().is_some() {
+            pattern.version = None;
+        }
+        Ok(pattern)
+    }
+
+    /// Same as [`parse_pattern`](Self::parse_pattern) without validating the version template.
+    pub(crate) fn parse_pattern_unchecked(
+        input: &str,
+        options: &WappLoadOptions,
+    ) -> Result<Self, Error> {
+        let mut rewritten = None;
+        let mut pattern = Tagged::parse(input, |s| {
+            let (regex, changes) = compile_regex(s, options)?;
+            rewritten = changes.map(|changes| (s.to_string(), changes));
+            Ok(regex)
+        })?;
+        pattern.rewritten = rewritten;
+        Ok(pattern)
+    }
+
+    /// The highest capture group index of the version template, if the pattern has no such group.
+    pub(crate) fn version_var_out_of_range(&self) -> Option<usize> {
+        let var = self.version.as_ref()?.max_var()?;
+        (var >= self.inner.captures_len()).then_some(var)
+    }
+}
+
+/// Compiles a pattern of `field`, recording it in `dropped` when it does not compile.
+fn parse_field_pattern(
+    source: &str,
+    field: &str,
+    options: &WappLoadOptions,
+    dropped: &mut Vec<WappDroppedPattern>,
+) -> Result<Tagged<Regex>, Error> {
+    Tagged::parse_pattern(source, options).inspect_err(|e| {
+        dropped.push(WappDroppedPattern {
+            field: field.into(),
+            pattern: source.into(),
+            reason: format!("{e:#}"),
+        })
+    })
+}
+
+/// Compiles the patterns of `field`, recording the ones that do not compile in `dropped`.
+fn to_pattern_vec(
+    value: Option<serde_json::Value>,
+    field: &str,
+    options: &WappLoadOptions,
+    dropped: &mut Vec<WappDroppedPattern>,
+) -> Vec<Tagged<Regex>> {
     to_vec(value, |s| match s {
-        serde_json::Value::String(s) => Tagged::parse(&s, |t| {
-            Regex::new(t).with_context(|| format!("Failed parsing regular expresion {t}"))
-        }),
+        serde_json::Value::String(s) => parse_field_pattern(&s, field, options, dropped),
         x => Err(anyhow!("Expect a string, found {x}")),
     })
 }
 
+/// [`to_pattern_vec`] for patterns given as strings.
+fn parse_patterns(
+    sources: &[String],
+    field: &str,
+    options: &WappLoadOptions,
+    dropped: &mut Vec<WappDroppedPattern>,
+) -> Vec<Tagged<Regex>> {
+    sources
+        .iter()
+        .filter_map(|s| parse_field_pattern(s, field, options, dropped).ok())
+        .collect()
+}
+
+/// [`to_pattern_map`] for patterns given as strings.
+fn parse_pattern_map(
+    map: &BTreeMap<String, Vec<String>>,
+    field: &str,
+    options: &WappLoadOptions,
+    dropped: &mut Vec<WappDroppedPattern>,
+) -> Vec<(String, Vec<Tagged<Regex>>)> {
+    map.iter()
+        .map(|(k, v)| {
+            (
+                k.clone(),
+                parse_patterns(v, &format!("{field}.{k}"), options, dropped),
+            )
+        })
+        .collect()
+}
+
+/// [`to_tagged_string_vec`] for values given as strings.
+fn parse_tagged_strings(values: &[String]) -> Vec<Tagged<String>> {
+    values
+        .iter()
+        .filter_map(|s| Tagged::parse(s, |t| Ok(t.to_string())).ok())
+        .collect()
+}
+
+/// [`to_pattern_vec`] for each key of a map of patterns, e.g. header names. Keys are recorded in `dropped` as part of
+/// the field, e.g. `headers.Server`.
 #[allow(dead_code, clippy::type_complexity)]
 fn to_pattern_map(
     value: Option<serde_json::Value>,
+    field: &str,
+    options: &WappLoadOptions,
+    dropped: &mut Vec<WappDroppedPattern>,
 ) -> Result<Vec<(String, Vec<Tagged<Regex>>)>, Error> {
     match value {
         None => Ok(Vec::new()),
         Some(serde_json::Value::Object(o)) => Ok(o
             .into_iter()
-            .map(|(k, v)| -> (String, Vec<Tagged<Regex>>) { (k, to_pattern_vec(Some(v))) })
+            .map(|(k, v)| {
+                let pats = to_pattern_vec(Some(v), &format!("{field}.{k}"), options, dropped);
+                (k, pats)
+            })
             .collect()),
         Some(x) => Err(anyhow!("Expect a object, found {x}")),
     }
 }
 
-impl WappTech {
-    pub(crate) fn load_from_bytes(bytes: &[u8]) -> Result<HashMap<String, Self>, Error> {
-        let data = serde_json::from_slice::<HashMap<String, WappTechRaw>>(bytes)
-            .context("Failed to parse JSON from bytes")?;
-
-        let mut result = HashMap::<String, Self>::with_capacity(data.len());
-
-        for (name, item) in data {
-            result.insert(
-                name.clone(),
-                Self {
-                    name,
-                    cats: item.cats,
-                    website: item.website,
-                    description: item.description,
-                    icon: (),
-                    cpe: item.cpe,
-                    saas: item.saas,
-                    oss: item.oss,
-                    pricing: item.pricing.unwrap_or_default(),
-                    cert_issuer: item.cert_issuer,
-                    implies: to_tagged_string_vec(item.implies),
-                    requires: to_string_vec(item.requires),
-                    requires_category: to_i32_vec(item.requires_category),
-                    excludes: to_string_vec(item.excludes),
-                    #[cfg(feature = "cookie")]
-                    cookies: to_pattern_map(item.cookies)?,
-                    #[cfg(feature = "scraper")]
-                    dom: item
-                        .dom
-                        .map(WappTechDomPatttern::from_json)
-                        .unwrap_or_default(),
-                    dns: (),
-                    js: (),
-                    #[cfg(feature = "http")]
-                    headers: to_pattern_map(item.headers)?,
-                    html: to_pattern_vec(item.html),
-                    text: to_pattern_vec(item.text),
-                    css: (),
-                    probe: (),
-                    robots: (),
-                    url: to_pattern_vec(item.url),
-                    xhr: (),
-                    #[cfg(feature = "scraper")]
-                    meta: to_pattern_map(item.meta)?,
-                    #[cfg(feature = "scraper")]
-                    script_src: to_pattern_vec(item.script_src),
-                    #[cfg(feature = "scraper")]
-                    scripts: to_pattern_vec(item.scripts),
-                },
-            );
+/// Keeps descriptive metadata of a technology, or empties it with the `slim-metadata` feature.
+#[cfg(not(feature = "slim-metadata"))]
+fn metadata<T>(value: T) -> T {
+    value
+}
+
+/// Keeps descriptive metadata of a technology, or empties it with the `slim-metadata` feature.
+#[cfg(feature = "slim-metadata")]
+fn metadata<T: Default>(_value: T) -> T {
+    T::default()
+}
+
+/// Compiles the `certIssuer` of a technology as a pattern, or matches it literally when it is not a valid pattern. An
+/// issuer that does not compile either way is recorded in `dropped` and skipped.
+fn compile_cert_issuer(
+    issuer: Option<&str>,
+    options: &WappLoadOptions,
+    dropped: &mut Vec<WappDroppedPattern>,
+) -> Vec<Tagged<Regex>> {
+    let Some(issuer) = issuer else {
+        return Vec::new();
+    };
+    let compiled = Tagged::parse_pattern(issuer, options).or_else(|_| {
+        compile_regex(&regex::escape(issuer), options).map(|(inner, _)| Tagged {
+            inner,
+            confidence: 100,
+            version: None,
+            rewritten: None,
+        })
+    });
+    match compiled {
+        Ok(pattern) => vec![pattern],
+        Err(e) => {
+            dropped.push(WappDroppedPattern {
+                field: "certIssuer".into(),
+                pattern: issuer.into(),
+                reason: format!("{e:#}"),
+            });
+            Vec::new()
         }
+    }
+}
+
+/// Reads the cookie names of a technology, recording the ones that are invalid patterns in `dropped` and skipping them
+/// along with their patterns.
+#[cfg(feature = "cookie")]
+fn compile_cookie_names(
+    cookies: Vec<(String, Vec<Tagged<Regex>>)>,
+    dropped: &mut Vec<WappDroppedPattern>,
+) -> Vec<(WappCookieName, Vec<Tagged<Regex>>)> {
+    cookies
+        .into_iter()
+        .filter_map(|(name, pats)| match WappCookieName::new(name.clone()) {
+            Ok(name) => Some((name, pats)),
+            Err(e) => {
+                dropped.push(WappDroppedPattern {
+                    field: "cookies".into(),
+                    pattern: name,
+                    reason: format!("{e:#}"),
+                });
+                None
+            }
+        })
+        .collect()
+}
+
+/// Converts each entry of a technologies file as soon as it is read, so that only one raw entry is held in memory at
+/// a time rather than the whole file as [`serde_json::Value`]s.
+struct WappTechMapVisitor<'a>(&'a WappLoadOptions);
+
+impl<'de> Visitor<'de> for WappTechMapVisitor<'_> {
+    type Value = HashMap<String, WappTech>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of technologies")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut result = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+
+        while let Some((name, item)) = map.next_entry::<String, WappTechRaw>()? {
+            let tech = WappTech::from_raw(name.clone(), item, self.0)
+                .map_err(|e| de::Error::custom(format!("Technology {name}: {e:#}")))?;
+            result.insert(name, tech);
+        }
+
+        Ok(result)
+    }
+}
+
+impl WappTech {
+    pub(crate) fn load_from_bytes(
+        bytes: &[u8],
+        options: &WappLoadOptions,
+    ) -> Result<HashMap<String, Self>, Error> {
+        Self::load(&mut serde_json::Deserializer::from_slice(bytes), options)
+            .context("Failed to parse JSON from bytes")
+    }
+
+    #[cfg(feature = "fs")]
+    pub(crate) fn load_from_reader<R: io::Read>(
+        reader: R,
+        options: &WappLoadOptions,
+    ) -> Result<HashMap<String, Self>, Error> {
+        Self::load(&mut serde_json::Deserializer::from_reader(reader), options)
+            .context("Failed to parse JSON from reader")
+    }
 
+    fn load<'de, R: serde_json::de::Read<'de>>(
+        de: &mut serde_json::Deserializer<R>,
+        options: &WappLoadOptions,
+    ) -> Result<HashMap<String, Self>, serde_json::Error> {
+        let result = de.deserialize_map(WappTechMapVisitor(options))?;
+        de.end()?;
         Ok(result)
     }
+
+    fn from_raw(name: String, item: WappTechRaw, options: &WappLoadOptions) -> Result<Self, Error> {
+        let unknown_fields: Vec<String> = item.unknown.into_keys().collect();
+        if !unknown_fields.is_empty() && !options.allow_unknown_fields {
+            bail!("Unknown fields: {}", unknown_fields.join(", "));
+        }
+        let mut dropped = Vec::new();
+
+        Ok(Self {
+            name,
+            cats: item.cats,
+            website: metadata(item.website),
+            description: metadata(item.description),
+            icon: item.icon.unwrap_or_else(|| "default.svg".into()),
+            cpe: item.cpe,
+            saas: item.saas,
+            oss: item.oss,
+            pricing: metadata(item.pricing.unwrap_or_default()),
+            cert_issuer_patterns: compile_cert_issuer(
+                item.cert_issuer.as_deref(),
+                options,
+                &mut dropped,
+            ),
+            cert_issuer: item.cert_issuer,
+            implies: to_tagged_string_vec(item.implies),
+            requires: to_string_vec(item.requires),
+            requires_category: to_i32_vec(item.requires_category),
+            excludes: to_tagged_string_vec(item.excludes),
+            #[cfg(feature = "cookie")]
+            cookies: compile_cookie_names(
+                to_pattern_map(item.cookies, "cookies", options, &mut dropped)?,
+                &mut dropped,
+            ),
+            #[cfg(feature = "scraper")]
+            dom: item
+                .dom
+                .map(|dom| WappTechDomPatttern::from_json(dom, options, &mut dropped))
+                .unwrap_or_default(),
+            dns: to_pattern_map(item.dns, "dns", options, &mut dropped)?
+                .into_iter()
+                .filter_map(|(record_type, pats)| Some((record_type.parse().ok()?, pats)))
+                .collect(),
+            js: to_pattern_map(item.js, "js", options, &mut dropped)?,
+            #[cfg(feature = "http")]
+            headers: to_pattern_map(item.headers, "headers", options, &mut dropped)?,
+            html: to_pattern_vec(item.html, "html", options, &mut dropped),
+            text: to_pattern_vec(item.text, "text", options, &mut dropped),
+            css: to_pattern_vec(item.css, "css", options, &mut dropped),
+            probe: to_pattern_map(item.probe, "probe", options, &mut dropped)?,
+            robots: (),
+            url: to_pattern_vec(item.url, "url", options, &mut dropped),
+            xhr: (),
+            // Meta names are case-insensitive in HTML.
+            #[cfg(feature = "scraper")]
+            meta: to_pattern_map(item.meta, "meta", options, &mut dropped)?
+                .into_iter()
+                .map(|(k, v)| (k.to_ascii_lowercase(), v))
+                .collect(),
+            #[cfg(feature = "scraper")]
+            script_src: to_pattern_vec(item.script_src, "scriptSrc", options, &mut dropped),
+            #[cfg(feature = "scraper")]
+            scripts: to_pattern_vec(item.scripts, "scripts", options, &mut dropped),
+            unknown_fields,
+            dropped_patterns: dropped,
+        })
+    }
+
+    /// Converts a technology defined in code. Patterns are compiled like those of a technologies file, and the ones
+    /// that do not compile are skipped and listed in [`WappTech::dropped_patterns`].
+    pub(crate) fn from_definition(def: WappTechDefinition, options: &WappLoadOptions) -> Self {
+        let mut dropped = Vec::new();
+        Self {
+            cats: def.cats,
+            website: metadata(def.website),
+            description: metadata(def.description),
+            icon: def.icon.unwrap_or_else(|| "default.svg".into()),
+            cpe: def.cpe,
+            saas: def.saas,
+            oss: def.oss,
+            pricing: metadata(def.pricing),
+            cert_issuer_patterns: compile_cert_issuer(
+                def.cert_issuer.as_deref(),
+                options,
+                &mut dropped,
+            ),
+            cert_issuer: def.cert_issuer.clone(),
+            implies: parse_tagged_strings(&def.implies),
+            requires: def.requires,
+            requires_category: def.requires_category,
+            excludes: parse_tagged_strings(&def.excludes),
+            #[cfg(feature = "cookie")]
+            cookies: compile_cookie_names(
+                parse_pattern_map(&def.cookies, "cookies", options, &mut dropped),
+                &mut dropped,
+            ),
+            #[cfg(feature = "scraper")]
+            dom: def
+                .dom
+                .iter()
+                .filter_map(|dom| WappTechDomPatttern::from_definition(dom, options, &mut dropped))
+                .collect(),
+            dns: parse_pattern_map(&def.dns, "dns", options, &mut dropped)
+                .into_iter()
+                .filter_map(|(record_type, pats)| Some((record_type.parse().ok()?, pats)))
+                .collect(),
+            js: parse_pattern_map(&def.js, "js", options, &mut dropped),
+            #[cfg(feature = "http")]
+            headers: parse_pattern_map(&def.headers, "headers", options, &mut dropped),
+            html: parse_patterns(&def.html, "html", options, &mut dropped),
+            text: parse_patterns(&def.text, "text", options, &mut dropped),
+            css: parse_patterns(&def.css, "css", options, &mut dropped),
+            probe: parse_pattern_map(&def.probe, "probe", options, &mut dropped),
+            robots: (),
+            url: parse_patterns(&def.url, "url", options, &mut dropped),
+            xhr: (),
+            // Meta names are case-insensitive in HTML.
+            #[cfg(feature = "scraper")]
+            meta: parse_pattern_map(&def.meta, "meta", options, &mut dropped)
+                .into_iter()
+                .map(|(k, v)| (k.to_ascii_lowercase(), v))
+                .collect(),
+            #[cfg(feature = "scraper")]
+            script_src: parse_patterns(&def.script_src, "scriptSrc", options, &mut dropped),
+            #[cfg(feature = "scraper")]
+            scripts: parse_patterns(&def.scripts, "scripts", options, &mut dropped),
+            unknown_fields: Vec::new(),
+            dropped_patterns: dropped,
+            name: def.name,
+        }
+    }
 }
 
 #[cfg(feature = "scraper")]
@@ -192,6 +562,7 @@ impl Tagged<()> {
             inner: (),
             confidence,
             version,
+            rewritten: None,
         }
     }
 }
@@ -208,10 +579,14 @@ impl<T> Tagged<T> {
         let mut version = None;
 
         for p in parts {
-            let (k, v) = p.split_once(':').unwrap();
+            let (k, v) = p
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Expect a tag in the form of key:value, found {p}"))?;
             match k {
                 "confidence" => {
-                    confidence = v.parse().unwrap();
+                    confidence = v
+                        .parse()
+                        .with_context(|| format!("Failed to parse confidence {v}"))?;
                 }
                 "version" => {
                     version = Some(WappTechVersionPattern::parse(v)?);
@@ -224,6 +599,7 @@ impl<T> Tagged<T> {
             inner: inner_parser(inner_input).context("Failed to parse content without tag")?,
             confidence,
             version,
+            rewritten: None,
         })
     }
 }
@@ -285,6 +661,29 @@ impl WappTechVersionValue {
 
 #[cfg(feature = "scraper")]
 impl WappTechDomPatttern {
+    /// Converts a `dom` pattern defined in code, `None` when its selector does not parse. Like an object of the
+    /// dataset with only an `exists` key, a definition with neither text, attributes nor properties detects when the
+    /// selector matches.
+    fn from_definition(
+        def: &WappDomDefinition,
+        options: &WappLoadOptions,
+        dropped: &mut Vec<WappDroppedPattern>,
+    ) -> Option<Self> {
+        let mut pat = Self::from_selector(&def.selector).ok()?;
+        let field = format!("dom.{}", pat.selector_source);
+        if def.text.is_some() || !def.attributes.is_empty() || !def.properties.is_empty() {
+            pat.exists = None;
+        }
+        pat.text = def.text.as_ref().and_then(|text| {
+            parse_field_pattern(text, &format!("{field}.text"), options, dropped).ok()
+        });
+        let attributes = format!("{field}.attributes");
+        pat.attributes = parse_pattern_map(&def.attributes, &attributes, options, dropped);
+        let properties = format!("{field}.properties");
+        pat.properties = parse_pattern_map(&def.properties, &properties, options, dropped);
+        Some(pat)
+    }
+
     fn from_selector(input: &str) -> Result<Self, Error> {
         let tagged_selector = Tagged::<Selector>::parse(input, |s| {
             Selector::parse(s)
@@ -293,14 +692,22 @@ impl WappTechDomPatttern {
 
         Ok(Self {
             selector: tagged_selector.inner,
-            exists: Tagged::new(tagged_selector.confidence, tagged_selector.version),
+            selector_source: input.split("\\;").next().unwrap_or_default().to_string(),
+            exists: Some(Tagged::new(
+                tagged_selector.confidence,
+                tagged_selector.version,
+            )),
             text: None,
             attributes: Vec::new(),
-            properties: (),
+            properties: Vec::new(),
         })
     }
 
-    fn from_json(input: serde_json::Value) -> Vec<Self> {
+    fn from_json(
+        input: serde_json::Value,
+        options: &WappLoadOptions,
+        dropped: &mut Vec<WappDroppedPattern>,
+    ) -> Vec<Self> {
         match input {
             serde_json::Value::String(s) => match Self::from_selector(&s) {
                 Ok(x) => vec![x],
@@ -327,6 +734,9 @@ impl WappTechDomPatttern {
                         Ok(p) => p,
                         Err(_) => continue,
                     };
+                    // Tags of the selector apply to an untagged `exists`.
+                    let selector_exists = pat.exists.take();
+                    let field = format!("dom.{}", pat.selector_source);
                     let description = match description {
                         serde_json::Value::Object(d) => d,
                         _ => continue,
@@ -345,37 +755,47 @@ impl WappTechDomPatttern {
                                     Ok(())
                                 });
                                 match t {
-                                    Ok(p) => pat.exists = p,
+                                    Ok(_) if s.is_empty() => pat.exists = selector_exists.clone(),
+                                    Ok(p) => pat.exists = Some(p),
                                     Err(_) => continue,
                                 };
                             }
-                            "text" => {
-                                pat.text = v
-                                    .as_str()
-                                    .ok_or_else(|| anyhow!("Expect string, fonud {v}"))
-                                    .and_then(|t| {
-                                        Tagged::<Regex>::parse(t, |s| {
-                                            Regex::new(s).with_context(|| {
-                                                format!("Failed parsing regular expression {s}")
-                                            })
-                                        })
-                                    })
-                                    .ok();
+                            "text" if v.is_string() => {
+                                let field = format!("{field}.text");
+                                pat.text = to_pattern_vec(Some(v), &field, options, dropped).pop();
                             }
-                            "attributes" | "properties" => {
-                                if let Ok(x) = to_pattern_map(Some(v.clone())) {
+                            "text" => pat.text = None,
+                            "attributes" => {
+                                let field = format!("{field}.attributes");
+                                if let Ok(x) = to_pattern_map(Some(v), &field, options, dropped) {
                                     pat.attributes.extend(x);
                                 }
                             }
-                            "src" => {}
-                            x => panic!("{x}"),
+                            "properties" => {
+                                let field = format!("{field}.properties");
+                                if let Ok(x) = to_pattern_map(Some(v), &field, options, dropped) {
+                                    pat.properties.extend(x);
+                                }
+                            }
+                            "src" => {
+                                let pats = to_pattern_vec(
+                                    Some(v),
+                                    &format!("{field}.src"),
+                                    options,
+                                    dropped,
+                                );
+                                if !pats.is_empty() {
+                                    pat.attributes.push(("src".into(), pats));
+                                }
+                            }
+                            _ => continue,
                         }
                     }
                     vals.push(pat);
                 }
                 vals
             }
-            _ => panic!(),
+            _ => Vec::new(),
         }
     }
 }
@@ -383,8 +803,12 @@ impl WappTechDomPatttern {
 #[cfg(test)]
 mod tests {
     use anyhow::{anyhow, Error};
+    use regex::Regex;
 
-    use super::{to_vec, Tagged, WappTechVersionPattern, WappTechVersionValue};
+    use super::{
+        to_pattern_map, to_vec, Tagged, WappTech, WappTechVersionPattern, WappTechVersionValue,
+    };
+    use crate::WappLoadOptions;
 
     #[test]
     fn test_to_vec() {
@@ -417,6 +841,181 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_load_techs() {
+        let bytes = br#"{"A": {"cats": [1], "website": ""}, "B": {"cats": [], "website": "", "html": "b"}}"#;
+
+        let techs = WappTech::load_from_bytes(bytes, &WappLoadOptions::default()).unwrap();
+        assert_eq!(techs.len(), 2);
+        assert_eq!(techs["A"].cats, [1]);
+        assert_eq!(techs["B"].html.len(), 1);
+
+        #[cfg(feature = "fs")]
+        assert_eq!(
+            WappTech::load_from_reader(&bytes[..], &WappLoadOptions::default())
+                .unwrap()
+                .len(),
+            2
+        );
+
+        assert!(WappTech::load_from_bytes(
+            br#"{"A": {"cats": [], "website": ""}} x"#,
+            &WappLoadOptions::default()
+        )
+        .is_err());
+
+        #[cfg(feature = "http")]
+        {
+            let bytes = br#"{"A": {"cats": [], "website": "", "headers": "x"}}"#;
+            let err = WappTech::load_from_bytes(bytes, &WappLoadOptions::default()).unwrap_err();
+            assert!(format!("{err:#}").contains("Technology A"));
+        }
+    }
+
+    #[test]
+    fn test_load_unknown_fields() {
+        let bytes =
+            br#"{"A": {"cats": [], "website": "", "html": "a", "xPath": "//a", "aiHints": []}}"#;
+
+        let err = WappTech::load_from_bytes(bytes, &WappLoadOptions::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("Unknown fields: aiHints, xPath"));
+
+        let options = WappLoadOptions {
+            allow_unknown_fields: true,
+            ..Default::default()
+        };
+        let techs = WappTech::load_from_bytes(bytes, &options).unwrap();
+        assert_eq!(techs["A"].unknown_fields, ["aiHints", "xPath"]);
+        assert_eq!(techs["A"].html.len(), 1);
+    }
+
+    #[test]
+    fn test_load_dropped_patterns() {
+        let bytes = br#"{"A": {"cats": [], "website": "", "html": ["a{", "(?=b)"], "js": {"a.b": ["(", "c"]}}}"#;
+        let techs = WappTech::load_from_bytes(bytes, &WappLoadOptions::default()).unwrap();
+
+        let fields: Vec<_> = techs["A"]
+            .dropped_patterns
+            .iter()
+            .map(|d| (d.field.as_str(), d.pattern.as_str()))
+            .collect();
+        assert_eq!(fields, [("js.a.b", "("), ("html", "(?=b)")]);
+        assert_eq!(techs["A"].html.len(), 1);
+        assert_eq!(techs["A"].html[0].source(), "a{");
+        assert_eq!(techs["A"].html[0].inner.as_str(), r"a\{");
+        assert!(techs["A"].js[0].1[0].rewritten.is_none());
+    }
+
+    #[test]
+    fn test_load_excludes() {
+        let bytes = br#"{
+            "A": {"cats": [], "website": "", "excludes": "X"},
+            "B": {"cats": [], "website": "", "excludes": ["X", "Y\\;confidence:50"]}
+        }"#;
+        let techs = WappTech::load_from_bytes(bytes, &WappLoadOptions::default()).unwrap();
+
+        assert_eq!(techs["A"].excludes().collect::<Vec<_>>(), [("X", 100)]);
+        assert_eq!(
+            techs["B"].excludes().collect::<Vec<_>>(),
+            [("X", 100), ("Y", 50)]
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive_patterns() {
+        let bytes = br#"{"A": {"cats": [], "website": "", "html": "nginx", "url": "(?-i)^https"}}"#;
+
+        let techs = WappTech::load_from_bytes(bytes, &WappLoadOptions::default()).unwrap();
+        assert!(techs["A"].html[0].inner.is_match("NGINX"));
+        assert!(!techs["A"].url[0].inner.is_match("HTTPS://"));
+
+        let options = WappLoadOptions {
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let techs = WappTech::load_from_bytes(bytes, &options).unwrap();
+        assert!(!techs["A"].html[0].inner.is_match("NGINX"));
+    }
+
+    #[test]
+    fn test_to_pattern_map() {
+        use serde_json::json;
+
+        let options = WappLoadOptions::default();
+        let mut dropped = Vec::new();
+        assert!(to_pattern_map(None, "meta", &options, &mut dropped)
+            .unwrap()
+            .is_empty());
+        assert!(to_pattern_map(Some(json!("generator")), "meta", &options, &mut dropped).is_err());
+
+        let map = to_pattern_map(
+            Some(json!({
+            "generator": ["^WordPress(?: ([\\d.]+))?\\;version:\\1", "^WooCommerce"],
+            "shopify-checkout-api-token": "",
+            "broken": ["(", "ok"],
+            })),
+            "meta",
+            &options,
+            &mut dropped,
+        )
+        .unwrap();
+
+        let patterns = |key: &str| -> Vec<&str> {
+            map.iter()
+                .find(|(k, _)| k == key)
+                .unwrap()
+                .1
+                .iter()
+                .map(|p| p.inner.as_str())
+                .collect()
+        };
+        assert_eq!(
+            patterns("generator"),
+            ["^WordPress(?: ([\\d.]+))?", "^WooCommerce"]
+        );
+        assert_eq!(patterns("shopify-checkout-api-token"), [""]);
+        assert_eq!(patterns("broken"), ["ok"]);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(
+            (dropped[0].field.as_str(), dropped[0].pattern.as_str()),
+            ("meta.broken", "(")
+        );
+    }
+
+    #[test]
+    fn test_translate_js_regex() {
+        use super::translate_js_regex;
+
+        assert_eq!(translate_js_regex(r"^a{2,3}b{4}[{}]\{"), None);
+        let (source, changes) = translate_js_regex(r"function\(\){return a{2}}").unwrap();
+        assert_eq!(source, r"function\(\)\{return a{2}\}");
+        assert_eq!(changes.len(), 2);
+
+        let options = WappLoadOptions::default();
+        let pattern = Tagged::<Regex>::parse_pattern(r"\.init\({\s*key", &options).unwrap();
+        assert!(pattern.inner.is_match("sdk.init({ key: 1 })"));
+    }
+
+    #[test]
+    fn test_parse_pattern_version_groups() {
+        let options = WappLoadOptions::default();
+        let parse = |s: &str| Tagged::<Regex>::parse_pattern(s, &options).unwrap();
+
+        assert!(parse(r"nginx/([\d.]+)\;version:\1").version.is_some());
+        assert!(parse(r"nginx/([\d.]+)\;version:1.0").version.is_some());
+        for input in [
+            r"nginx/[\d.]+\;version:\1",
+            r"nginx/([\d.]+)\;version:\2?a:b",
+            r"nginx/([\d.]+)\;version:\1?\3:b",
+        ] {
+            let pattern = parse(input);
+            assert!(pattern.version.is_none());
+            assert!(pattern.inner.is_match("nginx/1.0"));
+        }
+        let unchecked = Tagged::<Regex>::parse_pattern_unchecked(r"nginx\;version:\1", &options);
+        assert!(unchecked.unwrap().version.is_some());
+    }
+
     #[test]
     fn test_parse_tagged() {
         assert_eq!(
@@ -425,6 +1024,7 @@ mod tests {
                 inner: "pattern".to_string(),
                 confidence: 100,
                 version: None,
+                rewritten: None,
             }
         );
 
@@ -434,6 +1034,7 @@ mod tests {
                 inner: "pattern".to_string(),
                 confidence: 80,
                 version: None,
+                rewritten: None,
             },
         );
 
@@ -443,6 +1044,7 @@ mod tests {
                 inner: "(pattern)".to_string(),
                 confidence: 100,
                 version: Some(WappTechVersionPattern::Always(WappTechVersionValue::Var(1))),
+                rewritten: None,
             },
         );
 
@@ -459,6 +1061,7 @@ mod tests {
                     true_expr: Some(WappTechVersionValue::Const("next".into())),
                     false_expr: Some(WappTechVersionValue::Var(2)),
                 }),
+                rewritten: None,
             },
         );
     }
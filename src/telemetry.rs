@@ -0,0 +1,110 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use serde::Serialize;
+
+use crate::WappCheckResult;
+
+/// Receives aggregate counters from an analyzer, so hosts can forward them to their own metrics system. The crate does
+/// no I/O of its own here; every method has a no-op default.
+///
+/// Timings are measured with [`std::time::Instant`], which is unavailable on `wasm32-unknown-unknown`; do not install a
+/// hook there.
+pub trait WappScanTelemetry: Send + Sync {
+    /// Called once for every page checked by the analyzer.
+    fn page_checked(&self, _stats: &WappScanStats) {}
+}
+
+/// Counters for one checked page.
+#[derive(Debug, Clone, Default)]
+pub struct WappScanStats {
+    /// [`WappAnalyzer::dataset_version`](crate::WappAnalyzer::dataset_version) of the analyzer.
+    pub dataset_version: Option<String>,
+    /// Time spent checking the page.
+    pub duration: Duration,
+    /// Number of technologies the page was checked for.
+    pub techs: usize,
+    /// Number of technologies detected.
+    pub detections: usize,
+    /// Time spent per detector, summed over all technologies.
+    pub detector_timings: Vec<(&'static str, Duration)>,
+}
+
+impl WappScanStats {
+    pub(crate) fn add_timing(&mut self, detector: &'static str, duration: Duration) {
+        match self
+            .detector_timings
+            .iter_mut()
+            .find(|(d, _)| *d == detector)
+        {
+            Some((_, total)) => *total += duration,
+            None => self.detector_timings.push((detector, duration)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct TelemetryHook(pub(crate) Arc<dyn WappScanTelemetry>);
+
+impl fmt::Debug for TelemetryHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TelemetryHook")
+    }
+}
+
+/// How often each technology was detected across the pages checked by an analyzer, to spot fingerprints that match
+/// suspiciously often in real traffic and are likely prone to false positives. See
+/// [`WappAnalyzer::set_count_detections`](crate::WappAnalyzer::set_count_detections).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WappDetectionCounts {
+    /// Number of pages checked.
+    pub pages: u64,
+    /// Number of pages each technology was detected on. Technologies never detected are left out.
+    pub techs: BTreeMap<String, u64>,
+}
+
+impl WappDetectionCounts {
+    /// Share of the checked pages the technology was detected on, from 0 to 1.
+    pub fn rate(&self, tech_name: &str) -> f64 {
+        match (self.techs.get(tech_name), self.pages) {
+            (Some(&count), pages) if pages > 0 => count as f64 / pages as f64,
+            _ => 0.0,
+        }
+    }
+
+    /// Detected technologies with their counts, the most frequent first.
+    pub fn most_frequent(&self) -> Vec<(&str, u64)> {
+        let mut techs: Vec<_> = self.techs.iter().map(|(k, &v)| (k.as_str(), v)).collect();
+        techs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        techs
+    }
+}
+
+/// Counts shared by an analyzer and its clones and subsets.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DetectionCounter(Arc<Mutex<WappDetectionCounts>>);
+
+impl DetectionCounter {
+    pub(crate) fn record(&self, results: &[WappCheckResult]) {
+        let mut counts = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        counts.pages += 1;
+        for r in results {
+            *counts.techs.entry(r.tech_name.clone()).or_default() += 1;
+        }
+    }
+
+    pub(crate) fn get(&self) -> WappDetectionCounts {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub(crate) fn take(&self) -> WappDetectionCounts {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::Error;
+
+use crate::WappAnalyzer;
+
+const CATEGORIES: &[u8] = include_bytes!("test_dataset/categories.json");
+const GROUPS: &[u8] = include_bytes!("test_dataset/groups.json");
+const TECHNOLOGIES: &[u8] = include_bytes!("test_dataset/technologies.json");
+
+/// Loads the embedded test dataset, see [`WappAnalyzer::new_test`].
+pub(crate) fn load() -> Result<WappAnalyzer, Error> {
+    WappAnalyzer::from_bytes(CATEGORIES, GROUPS, &[TECHNOLOGIES])
+}
+
+/// Builds an analyzer from inline technologies and no categories or groups, for tests that need definitions the test
+/// dataset lacks.
+#[cfg(test)]
+pub(crate) fn with_techs(techs: &[u8]) -> WappAnalyzer {
+    WappAnalyzer::from_bytes(b"{}", b"{}", &[techs]).expect("inline test technologies are valid")
+}
+
+impl WappAnalyzer {
+    /// Builds an analyzer from a small dataset embedded in the crate, for use in tests that should not depend on a full
+    /// dataset checkout. It has one `Test <Detector>` technology per detector: `Test URL`, `Test Header`,
+    /// `Test Cookie`, `Test DOM`, `Test DOM Properties`, `Test HTML`, `Test Text`, `Test Meta`, `Test Script`
+    /// (`scriptSrc`), `Test Scripts`, `Test CSS`, `Test JS`, `Test DNS`, `Test Cert Issuer` and `Test Probe`, plus
+    /// `Test Implied`, implied by `Test HTML`. See `src/test_dataset/technologies.json` for the exact patterns.
+    ///
+    /// The dataset is pinned: changing it is a breaking change for tests built on it.
+    pub fn new_test() -> Self {
+        load().expect("embedded test dataset is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{WappAnalyzer, WappRawPage};
+
+    const HTML: &str = r#"<html><head>
+<meta name="generator" content="TestMeta 2.0">
+<script src="/assets/test-script.min.js"></script>
+</head><body>
+<!-- test-html 3.1 -->
+<div id="test-dom" data-version="4.2"></div>
+<p>Powered by TestText</p>
+</body></html>"#;
+
+    #[test]
+    fn test_new_test() {
+        let analyzer = WappAnalyzer::new_test();
+        assert_eq!(analyzer.techs.len(), 16);
+        assert_eq!(analyzer.cats.len(), 3);
+        assert_eq!(analyzer.groups.len(), 2);
+
+        let headers = [
+            ("Server".to_string(), "TestServer/1.5".to_string()),
+            ("Set-Cookie".to_string(), "test_session=abc".to_string()),
+        ];
+        let page = WappRawPage::new(
+            Some("https://url.test/"),
+            Some(&headers),
+            Some(HTML.as_bytes()),
+        );
+        let results = analyzer.check(&page);
+        let detected = |name: &str| results.iter().find(|r| r.tech_name == name);
+
+        assert!(detected("Test URL").is_some());
+        assert!(detected("Test Implied").is_some());
+        assert_eq!(
+            detected("Test HTML").unwrap().version.as_deref(),
+            Some("3.1")
+        );
+        #[cfg(feature = "http")]
+        assert_eq!(
+            detected("Test Header").unwrap().version.as_deref(),
+            Some("1.5")
+        );
+        #[cfg(feature = "cookie")]
+        assert!(detected("Test Cookie").is_some());
+        #[cfg(feature = "scraper")]
+        {
+            assert_eq!(
+                detected("Test DOM").unwrap().version.as_deref(),
+                Some("4.2")
+            );
+            assert_eq!(detected("Test Text").unwrap().confidence, 50);
+            assert!(detected("Test Script").is_some());
+            assert_eq!(
+                detected("Test Meta").unwrap().version.as_deref(),
+                Some("2.0")
+            );
+        }
+    }
+}
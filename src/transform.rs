@@ -0,0 +1,367 @@
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{WappAnalyzer, WappCheckResult, WappResultSource};
+
+/// A post-processing stage applied to the results of a check, after detection. See
+/// [`WappAnalyzer::transforms_mut`].
+pub trait WappResultTransform: Debug + Send + Sync {
+    fn apply(&self, analyzer: &WappAnalyzer, results: &mut Vec<WappCheckResult>);
+}
+
+pub(crate) fn default_transforms() -> Vec<Arc<dyn WappResultTransform>> {
+    vec![
+        Arc::new(WappRequiresTransform),
+        Arc::new(WappImpliesTransform::default()),
+        Arc::new(WappExcludesTransform),
+        Arc::new(WappRankTransform),
+    ]
+}
+
+/// Drops technologies with `requires` when none of the required technologies was detected, e.g. themes of a CMS that
+/// is not in use, and technologies with `requiresCategory` when no other detected technology is in one of the required
+/// categories. Runs until no more results are dropped, so requirements can be chained.
+///
+/// Like upstream, requirements are checked against the resolved technologies: those detected and those they imply
+/// (see [`WappAnalyzer::implied_closure`]), so a theme requiring `PHP` stays when `WordPress` is detected. This stage
+/// goes before [`WappImpliesTransform`], so dropped technologies do not imply anything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WappRequiresTransform;
+
+impl WappResultTransform for WappRequiresTransform {
+    fn apply(&self, analyzer: &WappAnalyzer, results: &mut Vec<WappCheckResult>) {
+        let graph = analyzer.implies_graph();
+        loop {
+            let resolved: Vec<(&str, &str)> = results
+                .iter()
+                .flat_map(|r| {
+                    let implied = graph.closure(&r.tech_name).iter();
+                    std::iter::once(r.tech_name.as_str())
+                        .chain(implied.map(|(name, _)| name.as_str()))
+                        .map(|name| (r.tech_name.as_str(), name))
+                })
+                .collect();
+            let unmet: Vec<usize> = (0..results.len())
+                .filter(|&i| {
+                    let Some(tech) = analyzer.techs.get(&results[i].tech_name) else {
+                        return false;
+                    };
+                    let requires_met = tech.requires.is_empty()
+                        || tech
+                            .requires
+                            .iter()
+                            .any(|name| resolved.iter().any(|(_, r)| r == name));
+                    let requires_category_met = tech.requires_category.is_empty()
+                        || resolved
+                            .iter()
+                            .filter(|(source, name)| *source != tech.name && *name != tech.name)
+                            .filter_map(|(_, name)| analyzer.techs.get(*name))
+                            .any(|t| t.cats.iter().any(|c| tech.requires_category.contains(c)));
+                    !(requires_met && requires_category_met)
+                })
+                .collect();
+            if unmet.is_empty() {
+                break;
+            }
+
+            let mut i = 0;
+            results.retain(|_| {
+                i += 1;
+                !unmet.contains(&(i - 1))
+            });
+        }
+    }
+}
+
+/// Adds the technologies implied by detected ones (e.g. `PHP` for `WordPress`), with the confidence of the `implies`
+/// tag scaled by that of the implying technology: `PHP\;confidence:50` implied by a 60% WordPress detection gives PHP
+/// 30%. A technology implied several times, or also detected, keeps the highest of its confidences. Implied
+/// technologies imply others in turn, as looked up in [`WappAnalyzer::implied_closure`].
+///
+/// Consumers with a low tolerance for inferred technologies can replace the default stage in
+/// [`WappAnalyzer::transforms_mut`] with one that has a higher [`min_source_confidence`](Self::min_source_confidence)
+/// or a lower [`multiplier`](Self::multiplier).
+#[derive(Debug, Clone, Copy)]
+pub struct WappImpliesTransform {
+    /// Detections less confident than this do not imply anything. Defaults to 0.
+    pub min_source_confidence: i32,
+    /// Percentage applied to implied confidences on top of the scaling above. Defaults to 100, as upstream.
+    pub multiplier: i32,
+}
+
+impl Default for WappImpliesTransform {
+    fn default() -> Self {
+        Self {
+            min_source_confidence: 0,
+            multiplier: 100,
+        }
+    }
+}
+
+impl WappResultTransform for WappImpliesTransform {
+    fn apply(&self, analyzer: &WappAnalyzer, results: &mut Vec<WappCheckResult>) {
+        let graph = analyzer.implies_graph();
+        let implied: Vec<_> = results
+            .iter()
+            .filter(|r| r.confidence >= self.min_source_confidence)
+            .flat_map(|r| {
+                let confidence = r.confidence;
+                graph
+                    .closure(&r.tech_name)
+                    .iter()
+                    .map(move |(name, c)| (name, confidence * c / 100 * self.multiplier / 100))
+            })
+            .filter(|(_, confidence)| *confidence > 0)
+            .collect();
+
+        for (name, confidence) in implied {
+            let r = match results.iter_mut().position(|r| &r.tech_name == name) {
+                Some(i) => &mut results[i],
+                None => {
+                    results.push(WappCheckResult {
+                        tech_name: name.clone(),
+                        confidence: 0,
+                        version: None,
+                        version_captures: Vec::new(),
+                        version_candidates: Vec::new(),
+                        sources: Vec::new(),
+                        evidence: Vec::new(),
+                    });
+                    results.last_mut().unwrap()
+                }
+            };
+            r.confidence = r.confidence.max(confidence);
+            match r.sources.iter_mut().find(|s| s.detector == "implies") {
+                Some(source) => source.confidence = source.confidence.max(confidence),
+                None => r.sources.push(WappResultSource {
+                    detector: "implies",
+                    confidence,
+                }),
+            }
+        }
+    }
+}
+
+/// Of every two detected technologies where one excludes the other, drops the one with the lower confidence, or the
+/// excluded one if both are equally confident. Results are considered from the most confident down, so a dropped
+/// technology does not exclude anything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WappExcludesTransform;
+
+impl WappResultTransform for WappExcludesTransform {
+    fn apply(&self, analyzer: &WappAnalyzer, results: &mut Vec<WappCheckResult>) {
+        let mut order: Vec<usize> = (0..results.len()).collect();
+        order.sort_by(|&a, &b| {
+            (results[b].confidence, &results[a].tech_name)
+                .cmp(&(results[a].confidence, &results[b].tech_name))
+        });
+
+        let mut dropped = vec![false; results.len()];
+        for i in order {
+            if dropped[i] {
+                continue;
+            }
+            let Some(tech) = analyzer.techs.get(&results[i].tech_name) else {
+                continue;
+            };
+            for (excluded, _) in tech.excludes() {
+                let Some(j) = results.iter().position(|r| r.tech_name == excluded) else {
+                    continue;
+                };
+                if j == i || dropped[j] {
+                    continue;
+                }
+                if results[j].confidence > results[i].confidence {
+                    dropped[i] = true;
+                    break;
+                }
+                dropped[j] = true;
+            }
+        }
+
+        let mut dropped = dropped.into_iter();
+        results.retain(|_| !dropped.next().unwrap());
+    }
+}
+
+/// Drops results below the given confidence.
+#[derive(Debug, Clone, Copy)]
+pub struct WappMinConfidenceTransform(pub i32);
+
+impl WappResultTransform for WappMinConfidenceTransform {
+    fn apply(&self, _analyzer: &WappAnalyzer, results: &mut Vec<WappCheckResult>) {
+        results.retain(|r| r.confidence >= self.0);
+    }
+}
+
+/// Sorts results by descending confidence, then by name.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WappRankTransform;
+
+impl WappResultTransform for WappRankTransform {
+    fn apply(&self, _analyzer: &WappAnalyzer, results: &mut Vec<WappCheckResult>) {
+        results.sort_by(|a, b| {
+            b.confidence
+                .cmp(&a.confidence)
+                .then_with(|| a.tech_name.cmp(&b.tech_name))
+        });
+    }
+}
+
+/// Removes versions, their captures and the other version candidates from results, e.g. before sharing reports outside
+/// the organization.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WappRedactVersionsTransform;
+
+impl WappResultTransform for WappRedactVersionsTransform {
+    fn apply(&self, _analyzer: &WappAnalyzer, results: &mut Vec<WappCheckResult>) {
+        for r in results {
+            r.version = None;
+            r.version_captures.clear();
+            r.version_candidates.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{
+        WappImpliesTransform, WappMinConfidenceTransform, WappRankTransform,
+        WappRedactVersionsTransform,
+    };
+    use crate::{test_dataset, WappAnalyzer, WappRawPage};
+
+    #[test]
+    fn test_requires() {
+        let techs = br#"{
+            "CMS": {"cats": [], "website": "", "html": "cms"},
+            "Theme": {"cats": [], "website": "", "html": "theme", "requires": "CMS"},
+            "Plugin": {"cats": [], "website": "", "html": "plugin", "requires": ["Theme"]}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let detected = |html: &'static str| {
+            let mut names: Vec<_> = analyzer
+                .check(&WappRawPage::new(None, None, Some(html.as_bytes())))
+                .into_iter()
+                .map(|r| r.tech_name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(detected("cms theme plugin"), ["CMS", "Plugin", "Theme"]);
+        assert_eq!(detected("theme plugin"), Vec::<String>::new());
+        assert_eq!(detected("cms plugin"), ["CMS"]);
+    }
+
+    #[test]
+    fn test_requires_implied() {
+        let techs = br#"{
+            "CMS": {"cats": [], "website": "", "html": "cms", "implies": "Lang"},
+            "Lang": {"cats": [2], "website": ""},
+            "Theme": {"cats": [], "website": "", "html": "theme", "requires": "Lang", "implies": "Extra"},
+            "Widget": {"cats": [], "website": "", "html": "widget", "requiresCategory": 2},
+            "Extra": {"cats": [], "website": ""}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let detected = |html: &'static str| {
+            let mut names: Vec<_> = analyzer
+                .check(&WappRawPage::new(None, None, Some(html.as_bytes())))
+                .into_iter()
+                .map(|r| r.tech_name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(
+            detected("cms theme widget"),
+            ["CMS", "Extra", "Lang", "Theme", "Widget"]
+        );
+        assert_eq!(detected("theme widget"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_requires_category() {
+        let techs = br#"{
+            "CMS": {"cats": [1], "website": "", "html": "cms"},
+            "Server": {"cats": [2], "website": "", "html": "server"},
+            "Plugin": {"cats": [1], "website": "", "html": "plugin", "requiresCategory": [1]}
+        }"#;
+        let analyzer = test_dataset::with_techs(techs);
+        let detected = |html: &'static str| {
+            let mut names: Vec<_> = analyzer
+                .check(&WappRawPage::new(None, None, Some(html.as_bytes())))
+                .into_iter()
+                .map(|r| r.tech_name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(detected("cms plugin"), ["CMS", "Plugin"]);
+        assert_eq!(detected("server plugin"), ["Server"]);
+    }
+
+    #[test]
+    fn test_implies_options() {
+        let techs = br#"{
+            "CMS": {"cats": [], "website": "", "html": "cms", "implies": "Lang\\;confidence:50"},
+            "Plugin": {"cats": [], "website": "", "html": "plugin\\;confidence:40", "implies": "Cache"},
+            "Lang": {"cats": [], "website": ""},
+            "Cache": {"cats": [], "website": ""}
+        }"#;
+        let mut analyzer = test_dataset::with_techs(techs);
+        let page = WappRawPage::new(None, None, Some(b"cms plugin"));
+        let detected = |analyzer: &WappAnalyzer| {
+            let mut results: Vec<_> = analyzer
+                .check(&page)
+                .into_iter()
+                .map(|r| (r.tech_name, r.confidence))
+                .collect();
+            results.sort();
+            results
+        };
+
+        *analyzer.transforms_mut() = vec![Arc::new(WappImpliesTransform {
+            min_source_confidence: 50,
+            multiplier: 80,
+        })];
+        assert_eq!(
+            detected(&analyzer),
+            [
+                ("CMS".into(), 100),
+                ("Lang".into(), 40),
+                ("Plugin".into(), 40)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transforms() {
+        let mut analyzer = WappAnalyzer::new_test();
+        let page = WappRawPage::new(
+            Some("https://url.test/"),
+            None,
+            Some(b"<!-- test-html 3.1 --> Powered by TestText"),
+        );
+
+        analyzer.transforms_mut().clear();
+        let names: Vec<_> = analyzer
+            .check(&page)
+            .into_iter()
+            .map(|r| r.tech_name)
+            .collect();
+        assert!(!names.contains(&"Test Implied".to_string()));
+
+        let transforms = analyzer.transforms_mut();
+        transforms.push(Arc::new(WappMinConfidenceTransform(100)));
+        transforms.push(Arc::new(WappRankTransform));
+        transforms.push(Arc::new(WappRedactVersionsTransform));
+        let results = analyzer.check(&page);
+        let names: Vec<_> = results.iter().map(|r| r.tech_name.as_str()).collect();
+        assert_eq!(names, ["Test HTML", "Test URL"]);
+        assert!(results.iter().all(|r| r.version.is_none()));
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::{bail, Context, Error};
+
+/// Fields of a technology entry that hold detection patterns, as named in the dataset JSON.
+pub const DETECTOR_FIELDS: &[&str] = &[
+    "certIssuer",
+    "cookies",
+    "css",
+    "dns",
+    "dom",
+    "headers",
+    "html",
+    "js",
+    "meta",
+    "probe",
+    "robots",
+    "scriptSrc",
+    "scripts",
+    "text",
+    "url",
+    "xhr",
+];
+
+/// Rewrites a technologies JSON file, dropping every detector field not listed in `keep` (e.g. drop `js`, `dns` and
+/// `probe` for a static-HTML pipeline). Metadata and relationship fields are kept untouched, and so are technologies
+/// left without any detector, since they can still be implied by others.
+pub fn trim_technologies(bytes: &[u8], keep: &[&str]) -> Result<Vec<u8>, Error> {
+    for k in keep {
+        if !DETECTOR_FIELDS.contains(k) {
+            bail!("Unknown detector {k}");
+        }
+    }
+
+    let mut data = serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(bytes)
+        .context("Failed to parse JSON from bytes")?;
+
+    for tech in data.values_mut() {
+        if let serde_json::Value::Object(fields) = tech {
+            fields.retain(|k, _| {
+                !DETECTOR_FIELDS.contains(&k.as_str()) || keep.contains(&k.as_str())
+            });
+        }
+    }
+
+    serde_json::to_vec_pretty(&data).context("Failed to serialize JSON")
+}
+
+/// Trims every technologies file of a dataset directory with [`trim_technologies`], writing the result to the same
+/// layout under `dst_dir`. Categories and groups are copied as they are.
+#[cfg(feature = "fs")]
+pub fn trim_dir<P, Q>(src_dir: P, dst_dir: Q, keep: &[&str]) -> Result<(), Error>
+where
+    P: AsRef<std::path::Path>,
+    Q: AsRef<std::path::Path>,
+{
+    use std::{fs, iter};
+
+    let (src, dst) = (src_dir.as_ref(), dst_dir.as_ref());
+    fs::create_dir_all(dst.join("technologies"))
+        .with_context(|| format!("Failed to create directory {}", dst.to_string_lossy()))?;
+
+    for name in ["categories.json", "groups.json"] {
+        fs::copy(src.join(name), dst.join(name))
+            .with_context(|| format!("Failed to copy {name}"))?;
+    }
+
+    for c in iter::once('_').chain('a'..='z') {
+        let name = format!("technologies/{c}.json");
+        let bytes =
+            fs::read(src.join(&name)).with_context(|| format!("Failed to open file {name}"))?;
+        let trimmed =
+            trim_technologies(&bytes, keep).with_context(|| format!("Trimming {name}"))?;
+        fs::write(dst.join(&name), trimmed)
+            .with_context(|| format!("Failed to write file {name}"))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::trim_technologies;
+
+    #[test]
+    fn test_trim_technologies() {
+        let bytes = br#"{
+            "Tech": {"cats": [1], "website": "w", "implies": "PHP", "js": {"a": ""}, "dns": {}, "html": "<a", "url": "b"},
+            "Implied": {"cats": [2], "website": "w", "probe": {"/x": ""}}
+        }"#;
+
+        let trimmed = trim_technologies(bytes, &["html", "url"]).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&trimmed).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "Tech": {"cats": [1], "website": "w", "implies": "PHP", "html": "<a", "url": "b"},
+                "Implied": {"cats": [2], "website": "w"}
+            })
+        );
+
+        assert!(trim_technologies(bytes, &["scriptsrc"]).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use std::{net::IpAddr, sync::LazyLock};
+
+use scraper::{Html, Selector};
+
+/// Resolves `reference` against the absolute URL `base` the way browsers resolve `src` and `href` attributes. Only
+/// URLs of the form `scheme://authority/path` are supported as base; otherwise `reference` is returned unchanged.
+pub(crate) fn resolve(base: &str, reference: &str) -> String {
+    let reference = reference.trim();
+    if has_scheme(reference) {
+        return reference.into();
+    }
+    let Some(scheme_end) = base.find("://").filter(|&i| has_scheme(&base[..=i])) else {
+        return reference.into();
+    };
+    if let Some(rest) = reference.strip_prefix("//") {
+        return format!("{}//{rest}", &base[..=scheme_end]);
+    }
+
+    let authority_start = scheme_end + 3;
+    let authority_end = base[authority_start..]
+        .find(['/', '?', '#'])
+        .map_or(base.len(), |i| authority_start + i);
+    let origin = &base[..authority_end];
+    let rest = base[authority_end..].split('#').next().unwrap_or_default();
+    let path = rest.split('?').next().unwrap_or_default();
+
+    match reference.chars().next() {
+        None => format!("{origin}{rest}"),
+        Some('#') => format!("{origin}{rest}{reference}"),
+        Some('?') => format!("{origin}{path}{reference}"),
+        Some('/') => format!("{origin}{}", remove_dot_segments(reference)),
+        Some(_) => {
+            let dir = path.rfind('/').map_or("/", |i| &path[..=i]);
+            format!(
+                "{origin}{}",
+                remove_dot_segments(&format!("{dir}{reference}"))
+            )
+        }
+    }
+}
+
+/// The URL relative URLs of the document resolve against: its `<base href>` if any, else the URL it was loaded from.
+pub(crate) fn document_base(dom: &Html, url: Option<&str>) -> Option<String> {
+    static BASE_SELECTOR: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("base[href]").unwrap());
+
+    let href = dom
+        .select(&BASE_SELECTOR)
+        .next()
+        .and_then(|el| el.attr("href"));
+    match (href, url) {
+        (Some(href), Some(url)) => Some(resolve(url, href)),
+        (Some(href), None) => Some(href.to_string()),
+        (None, url) => url.map(String::from),
+    }
+}
+
+/// Second-level labels that country-code domains are registered under, e.g. `co` in `example.co.uk`.
+const SECOND_LEVEL_LABELS: [&str; 10] = [
+    "ac", "co", "com", "edu", "gov", "ltd", "net", "org", "plc", "sch",
+];
+
+/// The registrable domain of the host of an absolute URL, e.g. `example.co.uk` for `https://cdn.example.co.uk/`.
+/// Approximated without the public suffix list: the last two labels of the host, or three under the
+/// [second-level labels](SECOND_LEVEL_LABELS) of country codes. IP addresses are returned whole.
+pub(crate) fn site(url: &str) -> Option<&str> {
+    let host = crate::scope::host(url)?.trim_end_matches('.');
+    if host.parse::<IpAddr>().is_ok() {
+        return Some(host);
+    }
+    let mut labels = host.rsplit('.');
+    let under_second_level = labels.next().is_some_and(|tld| tld.len() == 2)
+        && labels.next().is_some_and(|label| {
+            SECOND_LEVEL_LABELS
+                .iter()
+                .any(|l| l.eq_ignore_ascii_case(label))
+        });
+    let labels = if under_second_level { 3 } else { 2 };
+    let start = host
+        .rmatch_indices('.')
+        .nth(labels - 1)
+        .map_or(0, |(i, _)| i + 1);
+    Some(&host[start..])
+}
+
+/// Whether `s` starts with a URL scheme followed by `:`, e.g. `https:` or `data:`.
+fn has_scheme(s: &str) -> bool {
+    let Some((scheme, _)) = s.split_once(':') else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Removes `.` and `..` segments from an absolute path, leaving any query or fragment as is.
+fn remove_dot_segments(input: &str) -> String {
+    let split = input.find(['?', '#']).unwrap_or(input.len());
+    let (path, suffix) = input.split_at(split);
+
+    let mut segments: Vec<&str> = Vec::new();
+    let mut parts = path.split('/').skip(1).peekable();
+    while let Some(part) = parts.next() {
+        let is_last = parts.peek().is_none();
+        match part {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(part),
+        }
+        if is_last && matches!(part, "." | "..") {
+            segments.push("");
+        }
+    }
+
+    format!("/{}{suffix}", segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve, site};
+
+    #[test]
+    fn test_site() {
+        assert_eq!(site("https://cdn.Example.com/x.js"), Some("Example.com"));
+        assert_eq!(site("https://a.b.example.co.uk/"), Some("example.co.uk"));
+        assert_eq!(site("https://example.co.uk./"), Some("example.co.uk"));
+        assert_eq!(site("https://www.example.de/"), Some("example.de"));
+        assert_eq!(site("https://localhost:8080/"), Some("localhost"));
+        assert_eq!(site("http://[::1]/"), Some("::1"));
+        assert_eq!(site("http://10.0.0.1/"), Some("10.0.0.1"));
+        assert_eq!(site("data:text/javascript,1"), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let base = "https://example.com/a/b/page.html?q=1#top";
+
+        assert_eq!(
+            resolve(base, "http://cdn.test/x.js"),
+            "http://cdn.test/x.js"
+        );
+        assert_eq!(resolve(base, "//cdn.test/x.js"), "https://cdn.test/x.js");
+        assert_eq!(resolve(base, "/x.js"), "https://example.com/x.js");
+        assert_eq!(
+            resolve(base, "x.js?v=2"),
+            "https://example.com/a/b/x.js?v=2"
+        );
+        assert_eq!(resolve(base, "../x.js"), "https://example.com/a/x.js");
+        assert_eq!(
+            resolve(base, "./c/../../../../x.js"),
+            "https://example.com/x.js"
+        );
+        assert_eq!(resolve(base, "../"), "https://example.com/a/");
+        assert_eq!(
+            resolve(base, "?v=2"),
+            "https://example.com/a/b/page.html?v=2"
+        );
+        assert_eq!(
+            resolve(base, "#x"),
+            "https://example.com/a/b/page.html?q=1#x"
+        );
+        assert_eq!(resolve(base, ""), "https://example.com/a/b/page.html?q=1");
+        assert_eq!(
+            resolve("https://example.com", "x.js"),
+            "https://example.com/x.js"
+        );
+        assert_eq!(resolve("not a url", "x.js"), "x.js");
+    }
+}
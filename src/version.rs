@@ -0,0 +1,251 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+};
+
+use crate::WappCheckResult;
+
+/// A version string parsed leniently for comparisons, e.g. `v5.2`, `5.2.0-beta1`, `8.1.2-1ubuntu4`.
+///
+/// Only the leading dotted numeric components take part in ordering, with missing components treated as zero (`5.2 ==
+/// 5.2.0`). A version with a pre-release suffix (`-rc1`, `beta`, ...) orders before the same release without one;
+/// build metadata and distribution suffixes (`+build`, `-1ubuntu4`) are ignored.
+#[derive(Debug, Clone)]
+pub struct WappVersion {
+    raw: String,
+    parts: Vec<u64>,
+    pre_release: bool,
+}
+
+impl WappVersion {
+    /// Parses the leading numeric part of `input`. Returns `None` if it does not start with a number (after an optional
+    /// `v`).
+    pub fn parse(input: &str) -> Option<Self> {
+        let trimmed = input.trim();
+        let s = trimmed.strip_prefix(['v', 'V']).unwrap_or(trimmed);
+
+        let mut parts = Vec::new();
+        let mut rest = s;
+        loop {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if end == 0 {
+                break;
+            }
+            parts.push(rest[..end].parse().ok()?);
+            rest = &rest[end..];
+            match rest.strip_prefix('.') {
+                Some(r) if r.starts_with(|c: char| c.is_ascii_digit()) => rest = r,
+                _ => break,
+            }
+        }
+        if parts.is_empty() {
+            return None;
+        }
+
+        let suffix = rest
+            .trim_start_matches(['-', '.', '_'])
+            .to_ascii_lowercase();
+        let pre_release = ["alpha", "beta", "rc", "pre", "dev"]
+            .iter()
+            .any(|p| suffix.starts_with(p))
+            // Short forms such as `3.0b2`.
+            || (suffix.starts_with(['a', 'b']) && suffix[1..].starts_with(|c: char| c.is_ascii_digit()));
+
+        Some(Self {
+            raw: input.to_string(),
+            parts,
+            pre_release,
+        })
+    }
+
+    /// The string this version was parsed from.
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// Numeric components, e.g. `[5, 2, 1]`.
+    pub fn parts(&self) -> &[u64] {
+        &self.parts
+    }
+
+    pub fn is_pre_release(&self) -> bool {
+        self.pre_release
+    }
+
+    /// `self >= other`. Returns `false` if `other` cannot be parsed.
+    pub fn is_at_least(&self, other: &str) -> bool {
+        Self::parse(other).is_some_and(|o| *self >= o)
+    }
+
+    /// `self < other`. Returns `false` if `other` cannot be parsed.
+    pub fn is_below(&self, other: &str) -> bool {
+        Self::parse(other).is_some_and(|o| *self < o)
+    }
+
+    /// `min <= self < max`. Returns `false` if either bound cannot be parsed.
+    pub fn is_in_range(&self, min: &str, max: &str) -> bool {
+        self.is_at_least(min) && self.is_below(max)
+    }
+}
+
+impl PartialEq for WappVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for WappVersion {}
+
+impl PartialOrd for WappVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for WappVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.parts.len().max(other.parts.len());
+        let part = |v: &Self, i: usize| v.parts.get(i).copied().unwrap_or(0);
+        (0..len)
+            .map(|i| part(self, i).cmp(&part(other, i)))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+            // A pre-release orders before its release.
+            .then_with(|| other.pre_release.cmp(&self.pre_release))
+    }
+}
+
+impl Display for WappVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// A detected version string, with its parsed form when it parses.
+///
+/// Detected versions order from the least to the most preferred: unparsable ones first (by their string), then by
+/// [`WappVersion`] order, more specific versions ranking above equal but shorter ones (`5.2.0 > 5.2`), then by string,
+/// so that picking the greatest of several candidates is deterministic.
+#[derive(Debug, Clone)]
+pub struct WappDetectedVersion {
+    pub raw: String,
+    pub parsed: Option<WappVersion>,
+}
+
+impl WappDetectedVersion {
+    pub fn new(raw: impl Into<String>) -> Self {
+        let raw = raw.into();
+        let parsed = WappVersion::parse(&raw);
+        Self { raw, parsed }
+    }
+
+    fn sort_key(&self) -> (Option<(&WappVersion, usize)>, &str) {
+        let parsed = self.parsed.as_ref().map(|v| (v, v.parts.len()));
+        (parsed, &self.raw)
+    }
+}
+
+impl PartialEq for WappDetectedVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl Eq for WappDetectedVersion {}
+
+impl PartialOrd for WappDetectedVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for WappDetectedVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl Display for WappDetectedVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl WappCheckResult {
+    /// The detected version parsed for comparisons, e.g. to flag `PHP < 8`.
+    pub fn parsed_version(&self) -> Option<WappVersion> {
+        self.version.as_deref().and_then(WappVersion::parse)
+    }
+
+    /// The most specific, then highest of the [`version_candidates`](Self::version_candidates), see
+    /// [`WappDetectedVersion`]. Unlike [`version`](Self::version), which like upstream comes from the most confident
+    /// pattern, this does not depend on pattern confidences.
+    pub fn detected_version(&self) -> Option<WappDetectedVersion> {
+        let candidates = self.version_candidates.iter().chain(&self.version);
+        candidates.map(WappDetectedVersion::new).max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WappDetectedVersion, WappVersion};
+    use crate::WappCheckResult;
+
+    fn v(s: &str) -> WappVersion {
+        WappVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(v("v5.2").parts(), [5, 2]);
+        assert_eq!(v("8.1.2-1ubuntu4").parts(), [8, 1, 2]);
+        assert_eq!(v("1.2.").parts(), [1, 2]);
+        assert!(v("3.0.0-rc1").is_pre_release());
+        assert!(v("3.0b2").is_pre_release());
+        assert!(!v("3.0+build5").is_pre_release());
+        assert!(!v("3.0-build5").is_pre_release());
+        assert!(WappVersion::parse("latest").is_none());
+        assert!(WappVersion::parse("").is_none());
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(v("5.2"), v("5.2.0"));
+        assert!(v("5.10") > v("5.9"));
+        assert!(v("3.0.0-rc1") < v("3.0.0"));
+        assert!(v("3.0.0-rc1") > v("2.9"));
+        assert!(v("8.1.2-1ubuntu4") == v("8.1.2"));
+
+        assert!(v("5.2.1").is_at_least("5.2"));
+        assert!(!v("5.1").is_at_least("5.2"));
+        assert!(v("7.4.33").is_below("8"));
+        assert!(v("7.4").is_in_range("7", "8"));
+        assert!(!v("8.0").is_in_range("7", "8"));
+        assert!(!v("8.0").is_at_least("unknown"));
+    }
+
+    #[test]
+    fn test_detected_version() {
+        let d = WappDetectedVersion::new;
+        assert!(d("5.2.0") > d("5.2"));
+        assert!(d("5.2.1") > d("5.2.0"));
+        assert!(d("6") > d("5.2.1"));
+        assert!(d("1.0") > d("latest"));
+        assert!(d("latest") > d("dev"));
+        assert!(d("v5.2") > d("5.2"));
+        assert_eq!(d("5.2.0").parsed.unwrap().parts(), [5, 2, 0]);
+
+        let result = WappCheckResult {
+            tech_name: "A".into(),
+            confidence: 100,
+            version: Some("5.2".into()),
+            version_captures: Vec::new(),
+            version_candidates: vec!["5.2".into(), "latest".into(), "5.2.1".into()],
+            sources: Vec::new(),
+            evidence: Vec::new(),
+        };
+        assert_eq!(result.detected_version().unwrap().raw, "5.2.1");
+    }
+}
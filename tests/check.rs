@@ -0,0 +1,168 @@
+#![cfg(feature = "fs")]
+
+use std::path::{Path, PathBuf};
+
+use webappanalyzer::{WappAnalyzer, WappPage};
+
+#[cfg(feature = "cookie")]
+use cookie::Cookie;
+
+#[cfg(feature = "http")]
+use http::{HeaderMap, HeaderName, HeaderValue};
+
+#[cfg(feature = "scraper")]
+use scraper::Html;
+
+const ADVERSARIAL_HTML: &str = r#"<!DOCTYPE html>
+<html><head>
+<meta name="generator" content="">
+<meta name="generator">
+<meta content="\1?\2:">
+<script src=""></script>
+<script src="//">x</script>
+<script>\u0000 ${{ }} (((((</script>
+<link rel="stylesheet" href="\\">
+</head><body>
+<div id="" class="" data-x="&#0;"><p><p><p></div></span>
+<a href="javascript:void(0)">🦀</a>
+<!-- <script src="commented.js"></script>
+"#;
+
+struct AdversarialPage {
+    url: String,
+    #[cfg(feature = "http")]
+    headers: HeaderMap,
+    #[cfg(feature = "cookie")]
+    cookies: Vec<Cookie<'static>>,
+    #[cfg(feature = "scraper")]
+    dom: Html,
+    html: String,
+}
+
+impl AdversarialPage {
+    /// Builds a page that presents every header and cookie name known to the analyzer with empty, binary and oversized
+    /// values, together with malformed markup. Cookie names are read as written from the technologies files in `dir`.
+    #[allow(unused_variables)]
+    fn new(analyzer: &WappAnalyzer, dir: &Path) -> Self {
+        #[cfg(feature = "http")]
+        let headers = {
+            let mut headers = HeaderMap::new();
+            for tech in analyzer.techs.values() {
+                for (key, _) in &tech.headers {
+                    let Ok(name) = HeaderName::from_bytes(key.as_bytes()) else {
+                        continue;
+                    };
+                    headers.append(&name, HeaderValue::from_static(""));
+                    headers.append(&name, HeaderValue::from_bytes(b"\xff\xfe").unwrap());
+                    headers.append(&name, HeaderValue::from_str(&"9".repeat(4096)).unwrap());
+                }
+            }
+            headers
+        };
+
+        #[cfg(feature = "cookie")]
+        let cookies = dataset_cookie_names(dir)
+            .into_iter()
+            .flat_map(|name| {
+                [
+                    Cookie::new(name.clone(), ""),
+                    Cookie::new(name, "\u{1F980}"),
+                ]
+            })
+            .collect();
+
+        Self {
+            url: "http://[::1]:0/\u{1F980}?a=\\1#".into(),
+            #[cfg(feature = "http")]
+            headers,
+            #[cfg(feature = "cookie")]
+            cookies,
+            #[cfg(feature = "scraper")]
+            dom: Html::parse_document(ADVERSARIAL_HTML),
+            html: ADVERSARIAL_HTML.into(),
+        }
+    }
+}
+
+impl WappPage for AdversarialPage {
+    fn url(&self) -> Option<&str> {
+        Some(&self.url)
+    }
+
+    #[cfg(feature = "http")]
+    fn headers(&self) -> Option<&HeaderMap> {
+        Some(&self.headers)
+    }
+
+    #[cfg(feature = "cookie")]
+    fn cookies(&self) -> Option<&[Cookie<'_>]> {
+        Some(&self.cookies)
+    }
+
+    #[cfg(feature = "scraper")]
+    fn dom(&self) -> Option<&Html> {
+        Some(&self.dom)
+    }
+
+    fn html(&self) -> Option<&str> {
+        Some(&self.html)
+    }
+
+    fn text(&self) -> Option<&str> {
+        Some(&self.html)
+    }
+}
+
+/// The keys of every `cookies` object in the technologies files of a dataset.
+#[cfg(feature = "cookie")]
+fn dataset_cookie_names(dir: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    for entry in std::fs::read_dir(dir.join("technologies")).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let techs: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        for tech in techs
+            .as_object()
+            .into_iter()
+            .flat_map(|techs| techs.values())
+        {
+            if let Some(cookies) = tech["cookies"].as_object() {
+                names.extend(cookies.keys().cloned());
+            }
+        }
+    }
+    names
+}
+
+fn test_check_dataset(dataset: &str) {
+    let dir = PathBuf::from_iter(["./tests/webappanalyzer-data", dataset, "src"]);
+    let wapp_analyzer = WappAnalyzer::from_dir(&dir).unwrap();
+
+    let page = AdversarialPage::new(&wapp_analyzer, &dir);
+    #[cfg(feature = "cookie")]
+    for tech in wapp_analyzer.techs.values() {
+        tech.check_cookies(&page.cookies);
+    }
+    wapp_analyzer.check(&page);
+}
+
+#[test]
+#[ignore]
+fn test_check_wappalyzer_mit() {
+    test_check_dataset("wappalyzer-mit");
+}
+
+#[test]
+#[ignore]
+fn test_check_wappalyzer() {
+    test_check_dataset("wappalyzer");
+}
+
+#[test]
+#[ignore]
+fn test_check_webappanalyzer() {
+    test_check_dataset("webappanalyzer");
+}
@@ -0,0 +1,182 @@
+//! Compares detections against recorded upstream Wappalyzer CLI outputs.
+//!
+//! Each fixture is a directory under `tests/golden/` containing:
+//!
+//! - `page.json`: the page that was scanned, `{"url": "...", "headers": [["name", "value"], ...], "html": "..."}`
+//! - `upstream.json`: the output of the upstream CLI for that page,
+//!   `{"technologies": [{"name": "...", "version": "...", ...}, ...]}`
+//!
+//! A fixture fails when too few of the upstream detections are found (recall), when too many of the detections are not
+//! in the upstream output (precision), or when a technology is found with another version than upstream reported.
+//!
+//! Fixtures are checked against the `wappalyzer` dataset, which is the one upstream outputs are recorded with.
+
+#![cfg(feature = "fs")]
+
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use serde_json::Value;
+use webappanalyzer::{WappAnalyzer, WappPage};
+
+#[cfg(feature = "http")]
+use http::{HeaderMap, HeaderName, HeaderValue};
+
+#[cfg(feature = "scraper")]
+use scraper::Html;
+
+/// Minimum share of upstream detections that must also be detected by this crate, per fixture.
+const MIN_RECALL: f64 = 0.8;
+
+/// Minimum share of the detections of this crate that upstream also reported, per fixture.
+const MIN_PRECISION: f64 = 0.8;
+
+struct GoldenPage {
+    url: Option<String>,
+    #[cfg(feature = "http")]
+    headers: HeaderMap,
+    #[cfg(feature = "scraper")]
+    dom: Html,
+    html: String,
+}
+
+impl GoldenPage {
+    fn from_json(page: &Value) -> Self {
+        let html = page["html"].as_str().unwrap_or_default().to_string();
+
+        #[cfg(feature = "http")]
+        let headers = {
+            let mut headers = HeaderMap::new();
+            for pair in page["headers"].as_array().into_iter().flatten() {
+                let (Some(k), Some(v)) = (pair[0].as_str(), pair[1].as_str()) else {
+                    continue;
+                };
+                if let (Ok(k), Ok(v)) = (
+                    HeaderName::from_bytes(k.as_bytes()),
+                    HeaderValue::from_str(v),
+                ) {
+                    headers.append(k, v);
+                }
+            }
+            headers
+        };
+
+        Self {
+            url: page["url"].as_str().map(String::from),
+            #[cfg(feature = "http")]
+            headers,
+            #[cfg(feature = "scraper")]
+            dom: Html::parse_document(&html),
+            html,
+        }
+    }
+}
+
+impl WappPage for GoldenPage {
+    fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    #[cfg(feature = "http")]
+    fn headers(&self) -> Option<&HeaderMap> {
+        Some(&self.headers)
+    }
+
+    #[cfg(feature = "scraper")]
+    fn dom(&self) -> Option<&Html> {
+        Some(&self.dom)
+    }
+
+    fn html(&self) -> Option<&str> {
+        Some(&self.html)
+    }
+}
+
+fn read_json(path: PathBuf) -> Value {
+    let bytes = fs::read(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    serde_json::from_slice(&bytes).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+/// Share of `part` in `total`, 1 when there is nothing to share.
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+#[test]
+#[ignore]
+fn test_golden_upstream_parity() {
+    let fixtures =
+        fs::read_dir("./tests/golden").expect("No golden fixtures found in tests/golden");
+
+    let dir = PathBuf::from_iter(["./tests/webappanalyzer-data", "wappalyzer", "src"]);
+    let wapp_analyzer = WappAnalyzer::from_dir(dir).unwrap();
+
+    let mut fixture_count = 0;
+    let mut failures = Vec::new();
+
+    for fixture in fixtures {
+        let fixture = fixture.unwrap().path();
+        fixture_count += 1;
+        let page = GoldenPage::from_json(&read_json(fixture.join("page.json")));
+        let upstream = read_json(fixture.join("upstream.json"));
+
+        let expected: HashMap<&str, Option<&str>> = upstream["technologies"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|t| {
+                let version = t["version"].as_str().filter(|v| !v.is_empty());
+                Some((t["name"].as_str()?, version))
+            })
+            .collect();
+        let detected: HashMap<String, Option<String>> = wapp_analyzer
+            .check(&page)
+            .into_iter()
+            .map(|r| (r.tech_name, r.version))
+            .collect();
+
+        let matched = expected
+            .keys()
+            .filter(|t| detected.contains_key(**t))
+            .count();
+        let recall = ratio(matched, expected.len());
+        let precision = ratio(matched, detected.len());
+
+        let mut missing: Vec<_> = expected
+            .keys()
+            .filter(|t| !detected.contains_key(**t))
+            .collect();
+        missing.sort();
+        let mut extra: Vec<_> = detected
+            .keys()
+            .filter(|t| !expected.contains_key(t.as_str()))
+            .collect();
+        extra.sort();
+        let mut wrong_versions: Vec<_> = expected
+            .iter()
+            .filter_map(|(name, version)| {
+                let found = detected.get(*name)?.as_deref();
+                (version.is_some() && found != *version)
+                    .then(|| format!("{name} {found:?} != {version:?}"))
+            })
+            .collect();
+        wrong_versions.sort();
+
+        if recall < MIN_RECALL || precision < MIN_PRECISION || !wrong_versions.is_empty() {
+            failures.push(format!(
+                "{}: recall {recall:.2}, precision {precision:.2}, missing {missing:?}, extra {extra:?}, \
+                 versions {wrong_versions:?}",
+                fixture.display()
+            ));
+        }
+    }
+
+    assert!(
+        fixture_count > 0,
+        "No golden fixtures found in tests/golden"
+    );
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "fs")]
+
 use std::path::PathBuf;
 
 use webappanalyzer::WappAnalyzer;
@@ -28,3 +30,32 @@ fn test_parse_wappalyzer() {
 fn test_parse_webappanalyzer() {
     test_parse_dataset("webappanalyzer");
 }
+
+#[test]
+fn test_parse_partial_reports_every_file() {
+    let dir = std::env::temp_dir().join(format!("webappanalyzer-partial-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("categories.json"), "{}").unwrap();
+    std::fs::write(dir.join("a.json"), r#"{"A": {"cats": [], "website": ""}}"#).unwrap();
+    std::fs::write(dir.join("b.json"), "{ not json").unwrap();
+
+    let files = ["a.json", "b.json", "c.json"].map(|f| dir.join(f));
+    let (wapp_analyzer, report) = WappAnalyzer::from_files_partial(
+        dir.join("categories.json"),
+        dir.join("groups.json"),
+        files.into_iter(),
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(wapp_analyzer.techs.contains_key("A"));
+    let failed: Vec<_> = report
+        .errors
+        .iter()
+        .map(|e| e.path.file_name().unwrap())
+        .collect();
+    assert_eq!(failed, ["groups.json", "b.json", "c.json"]);
+
+    let error = report.into_error().unwrap().to_string();
+    assert!(error.starts_with("Failed to load 3 file(s)"));
+    assert!(error.contains("b.json"));
+}